@@ -0,0 +1,488 @@
+use crate::cli::walk;
+use crate::env::config::{random_string, Config};
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::{establish, establish_checked, migration};
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use tar::{Archive, Builder, Header};
+
+/// increased when the layout of the archive changes
+const BACKUP_VERSION: u32 = 1;
+/// first entry of every archive
+const MANIFEST: &str = "manifest.json";
+const DATABASE: &str = "db.surql";
+/// prefix of the backed up files inside of the archive
+const FILES: &str = "files/";
+/// folders inside of root_folder which are backed up
+const FOLDERS: [&str; 4] = ["covers", "mangas", "users", "external"];
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    /// referenced by incremental backups
+    id: String,
+    created: String,
+    server_version: String,
+    /// id of the backup this one is based on
+    base: Option<String>,
+    database: FileEntry,
+    /// every file at the time of the backup. relative to root_folder
+    files: BTreeMap<String, FileEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct FileEntry {
+    size: u64,
+    sha256: String,
+    /// id of the backup which contains the file
+    archive: String,
+}
+
+/// sha256 and size of everything written
+struct HashWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl<W: Write> HashWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    }
+
+    fn finish(self) -> (u64, String) {
+        (self.size, format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn hash_file(path: &Path) -> io::Result<(u64, String)> {
+    let mut writer = HashWriter::new(io::sink());
+    io::copy(&mut File::open(path)?, &mut writer)?;
+    Ok(writer.finish())
+}
+
+/// rejects absolute paths and `..`
+fn safe_path(rel: &str) -> ApiResult<&Path> {
+    let path = Path::new(rel);
+    match path.components().all(|v| matches!(v, Component::Normal(_))) {
+        true => Ok(path),
+        false => Err(ApiError::invalid_input(format!(
+            "Illegal path in archive: {}",
+            rel
+        ))),
+    }
+}
+
+fn read_manifest(archive: &Path) -> ApiResult<Manifest> {
+    let mut archive = Archive::new(File::open(archive)?);
+    let entry = archive.entries()?.next().transpose()?;
+    let manifest: Manifest = match entry {
+        Some(entry) if entry.path()?.as_ref() == Path::new(MANIFEST) => {
+            serde_json::from_reader(entry)?
+        }
+        _ => return Err(ApiError::invalid_input("Archive has no manifest")),
+    };
+    if manifest.version > BACKUP_VERSION {
+        return Err(ApiError::invalid_input(format!(
+            "Backup version {} is not supported",
+            manifest.version
+        )));
+    }
+    Ok(manifest)
+}
+
+fn append_bytes(builder: &mut Builder<File>, name: &str, data: &[u8]) -> io::Result<()> {
+    let mut header = Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, name, data)
+}
+
+/// archive contains the manifest, a database export and every file which isn't part of `base`
+pub async fn backup(config: &Config, archive: &Path, base: Option<&Path>) -> ApiResult<()> {
+    let root = &config.root_folder;
+    let base = base.map(read_manifest).transpose()?;
+    let id = random_string(16).replace(|c: char| !c.is_ascii_alphanumeric(), "0");
+
+    let mut files = BTreeMap::new();
+    let mut contained = vec![];
+    for folder in FOLDERS {
+        for rel in walk(root, folder)? {
+            let (size, sha256) = hash_file(&root.join(&rel))?;
+            let archive = match base.as_ref().and_then(|v| v.files.get(&rel)) {
+                Some(old) if old.size == size && old.sha256 == sha256 => old.archive.clone(),
+                _ => {
+                    contained.push(rel.clone());
+                    id.clone()
+                }
+            };
+            files.insert(
+                rel,
+                FileEntry {
+                    size,
+                    sha256,
+                    archive,
+                },
+            );
+        }
+    }
+
+    let export = root.join("temp").join(format!("backup-{}.surql", id));
+    let conn = establish_checked(root.clone(), false).await?;
+    conn.export(&export).await?;
+    drop(conn);
+    let (size, sha256) = hash_file(&export)?;
+
+    let manifest = Manifest {
+        version: BACKUP_VERSION,
+        id: id.clone(),
+        created: Utc::now().to_rfc3339(),
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        base: base.map(|v| v.id),
+        database: FileEntry {
+            size,
+            sha256,
+            archive: id,
+        },
+        files,
+    };
+
+    let part = archive.with_extension("part");
+    let mut builder = Builder::new(File::create(&part)?);
+    append_bytes(
+        &mut builder,
+        MANIFEST,
+        &serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    builder.append_path_with_name(&export, DATABASE)?;
+    for rel in &contained {
+        builder.append_path_with_name(root.join(rel), format!("{}{}", FILES, rel))?;
+    }
+    builder.into_inner()?.sync_all()?;
+    fs::rename(part, archive)?;
+    fs::remove_file(export)?;
+    info!(
+        "Backed up {} of {} files to {}",
+        contained.len(),
+        manifest.files.len(),
+        archive.display()
+    );
+    Ok(())
+}
+
+/// copies the files of the archive which are needed by `target` into `staging`.
+/// fails if a file is missing or its checksum doesn't match
+fn extract(
+    archive: &Path,
+    manifest: &Manifest,
+    target: &Manifest,
+    staging: &Path,
+) -> ApiResult<()> {
+    let mut needed: HashMap<&str, &FileEntry> = target
+        .files
+        .iter()
+        .filter(|(_, v)| v.archive == manifest.id)
+        .map(|(k, v)| (k.as_str(), v))
+        .collect();
+    let mut database = manifest.id == target.id;
+    if needed.is_empty() && !database {
+        return Ok(());
+    }
+    let mut tar = Archive::new(File::open(archive)?);
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        let (dest, expected) = if name == DATABASE && database {
+            database = false;
+            (staging.join(DATABASE), &target.database)
+        } else if let Some(rel) = name.strip_prefix(FILES) {
+            match needed.remove(rel) {
+                Some(expected) => (staging.join(safe_path(rel)?), expected),
+                None => continue,
+            }
+        } else {
+            continue;
+        };
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut writer = HashWriter::new(File::create(&dest)?);
+        io::copy(&mut entry, &mut writer)?;
+        let (size, sha256) = writer.finish();
+        if size != expected.size || sha256 != expected.sha256 {
+            return Err(ApiError::invalid_input(format!(
+                "{} in {} is corrupted",
+                name,
+                archive.display()
+            )));
+        }
+    }
+    if database {
+        return Err(ApiError::invalid_input(format!(
+            "{} has no database export",
+            archive.display()
+        )));
+    }
+    if !needed.is_empty() {
+        return Err(ApiError::invalid_input(format!(
+            "{} files are missing in {}",
+            needed.len(),
+            archive.display()
+        )));
+    }
+    Ok(())
+}
+
+/// replaces the folders in root with the ones in staging. reverts on failure
+fn swap(root: &Path, staging: &Path) -> io::Result<()> {
+    let old = staging.join("old");
+    fs::create_dir_all(&old)?;
+    let mut swapped = vec![];
+    for name in FOLDERS.into_iter().chain(["db"]) {
+        let res = (|| {
+            if root.join(name).exists() {
+                fs::rename(root.join(name), old.join(name))?;
+            }
+            fs::rename(staging.join(name), root.join(name))
+        })();
+        if let Err(e) = res {
+            for name in swapped.into_iter().chain([name]) {
+                if old.join(name).exists() {
+                    let _ = fs::remove_dir_all(root.join(name));
+                    if let Err(e) = fs::rename(old.join(name), root.join(name)) {
+                        warn!("Failed to revert {}: {}", name, e);
+                    }
+                }
+            }
+            return Err(e);
+        }
+        swapped.push(name);
+    }
+    fs::remove_dir_all(old)
+}
+
+/// verifies and unpacks everything into a staging folder before the current data is replaced.
+/// the live database stays open until the folders are swapped, so a running server makes it fail
+pub async fn restore(config: &Config, archives: &[PathBuf]) -> ApiResult<()> {
+    let root = &config.root_folder;
+    let live = establish(root.clone(), false).await.map_err(|e| {
+        ApiError::invalid_input(format!(
+            "Failed to open the database. Is the server still running? {}",
+            e
+        ))
+    })?;
+    let mut manifests = vec![];
+    for archive in archives {
+        manifests.push(read_manifest(archive)?);
+    }
+    let target = manifests
+        .last()
+        .ok_or(ApiError::invalid_input("No archive given"))?;
+    for entry in target.files.values() {
+        if !manifests.iter().any(|v| v.id == entry.archive) {
+            return Err(ApiError::invalid_input(format!(
+                "Backup {} is missing",
+                entry.archive
+            )));
+        }
+    }
+
+    let staging = root.join(format!("restore-{}", target.id));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    for folder in FOLDERS {
+        fs::create_dir_all(staging.join(folder))?;
+    }
+    let res: ApiResult<()> = async {
+        for (archive, manifest) in archives.iter().zip(&manifests) {
+            extract(archive, manifest, target, &staging)?;
+        }
+        let conn = establish(staging.clone(), false).await?;
+        conn.import(staging.join(DATABASE)).await?;
+        // a backup of a newer server can't be opened by this one
+        migration::check_version(&conn).await?;
+        drop(conn);
+        fs::remove_file(staging.join(DATABASE))?;
+        swap(root, &staging)?;
+        Ok(())
+    }
+    .await;
+    drop(live);
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        warn!("Failed to remove {}: {}", staging.display(), e);
+    }
+    res?;
+    info!(
+        "Restored backup {} from {} with {} files",
+        target.id,
+        target.created,
+        target.files.len()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_folder(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", name, nanoid::nanoid!()));
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn entry(data: &[u8]) -> FileEntry {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        FileEntry {
+            size: data.len() as u64,
+            sha256: format!("{:x}", hasher.finalize()),
+            archive: "a".to_string(),
+        }
+    }
+
+    fn manifest(files: &[(&str, &[u8])]) -> Manifest {
+        Manifest {
+            version: BACKUP_VERSION,
+            id: "a".to_string(),
+            created: String::new(),
+            server_version: String::new(),
+            base: None,
+            database: entry(b"db"),
+            files: files
+                .iter()
+                .map(|(k, v)| (k.to_string(), entry(v)))
+                .collect(),
+        }
+    }
+
+    /// names are written without the checks of the tar crate
+    fn archive(folder: &Path, entries: &[(&str, &[u8])]) -> PathBuf {
+        let path = folder.join("backup.tar");
+        let mut builder = Builder::new(File::create(&path).unwrap());
+        for (name, data) in entries {
+            let mut header = Header::new_gnu();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, *data).unwrap();
+        }
+        builder.into_inner().unwrap();
+        path
+    }
+
+    #[test]
+    fn safe_paths() {
+        assert!(safe_path("covers/abc.jpeg").is_ok());
+        assert!(safe_path("../config.yml").is_err());
+        assert!(safe_path("covers/../../config.yml").is_err());
+        assert!(safe_path("/etc/passwd").is_err());
+        assert!(safe_path("./covers").is_err());
+    }
+
+    #[test]
+    fn extract_verifies_files() {
+        let folder = temp_folder("extract");
+        let manifest = manifest(&[("covers/a.jpeg", b"cover")]);
+        let path = archive(
+            &folder,
+            &[
+                (DATABASE, b"db"),
+                ("files/covers/a.jpeg", b"cover"),
+                ("files/covers/b.jpeg", b"unused"),
+            ],
+        );
+        let staging = folder.join("ok");
+        extract(&path, &manifest, &manifest, &staging).unwrap();
+        assert_eq!(fs::read(staging.join("covers/a.jpeg")).unwrap(), b"cover");
+        assert_eq!(fs::read(staging.join(DATABASE)).unwrap(), b"db");
+        assert!(!staging.join("covers/b.jpeg").exists());
+
+        let path = archive(
+            &folder,
+            &[(DATABASE, b"db"), ("files/covers/a.jpeg", b"changed")],
+        );
+        assert!(extract(&path, &manifest, &manifest, &folder.join("corrupt")).is_err());
+
+        let path = archive(&folder, &[("files/covers/a.jpeg", b"cover")]);
+        assert!(extract(&path, &manifest, &manifest, &folder.join("no_db")).is_err());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn extract_rejects_escaping_paths() {
+        let folder = temp_folder("extract");
+        let manifest = manifest(&[("../escaped", b"evil")]);
+        let path = archive(&folder, &[(DATABASE, b"db"), ("files/../escaped", b"evil")]);
+        let staging = folder.join("staging");
+        assert!(extract(&path, &manifest, &manifest, &staging).is_err());
+        assert!(!folder.join("escaped").exists());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    fn fill(root: &Path, content: &str) {
+        for name in FOLDERS.into_iter().chain(["db"]) {
+            fs::create_dir_all(root.join(name)).unwrap();
+            fs::write(root.join(name).join("file"), content).unwrap();
+        }
+    }
+
+    fn content(root: &Path, name: &str) -> String {
+        fs::read_to_string(root.join(name).join("file")).unwrap()
+    }
+
+    #[test]
+    fn swap_replaces_folders() {
+        let root = temp_folder("swap");
+        let staging = root.join("staging");
+        fill(&root, "old");
+        fill(&staging, "new");
+        swap(&root, &staging).unwrap();
+        for name in FOLDERS.into_iter().chain(["db"]) {
+            assert_eq!(content(&root, name), "new");
+        }
+        assert!(!staging.join("old").exists());
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn swap_reverts_on_failure() {
+        let root = temp_folder("swap");
+        let staging = root.join("staging");
+        fill(&root, "old");
+        fill(&staging, "new");
+        fs::remove_dir_all(staging.join("db")).unwrap();
+        assert!(swap(&root, &staging).is_err());
+        for name in FOLDERS.into_iter().chain(["db"]) {
+            assert_eq!(content(&root, name), "old");
+        }
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,485 @@
+use crate::cli::walk;
+use crate::env::config::Config;
+use crate::errors::ApiResult;
+use crate::services::db::chapter::Chapter;
+use crate::services::db::chapter_version::ChapterVersion;
+use crate::services::db::comment::Comment;
+use crate::services::db::establish_checked;
+use crate::services::db::manga::Manga;
+use crate::services::db::manga_kind::Kind;
+use crate::services::db::manga_list::MangaList;
+use crate::services::db::manga_relation::MangaRelation;
+use crate::services::db::notification::UserNotification;
+use crate::services::db::page::Page;
+use crate::services::db::progress::UserProgress;
+use crate::services::db::rating::MangaRating;
+use crate::services::db::tag::Tag;
+use crate::services::db::translation::PageTranslation;
+use crate::services::db::user::User;
+use crate::services::db::version::Version;
+use img_hash::HasherConfig;
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::Thing;
+use surrealdb::Surreal;
+use surrealdb_extras::SurrealTableInfo;
+
+/// files without a page are moved here
+const LOST_AND_FOUND: &str = "lost+found";
+
+/// how a dangling link is repaired
+enum Repair {
+    /// removes the link from the array
+    Remove,
+    /// deletes the record which contains the link
+    DeleteRecord,
+    /// the link is required and can't be removed
+    Manual,
+}
+
+/// record link which has to point to an existing record
+struct Link {
+    table: String,
+    /// single link or array of links
+    field: &'static str,
+    target: String,
+    repair: Repair,
+}
+
+impl Link {
+    fn new(table: &str, field: &'static str, target: &str, repair: Repair) -> Self {
+        Self {
+            table: table.to_string(),
+            field,
+            target: target.to_string(),
+            repair,
+        }
+    }
+}
+
+/// `Chapter.versions` is checked while walking the mangas
+fn links() -> Vec<Link> {
+    vec![
+        Link::new(Manga::name(), "kind", Kind::name(), Repair::Manual),
+        Link::new(Manga::name(), "tags", Tag::name(), Repair::Remove),
+        Link::new(Manga::name(), "uploader", User::name(), Repair::Manual),
+        Link::new(Manga::name(), "artists", User::name(), Repair::Remove),
+        Link::new(Manga::name(), "authors", User::name(), Repair::Remove),
+        Link::new(Manga::name(), "chapters", Chapter::name(), Repair::Remove),
+        Link::new(Manga::name(), "scraper", Version::name(), Repair::Remove),
+        Link::new(Chapter::name(), "tags", Tag::name(), Repair::Remove),
+        Link::new(
+            ChapterVersion::name(),
+            "version",
+            Version::name(),
+            Repair::Manual,
+        ),
+        Link::new(
+            ChapterVersion::name(),
+            "pages",
+            Page::name(),
+            Repair::Remove,
+        ),
+        Link::new(
+            PageTranslation::name(),
+            "page",
+            Page::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            UserProgress::name(),
+            "user",
+            User::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            UserProgress::name(),
+            "manga",
+            Manga::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            UserProgress::name(),
+            "chapter",
+            Chapter::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            MangaList::name(),
+            "user",
+            User::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(MangaList::name(), "mangas", Manga::name(), Repair::Remove),
+        Link::new(
+            MangaRating::name(),
+            "manga",
+            Manga::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            MangaRating::name(),
+            "user",
+            User::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            MangaRelation::name(),
+            "manga",
+            Manga::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            MangaRelation::name(),
+            "related",
+            Manga::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            UserNotification::name(),
+            "user",
+            User::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            UserNotification::name(),
+            "manga",
+            Manga::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            UserNotification::name(),
+            "chapter",
+            Chapter::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            Comment::name(),
+            "manga",
+            Manga::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(
+            Comment::name(),
+            "chapter",
+            Chapter::name(),
+            Repair::DeleteRecord,
+        ),
+        Link::new(Comment::name(), "parent", Comment::name(), Repair::Manual),
+        Link::new(Comment::name(), "author", User::name(), Repair::Manual),
+    ]
+}
+
+#[derive(Deserialize)]
+struct LinkRow {
+    id: Thing,
+    links: Vec<Thing>,
+}
+
+#[derive(Deserialize)]
+struct MangaRow {
+    id: Thing,
+    covers: Vec<String>,
+    chapters: Vec<Thing>,
+}
+
+#[derive(Deserialize)]
+struct ChapterRow {
+    id: Thing,
+    versions: HashMap<String, Thing>,
+}
+
+#[derive(Deserialize)]
+struct VersionRow {
+    id: Thing,
+    pages: Vec<Thing>,
+}
+
+/// hash is optional, because old pages were stored without one
+#[derive(Deserialize)]
+struct PageRow {
+    id: Thing,
+    page: u32,
+    ext: String,
+    hash: Option<String>,
+}
+
+struct Report {
+    fix: bool,
+    found: u32,
+    fixed: u32,
+}
+
+impl Report {
+    /// returns true if the problem should be fixed
+    fn found(&mut self, problem: impl Display) -> bool {
+        self.found += 1;
+        warn!("{}", problem);
+        self.fix
+    }
+
+    fn fixed(&mut self) {
+        self.fixed += 1;
+    }
+}
+
+async fn select<T: DeserializeOwned>(
+    db: &Surreal<Db>,
+    fields: &str,
+    table: &str,
+) -> ApiResult<Vec<T>> {
+    Ok(db
+        .query(format!("SELECT {} FROM {}", fields, table))
+        .await?
+        .take(0)?)
+}
+
+async fn by_id<T: DeserializeOwned>(
+    db: &Surreal<Db>,
+    fields: &str,
+    table: &str,
+    id: impl Fn(&T) -> Thing,
+) -> ApiResult<HashMap<Thing, T>> {
+    let rows: Vec<T> = select(db, fields, table).await?;
+    Ok(rows.into_iter().map(|v| (id(&v), v)).collect())
+}
+
+/// reports problems of the stored data. `fix` repairs them
+pub async fn fsck(config: &Config, fix: bool) -> ApiResult<()> {
+    let root = &config.root_folder;
+    let db = establish_checked(root.clone(), false).await?;
+    let mut report = Report {
+        fix,
+        found: 0,
+        fixed: 0,
+    };
+    check_files(&db, root, &mut report).await?;
+    check_links(&db, &mut report).await?;
+    match fix {
+        true => info!("{} problems found, {} fixed", report.found, report.fixed),
+        false => info!(
+            "{} problems found. run `api fsck --fix` to repair them",
+            report.found
+        ),
+    }
+    Ok(())
+}
+
+/// walks mangas => chapters => versions => pages and compares them to the files
+async fn check_files(db: &Surreal<Db>, root: &Path, report: &mut Report) -> ApiResult<()> {
+    let mangas: Vec<MangaRow> = select(db, "id, covers, chapters", Manga::name()).await?;
+    let chapters = by_id(db, "id, versions", Chapter::name(), |v: &ChapterRow| {
+        v.id.clone()
+    })
+    .await?;
+    let versions = by_id(db, "id, pages", ChapterVersion::name(), |v: &VersionRow| {
+        v.id.clone()
+    })
+    .await?;
+    let pages = by_id(db, "id, page, ext, hash", Page::name(), |v: &PageRow| {
+        v.id.clone()
+    })
+    .await?;
+    let hasher = HasherConfig::new().to_hasher();
+    let mut expected = HashSet::new();
+    let mut reachable = HashSet::new();
+
+    for manga in &mangas {
+        let manga_id = manga.id.id.to_raw();
+        check_covers(db, root, manga, report).await?;
+        for chapter in manga.chapters.iter().filter_map(|v| chapters.get(v)) {
+            let mut chapter_versions = chapter.versions.clone();
+            for (key, cv_id) in &chapter.versions {
+                let Some(cv) = versions.get(cv_id) else {
+                    if report.found(format!("{} links to missing {}", chapter.id, cv_id)) {
+                        chapter_versions.remove(key);
+                        report.fixed();
+                    }
+                    continue;
+                };
+                reachable.insert(cv_id);
+                let folder = root
+                    .join("mangas")
+                    .join(&manga_id)
+                    .join(chapter.id.id.to_raw())
+                    .join(key.split_once(':').map(|v| v.1).unwrap_or(key));
+                let mut remaining = 0;
+                for page in cv.pages.iter().filter_map(|v| pages.get(v)) {
+                    let path = folder.join(format!("{}.{}", page.page, page.ext));
+                    if !path.is_file() {
+                        if report.found(format!("{} is missing {}", page.id, path.display())) {
+                            db.query("UPDATE $cv SET pages -= $page; DELETE $page")
+                                .bind(("cv", cv.id.clone()))
+                                .bind(("page", page.id.clone()))
+                                .await?
+                                .check()?;
+                            report.fixed();
+                        }
+                        continue;
+                    }
+                    remaining += 1;
+                    if page.hash.as_deref().unwrap_or_default().is_empty()
+                        && report.found(format!("{} has no hash", page.id))
+                    {
+                        match image::open(&path) {
+                            Ok(image) => {
+                                db.query("UPDATE $page SET hash = $hash")
+                                    .bind(("page", page.id.clone()))
+                                    .bind(("hash", hasher.hash_image(&image).to_base64()))
+                                    .await?
+                                    .check()?;
+                                report.fixed();
+                            }
+                            Err(e) => warn!("Failed to hash {}: {}", path.display(), e),
+                        }
+                    }
+                    expected.insert(path);
+                }
+                if remaining == 0 && report.found(format!("{} has no pages", cv.id)) {
+                    db.query("DELETE $cv")
+                        .bind(("cv", cv.id.clone()))
+                        .await?
+                        .check()?;
+                    chapter_versions.remove(key);
+                    report.fixed();
+                }
+            }
+            if chapter_versions.len() != chapter.versions.len() {
+                db.query("UPDATE $chapter SET versions = $versions")
+                    .bind(("chapter", chapter.id.clone()))
+                    .bind(("versions", chapter_versions))
+                    .await?
+                    .check()?;
+            }
+        }
+    }
+
+    for cv in versions.values() {
+        if !reachable.contains(&cv.id)
+            && cv.pages.is_empty()
+            && report.found(format!("{} has no pages", cv.id))
+        {
+            db.query("DELETE $cv")
+                .bind(("cv", cv.id.clone()))
+                .await?
+                .check()?;
+            report.fixed();
+        }
+    }
+
+    for rel in walk(root, "mangas")? {
+        let path = root.join(&rel);
+        // legacy translations are stored next to the pages
+        if expected.contains(&path) || rel.ends_with(".json") {
+            continue;
+        }
+        if report.found(format!("{} has no page", rel)) {
+            let dest = root.join(LOST_AND_FOUND).join(&rel);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(path, dest)?;
+            report.fixed();
+        }
+    }
+    Ok(())
+}
+
+/// missing covers are removed as long as one cover is left
+async fn check_covers(
+    db: &Surreal<Db>,
+    root: &Path,
+    manga: &MangaRow,
+    report: &mut Report,
+) -> ApiResult<()> {
+    let manga_id = manga.id.id.to_raw();
+    let missing: Vec<_> = manga
+        .covers
+        .iter()
+        .filter(|ext| {
+            !root
+                .join("covers")
+                .join(format!("{}.{}", manga_id, ext))
+                .is_file()
+        })
+        .collect();
+    let left = manga.covers.len() - missing.len();
+    for ext in missing {
+        if !report.found(format!(
+            "{} is missing cover {}.{}",
+            manga.id, manga_id, ext
+        )) {
+            continue;
+        }
+        if left == 0 {
+            warn!("{} needs at least one cover", manga.id);
+            continue;
+        }
+        db.query("UPDATE $manga SET covers -= $ext")
+            .bind(("manga", manga.id.clone()))
+            .bind(("ext", ext))
+            .await?
+            .check()?;
+        report.fixed();
+    }
+    Ok(())
+}
+
+async fn check_links(db: &Surreal<Db>, report: &mut Report) -> ApiResult<()> {
+    let mut existing: HashMap<String, HashSet<Thing>> = HashMap::new();
+    for link in links() {
+        if !existing.contains_key(&link.target) {
+            let ids: Vec<Thing> = db
+                .query(format!("SELECT VALUE id FROM {}", link.target))
+                .await?
+                .take(0)?;
+            existing.insert(link.target.clone(), ids.into_iter().collect());
+        }
+        let ids = &existing[&link.target];
+        let rows: Vec<LinkRow> = select(
+            db,
+            &format!(
+                "id, array::compact(array::flatten([{}])) AS links",
+                link.field
+            ),
+            &link.table,
+        )
+        .await?;
+        for row in rows {
+            for target in row.links.into_iter().filter(|v| !ids.contains(v)) {
+                if !report.found(format!(
+                    "{}.{} links to missing {}",
+                    row.id, link.field, target
+                )) {
+                    continue;
+                }
+                match link.repair {
+                    Repair::Remove => {
+                        db.query(format!("UPDATE $id SET {} -= $target", link.field))
+                            .bind(("id", row.id.clone()))
+                            .bind(("target", target))
+                            .await?
+                            .check()?;
+                        report.fixed();
+                    }
+                    Repair::DeleteRecord => {
+                        db.query("DELETE $id")
+                            .bind(("id", row.id.clone()))
+                            .await?
+                            .check()?;
+                        report.fixed();
+                        break;
+                    }
+                    Repair::Manual => warn!("{} has to be repaired manually", row.id),
+                }
+            }
+        }
+    }
+    Ok(())
+}
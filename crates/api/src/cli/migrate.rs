@@ -0,0 +1,18 @@
+use crate::env::config::Config;
+use crate::errors::ApiResult;
+use crate::services::db::establish_checked;
+use crate::services::db::migration::{self, latest_version};
+use crate::services::storage::new_store;
+use log::info;
+
+/// a dry run doesn't register the schema, so nothing is written
+pub async fn migrate(config: &Config, dry_run: bool) -> ApiResult<()> {
+    let db = establish_checked(config.root_folder.clone(), !dry_run).await?;
+    let current = migration::version(&db).await?;
+    info!("Schema version {}, latest {}", current, latest_version());
+    migration::migrate(&db, &*new_store(config), dry_run).await?;
+    if !dry_run {
+        info!("Schema version {}", migration::version(&db).await?);
+    }
+    Ok(())
+}
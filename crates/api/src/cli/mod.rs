@@ -0,0 +1,161 @@
+mod backup;
+mod fsck;
+mod migrate;
+
+use crate::env::config::{Config, Storage};
+use crate::errors::{ApiError, ApiResult};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "usage:
+    api [--config <path>] [<command>]          the config defaults to config.yml
+    api                                        starts the server
+    api backup <archive> [--incremental <base>] backs up the database and the image folders
+    api restore [<base>...] <archive>          restores a backup. incremental backups need their bases
+    api fsck [--fix]                           checks the database against the files. --fix repairs the problems
+    api migrate [--dry-run]                    runs pending schema migrations. the server runs them on start";
+
+/// maintenance commands which run instead of the server.
+/// the server has to be stopped, because the database can only be opened once
+pub enum Command {
+    Backup {
+        archive: PathBuf,
+        /// only files which changed since this backup are added
+        base: Option<PathBuf>,
+    },
+    Restore {
+        /// the last archive is restored
+        archives: Vec<PathBuf>,
+    },
+    Fsck {
+        /// without it only reports problems
+        fix: bool,
+    },
+    Migrate {
+        /// only reports what would change
+        dry_run: bool,
+    },
+}
+
+/// removes `--config <path>` from the arguments
+pub fn take_config_path(args: &mut Vec<String>) -> Result<Option<PathBuf>, String> {
+    let Some(i) = args.iter().position(|v| v == "--config") else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err(USAGE.to_string());
+    }
+    let path = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(path.into()))
+}
+
+impl Command {
+    /// none starts the server
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let Some(command) = args.next() else {
+            return Ok(None);
+        };
+        let args: Vec<String> = args.collect();
+        let command = match (command.as_str(), args.as_slice()) {
+            ("backup", [archive]) => Command::Backup {
+                archive: archive.into(),
+                base: None,
+            },
+            ("backup", [archive, flag, base]) if flag == "--incremental" => Command::Backup {
+                archive: archive.into(),
+                base: Some(base.into()),
+            },
+            ("restore", archives) if !archives.is_empty() => Command::Restore {
+                archives: archives.iter().map(PathBuf::from).collect(),
+            },
+            ("fsck", []) => Command::Fsck { fix: false },
+            ("fsck", [flag]) if flag == "--fix" => Command::Fsck { fix: true },
+            ("migrate", []) => Command::Migrate { dry_run: false },
+            ("migrate", [flag]) if flag == "--dry-run" => Command::Migrate { dry_run: true },
+            _ => return Err(USAGE.to_string()),
+        };
+        Ok(Some(command))
+    }
+
+    pub async fn run(self, config: &Config) -> ApiResult<()> {
+        let files = matches!(
+            self,
+            Command::Backup { .. } | Command::Restore { .. } | Command::Fsck { .. }
+        );
+        if files && !matches!(config.storage, Storage::Local) {
+            return Err(ApiError::invalid_input(
+                "backup, restore and fsck only support local storage",
+            ));
+        }
+        match self {
+            Command::Backup { archive, base } => {
+                backup::backup(config, &archive, base.as_deref()).await
+            }
+            Command::Restore { archives } => backup::restore(config, &archives).await,
+            Command::Fsck { fix } => fsck::fsck(config, fix).await,
+            Command::Migrate { dry_run } => migrate::migrate(config, dry_run).await,
+        }
+    }
+}
+
+/// files inside of `root/folder` relative to root. always separated by `/`
+fn walk(root: &Path, folder: &str) -> io::Result<Vec<String>> {
+    let mut files = vec![];
+    let mut dirs = vec![root.join(folder)];
+    while let Some(dir) = dirs.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.is_file() {
+                if let Ok(rel) = path.strip_prefix(root) {
+                    let rel: Vec<_> = rel
+                        .components()
+                        .map(|v| v.as_os_str().to_string_lossy())
+                        .collect();
+                    files.push(rel.join("/"));
+                }
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(v: &[&str]) -> Vec<String> {
+        v.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn config_path() {
+        let mut a = args(&["--config", "/etc/manread.yml", "migrate"]);
+        assert_eq!(
+            take_config_path(&mut a).unwrap(),
+            Some(PathBuf::from("/etc/manread.yml"))
+        );
+        assert_eq!(a, args(&["migrate"]));
+
+        let mut a = args(&["backup", "a.tar", "--config", "c.yml"]);
+        assert_eq!(take_config_path(&mut a).unwrap(), Some("c.yml".into()));
+        assert_eq!(a, args(&["backup", "a.tar"]));
+
+        let mut a = args(&["fsck"]);
+        assert_eq!(take_config_path(&mut a).unwrap(), None);
+        assert_eq!(a, args(&["fsck"]));
+    }
+
+    #[test]
+    fn config_path_missing() {
+        let mut a = args(&["migrate", "--config"]);
+        assert!(take_config_path(&mut a).is_err());
+    }
+}
@@ -1,8 +1,19 @@
+use log::LevelFilter;
 use nanoid::nanoid;
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+/// used when no `--config` is passed. created with defaults if missing
+const DEFAULT_PATH: &str = "config.yml";
+/// prefix of the environment variables which override the config file
+const ENV_PREFIX: &str = "MANREAD_";
+/// shorter keys only log a warning, because older configs may still use them
+const MIN_SECRET_KEY: usize = 32;
 
 pub const ID_CHARS: [char; 71] = [
     '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i',
@@ -19,9 +30,69 @@ pub struct Config {
     pub rust_log: String,
     pub secret_key: String,
     pub spinner: Spinner,
+    #[serde(default)]
+    pub translation_backend: Option<TranslationBackend>,
+    #[serde(default)]
+    pub temp_uploads: TempUploads,
+    /// allowed origins of cross origin requests. ignored with the `cors-permissive` feature
+    #[serde(default)]
+    pub cors_origins: Vec<String>,
+    /// where covers, pages and user images are stored
+    #[serde(default)]
+    pub storage: Storage,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub enum Storage {
+    /// files inside of root_folder
+    #[default]
+    Local,
+    S3(S3Storage),
 }
 
+/// S3 compatible object storage like MinIO
 #[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct S3Storage {
+    /// e.g. http://localhost:9000
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+/// limits for files in `root_folder/temp` which weren't claimed yet
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TempUploads {
+    /// max bytes per user. uploads without a session are counted per ip
+    pub quota: u64,
+    /// seconds until an unclaimed file is deleted
+    pub ttl: u64,
+}
+
+impl Default for TempUploads {
+    fn default() -> Self {
+        Self {
+            quota: 100 * 1024 * 1024,
+            ttl: 24 * 60 * 60,
+        }
+    }
+}
+
+/// http service which receives a page image and returns the text areas
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TranslationBackend {
+    pub url: String,
+    /// stored as source of the areas
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Spinner {
     Pikachu1,
     Pikachu2,
@@ -64,6 +135,10 @@ impl Default for Config {
             rust_log: "info".to_string(),
             secret_key: random_string(64), //2048bit = 256byte = 64 chars
             spinner: Spinner::Pikachu2,
+            translation_backend: None,
+            temp_uploads: TempUploads::default(),
+            cors_origins: vec![],
+            storage: Storage::Local,
         }
     }
 }
@@ -72,20 +147,343 @@ pub fn random_string(len: usize) -> String {
     nanoid!(len, &ID_CHARS)
 }
 
-pub fn get_env() -> std::io::Result<Config> {
-    let path = PathBuf::from("config.yml");
-    if path.is_file() {
-        let mut file = File::open(path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        Ok(serde_yaml::from_str(&contents).expect("Unable to deserialize YAML"))
-    } else {
-        let config = Config::default();
-        File::create(path)?.write_all(
-            serde_yaml::to_string(&config)
-                .expect("Unable to serialize")
-                .as_bytes(),
-        )?;
-        Ok(config)
+#[derive(Debug)]
+pub struct ConfigError {
+    /// name in the config file
+    pub field: String,
+    pub message: String,
+}
+
+impl ConfigError {
+    fn new(field: impl ToString, message: impl ToString) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid config field `{}`: {}", self.field, self.message)
+    }
+}
+
+/// reads the config file, applies the environment variables and validates the result.
+/// the default file is created if it doesn't exist
+pub fn get_env(path: Option<&Path>) -> Result<Config, ConfigError> {
+    let mut config = match path {
+        Some(path) => read_file(path)?,
+        None if Path::new(DEFAULT_PATH).is_file() => read_file(Path::new(DEFAULT_PATH))?,
+        None => {
+            let config = Config::default();
+            File::create(DEFAULT_PATH)
+                .and_then(|mut file| {
+                    file.write_all(
+                        serde_yaml::to_string(&config)
+                            .expect("Unable to serialize")
+                            .as_bytes(),
+                    )
+                })
+                .map_err(|e| ConfigError::new(DEFAULT_PATH, e))?;
+            config
+        }
+    };
+    config.apply_env(|key| std::env::var(key).ok())?;
+    config.validate()?;
+    Ok(config)
+}
+
+fn read_file(path: &Path) -> Result<Config, ConfigError> {
+    let mut contents = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|e| ConfigError::new(path.display(), e))?;
+    serde_yaml::from_str(&contents).map_err(|e| {
+        // serde_yaml prefixes errors of nested values with their path
+        let message = e.to_string();
+        match message.split_once(": ") {
+            Some((field, rest)) if !field.contains(' ') => ConfigError::new(field, rest),
+            _ => ConfigError::new(path.display(), message),
+        }
+    })
+}
+
+/// parses the variable `MANREAD_<FIELD>` if it is set
+fn parse_var<T: FromStr>(
+    var: &impl Fn(&str) -> Option<String>,
+    field: &str,
+) -> Result<Option<T>, ConfigError>
+where
+    T::Err: Display,
+{
+    let key = format!("{}{}", ENV_PREFIX, field.replace('.', "_").to_uppercase());
+    var(&key)
+        .map(|v| {
+            v.parse()
+                .map_err(|e| ConfigError::new(field, format!("{} from {}", e, key)))
+        })
+        .transpose()
+}
+
+impl Config {
+    /// e.g. `MANREAD_PORT` overrides `port` and `MANREAD_TEMP_UPLOADS_TTL` overrides `temp_uploads.ttl`
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        if let Some(v) = parse_var(&var, "root_folder")? {
+            self.root_folder = v;
+        }
+        if let Some(v) = parse_var(&var, "port")? {
+            self.port = v;
+        }
+        if let Some(v) = parse_var(&var, "https_port")? {
+            self.https_port = v;
+        }
+        if let Some(v) = parse_var(&var, "rust_log")? {
+            self.rust_log = v;
+        }
+        if let Some(v) = parse_var(&var, "secret_key")? {
+            self.secret_key = v;
+        }
+        if let Some(v) = parse_var::<String>(&var, "spinner")? {
+            self.spinner = serde_yaml::from_str(&v)
+                .map_err(|_| ConfigError::new("spinner", format!("Unknown spinner {}", v)))?;
+        }
+        let url = parse_var(&var, "translation_backend.url")?;
+        let name = parse_var(&var, "translation_backend.name")?;
+        match (url, name) {
+            (Some(url), Some(name)) => {
+                self.translation_backend = Some(TranslationBackend { url, name })
+            }
+            (None, None) => {}
+            (url, name) => match self.translation_backend.as_mut() {
+                Some(backend) => {
+                    if let Some(url) = url {
+                        backend.url = url;
+                    }
+                    if let Some(name) = name {
+                        backend.name = name;
+                    }
+                }
+                None => {
+                    return Err(ConfigError::new(
+                        "translation_backend",
+                        "needs a url and a name",
+                    ))
+                }
+            },
+        }
+        if let Some(v) = parse_var(&var, "temp_uploads.quota")? {
+            self.temp_uploads.quota = v;
+        }
+        if let Some(v) = parse_var(&var, "temp_uploads.ttl")? {
+            self.temp_uploads.ttl = v;
+        }
+        if let Storage::S3(s3) = &mut self.storage {
+            if let Some(v) = parse_var(&var, "storage.endpoint")? {
+                s3.endpoint = v;
+            }
+            if let Some(v) = parse_var(&var, "storage.bucket")? {
+                s3.bucket = v;
+            }
+            if let Some(v) = parse_var(&var, "storage.region")? {
+                s3.region = v;
+            }
+            if let Some(v) = parse_var(&var, "storage.access_key")? {
+                s3.access_key = v;
+            }
+            if let Some(v) = parse_var(&var, "storage.secret_key")? {
+                s3.secret_key = v;
+            }
+        }
+        if let Some(v) = parse_var::<String>(&var, "cors_origins")? {
+            self.cors_origins = v
+                .split(',')
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .collect();
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.root_folder.as_os_str().is_empty() {
+            return Err(ConfigError::new("root_folder", "must not be empty"));
+        }
+        for (field, port) in [("port", self.port), ("https_port", self.https_port)] {
+            if !(1..=u16::MAX as u32).contains(&port) {
+                return Err(ConfigError::new(field, "must be between 1 and 65535"));
+            }
+        }
+        self.log_level()?;
+        if self.secret_key.is_empty() {
+            return Err(ConfigError::new("secret_key", "must not be empty"));
+        }
+        if let Spinner::Custom(file) = &self.spinner {
+            let path = Path::new(file);
+            if path.components().count() != 1
+                || !matches!(path.components().next(), Some(Component::Normal(_)))
+            {
+                return Err(ConfigError::new(
+                    "spinner",
+                    "custom spinners have to be a file name inside of the spinners folder",
+                ));
+            }
+        }
+        if let Some(backend) = &self.translation_backend {
+            Url::parse(&backend.url).map_err(|e| ConfigError::new("translation_backend.url", e))?;
+        }
+        if self.temp_uploads.quota == 0 {
+            return Err(ConfigError::new("temp_uploads.quota", "must not be 0"));
+        }
+        if self.temp_uploads.ttl == 0 {
+            return Err(ConfigError::new("temp_uploads.ttl", "must not be 0"));
+        }
+        if let Storage::S3(s3) = &self.storage {
+            Url::parse(&s3.endpoint).map_err(|e| ConfigError::new("storage.endpoint", e))?;
+            if s3.bucket.is_empty() {
+                return Err(ConfigError::new("storage.bucket", "must not be empty"));
+            }
+        }
+        for origin in &self.cors_origins {
+            match Url::parse(origin) {
+                Ok(url) if ["http", "https"].contains(&url.scheme()) => {}
+                _ => {
+                    return Err(ConfigError::new(
+                        "cors_origins",
+                        format!("{} is not a http(s) origin", origin),
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// problems which don't stop the server. logged once the logger is set up
+    pub fn warnings(&self) -> Vec<ConfigError> {
+        let mut warnings = vec![];
+        if self.secret_key.chars().count() < MIN_SECRET_KEY {
+            warnings.push(ConfigError::new(
+                "secret_key",
+                format!("should have at least {} characters", MIN_SECRET_KEY),
+            ));
+        }
+        warnings
+    }
+
+    pub fn log_level(&self) -> Result<LevelFilter, ConfigError> {
+        LevelFilter::from_str(&self.rust_log).map_err(|_| {
+            ConfigError::new(
+                "rust_log",
+                "has to be off, error, warn, info, debug or trace",
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn apply(config: &mut Config, vars: &[(&str, &str)]) -> Result<(), ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        config.apply_env(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn env_overrides() {
+        let mut config = Config::default();
+        apply(
+            &mut config,
+            &[
+                ("MANREAD_PORT", "8080"),
+                ("MANREAD_ROOT_FOLDER", "/data"),
+                ("MANREAD_TEMP_UPLOADS_TTL", "60"),
+                ("MANREAD_SPINNER", "Ferris"),
+                ("MANREAD_CORS_ORIGINS", "https://a.com, ,http://b.com"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.root_folder, PathBuf::from("/data"));
+        assert_eq!(config.temp_uploads.ttl, 60);
+        assert_eq!(config.spinner, Spinner::Ferris);
+        assert_eq!(config.cors_origins, vec!["https://a.com", "http://b.com"]);
+        // unchanged
+        assert_eq!(config.https_port, 8081);
+    }
+
+    #[test]
+    fn env_errors_name_the_field() {
+        let err = apply(&mut Config::default(), &[("MANREAD_PORT", "http")]).unwrap_err();
+        assert_eq!(err.field, "port");
+        let err = apply(&mut Config::default(), &[("MANREAD_SPINNER", "Unknown")]).unwrap_err();
+        assert_eq!(err.field, "spinner");
+    }
+
+    #[test]
+    fn env_translation_backend() {
+        let mut config = Config::default();
+        let err = apply(
+            &mut config,
+            &[("MANREAD_TRANSLATION_BACKEND_URL", "http://localhost")],
+        )
+        .unwrap_err();
+        assert_eq!(err.field, "translation_backend");
+        apply(
+            &mut config,
+            &[
+                ("MANREAD_TRANSLATION_BACKEND_URL", "http://localhost"),
+                ("MANREAD_TRANSLATION_BACKEND_NAME", "ocr"),
+            ],
+        )
+        .unwrap();
+        apply(
+            &mut config,
+            &[("MANREAD_TRANSLATION_BACKEND_NAME", "other")],
+        )
+        .unwrap();
+        let backend = config.translation_backend.unwrap();
+        assert_eq!(backend.url, "http://localhost");
+        assert_eq!(backend.name, "other");
+    }
+
+    fn invalid_field(change: impl FnOnce(&mut Config)) -> String {
+        let mut config = Config::default();
+        change(&mut config);
+        config.validate().unwrap_err().field
+    }
+
+    #[test]
+    fn validate() {
+        assert!(Config::default().validate().is_ok());
+        assert_eq!(invalid_field(|v| v.port = 0), "port");
+        assert_eq!(invalid_field(|v| v.https_port = 70000), "https_port");
+        assert_eq!(invalid_field(|v| v.rust_log = "loud".into()), "rust_log");
+        assert_eq!(invalid_field(|v| v.secret_key.clear()), "secret_key");
+        assert_eq!(
+            invalid_field(|v| v.spinner = Spinner::Custom("../a.gif".into())),
+            "spinner"
+        );
+        assert_eq!(
+            invalid_field(|v| v.temp_uploads.quota = 0),
+            "temp_uploads.quota"
+        );
+        assert_eq!(
+            invalid_field(|v| v.cors_origins = vec!["ftp://a.com".into()]),
+            "cors_origins"
+        );
+    }
+
+    #[test]
+    fn short_secret_key_only_warns() {
+        let mut config = Config::default();
+        assert!(config.warnings().is_empty());
+        config.secret_key = "short".to_string();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.warnings()[0].field, "secret_key");
     }
 }
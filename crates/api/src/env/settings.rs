@@ -0,0 +1,66 @@
+use crate::env::config::{Config, Spinner};
+use log::LevelFilter;
+use std::sync::RwLock;
+
+/// part of the config which is reloaded on SIGHUP. everything else needs a restart
+pub struct Settings {
+    inner: RwLock<Reloadable>,
+}
+
+#[derive(Clone, PartialEq)]
+struct Reloadable {
+    log_level: LevelFilter,
+    spinner: Spinner,
+    cors_origins: Vec<String>,
+}
+
+impl Reloadable {
+    fn new(config: &Config) -> Self {
+        Self {
+            log_level: config.log_level().unwrap_or(LevelFilter::Info),
+            spinner: config.spinner.clone(),
+            cors_origins: config.cors_origins.clone(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            inner: RwLock::new(Reloadable::new(config)),
+        }
+    }
+
+    pub fn spinner(&self) -> Spinner {
+        self.inner.read().unwrap().spinner.clone()
+    }
+
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        let origin = origin.trim_end_matches('/');
+        self.inner
+            .read()
+            .unwrap()
+            .cors_origins
+            .iter()
+            .any(|v| v.trim_end_matches('/') == origin)
+    }
+
+    /// sets the max level of the logger
+    pub fn apply_log_level(&self) {
+        log::set_max_level(self.inner.read().unwrap().log_level);
+    }
+
+    /// returns false if nothing changed
+    #[cfg(unix)]
+    pub fn reload(&self, config: &Config) -> bool {
+        let new = Reloadable::new(config);
+        let mut inner = self.inner.write().unwrap();
+        if *inner == new {
+            return false;
+        }
+        *inner = new;
+        drop(inner);
+        self.apply_log_level();
+        true
+    }
+}
@@ -46,6 +46,15 @@ impl ApiError {
         .into()
     }
 
+    pub fn not_found(msg: impl ToString) -> ApiError {
+        ApiErr {
+            message: Some(msg.to_string()),
+            cause: None,
+            err_type: ApiErrorType::NotFoundError,
+        }
+        .into()
+    }
+
     pub fn write_error(err: impl ToString) -> ApiError {
         ApiErr {
             message: None,
@@ -3,6 +3,7 @@ mod defaults;
 mod image;
 mod io;
 mod json;
+mod reqwest;
 mod scrape;
 mod surreal;
 
@@ -0,0 +1,14 @@
+use crate::errors::{ApiError, ApiErrorType};
+use api_structure::error::ApiErr;
+use reqwest::Error;
+
+impl From<Error> for ApiError {
+    fn from(value: Error) -> Self {
+        ApiErr {
+            message: Some("Failed to reach external service".to_string()),
+            cause: Some(value.to_string()),
+            err_type: ApiErrorType::InternalError,
+        }
+        .into()
+    }
+}
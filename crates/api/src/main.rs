@@ -1,23 +1,42 @@
 use crate::env::config::Config;
+use crate::env::settings::Settings;
 use crate::errors::ApiResult;
-use crate::routes::manga::is_valid_translation;
 use crate::services::auth_service::validator;
 use crate::services::crypto_service::CryptoService;
+use crate::services::db::audit_log::AuditLogDBService;
 use crate::services::db::auth_tokens::AuthTokenDBService;
 use crate::services::db::chapter::ChapterDBService;
 use crate::services::db::chapter_version::ChapterVersionDBService;
-use crate::services::db::establish;
+use crate::services::db::comment::CommentDBService;
+use crate::services::db::establish_checked;
 use crate::services::db::manga::MangaDBService;
 use crate::services::db::manga_kind::MangaKindDBService;
 use crate::services::db::manga_list::MangaListDBService;
+use crate::services::db::manga_relation::MangaRelationDBService;
+use crate::services::db::manga_revision::MangaRevisionDBService;
+use crate::services::db::migration;
+use crate::services::db::notification::NotificationDBService;
 use crate::services::db::page::PageDBService;
 use crate::services::db::progress::ProgressDBService;
+use crate::services::db::rating::RatingDBService;
+use crate::services::db::reading_stats::ReadingStatsDBService;
 use crate::services::db::scrape_account::ScrapeAccountDBService;
 use crate::services::db::scrape_list::ScrapeListDBService;
 use crate::services::db::tag::TagDBService;
+use crate::services::db::temp_upload::TempUploadDBService;
+use crate::services::db::translation::{is_valid_translation, TranslationDBService};
+use crate::services::db::translation_job::TranslationJobDBService;
 use crate::services::db::user::UserDBService;
 use crate::services::db::version::VersionDBService;
 use crate::services::internal::internal_service;
+use crate::services::notification_service::{notification_service, NotificationHub};
+use crate::services::recommendation_service::{recommendation_service, RecommendationStore};
+#[cfg(unix)]
+use crate::services::reload_service::reload_service;
+use crate::services::storage::new_store;
+use crate::services::sync_service::ProgressHub;
+use crate::services::temp_service::temp_service;
+use crate::services::translation_service::{translation_service, TranslationQueue};
 use crate::services::uri_service::UriService;
 use crate::util::create_folders;
 use actix_files::NamedFile;
@@ -28,17 +47,17 @@ use actix_web_httpauth::middleware::HttpAuthentication;
 use api_structure::error::{ApiErr, ApiErrorType};
 use api_structure::fonts::FontRequest;
 use fern::colors::{Color, ColoredLevelConfig};
-use log::{info, LevelFilter};
+use log::{error, info, warn, LevelFilter};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::read_dir;
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
 use std::sync::Arc;
 use std::time::SystemTime;
 use surrealdb::engine::local::Db;
 use surrealdb::Surreal;
 
+mod cli;
 mod env;
 mod errors;
 mod routes;
@@ -47,7 +66,16 @@ mod util;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    let config = env::config::get_env().expect("Server configuration failed");
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let config_path = cli::take_config_path(&mut args).unwrap_or_else(|usage| {
+        eprintln!("{}", usage);
+        std::process::exit(2)
+    });
+    let config = env::config::get_env(config_path.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    });
+    let settings = Arc::new(Settings::new(&config));
     create_folders(
         &config.root_folder,
         vec![
@@ -80,14 +108,41 @@ async fn main() -> std::io::Result<()> {
                 message
             ))
         })
-        .level(log::LevelFilter::from_str(&config.rust_log).unwrap())
+        // filtered by the max level of [Settings], so it can be changed on reload
+        .level(LevelFilter::Trace)
         .level_for("selectors", LevelFilter::Off) //remove logger for scraping
         .level_for("html5ever", LevelFilter::Off)
         .level_for("hyper", LevelFilter::Off)
         .chain(std::io::stdout())
         .apply()
         .unwrap();
-    let db = Arc::new(establish(config.root_folder.clone(), true).await.unwrap());
+    settings.apply_log_level();
+    for warning in config.warnings() {
+        warn!("{}", warning);
+    }
+    let command = cli::Command::from_args(args.into_iter()).unwrap_or_else(|usage| {
+        eprintln!("{}", usage);
+        std::process::exit(2)
+    });
+    if let Some(command) = command {
+        if let Err(e) = command.run(&config).await {
+            error!("{}", e);
+            std::process::exit(1)
+        }
+        return Ok(());
+    }
+    let db = match establish_checked(config.root_folder.clone(), true).await {
+        Ok(db) => Arc::new(db),
+        Err(e) => {
+            error!("Failed to open the database: {}", e);
+            std::process::exit(1)
+        }
+    };
+    let store = new_store(&config);
+    if let Err(e) = migration::migrate(&db, &*store, false).await {
+        error!("Migration failed: {}", e);
+        std::process::exit(1)
+    }
     log_url(&config);
     #[cfg(feature = "https")]
     let ssl_builder = {
@@ -105,6 +160,29 @@ async fn main() -> std::io::Result<()> {
             .expect("File does not exist");
         builder
     };
+    let openapi = Data::new(routes::openapi::document());
+    let translation_queue = Arc::new(TranslationQueue::default());
+    tokio::spawn(translation_service(
+        db.clone(),
+        config.clone(),
+        store.clone(),
+        translation_queue.clone(),
+    ));
+    let notification_hub = Arc::new(NotificationHub::default());
+    tokio::spawn(notification_service(db.clone(), notification_hub.clone()));
+    let progress_hub = Arc::new(ProgressHub::default());
+    let recommendations = Arc::new(RecommendationStore::default());
+    tokio::spawn(recommendation_service(db.clone(), recommendations.clone()));
+    tokio::spawn(temp_service(db.clone(), config.clone()));
+    // SIGHUP only exists on unix
+    #[cfg(unix)]
+    tokio::spawn(reload_service(
+        settings.clone(),
+        config_path.clone(),
+        config.clone(),
+    ));
+    // shared by the workers, because it guards the upload quota
+    let temp_uploads = Arc::new(TempUploadDBService::new(db.clone()));
     let dbc = db.clone();
     let cfgc = config.clone();
     let hs = HttpServer::new(move || {
@@ -115,8 +193,15 @@ async fn main() -> std::io::Result<()> {
         let (multi, single, search, meta) =
             manread_scraper::init(cfgc.root_folder.clone()).unwrap();
         #[cfg(all(feature = "cors", not(feature = "cors-permissive")))]
+        let origins = settings.clone();
+        #[cfg(all(feature = "cors", not(feature = "cors-permissive")))]
         let app = app.wrap(
             actix_cors::Cors::default()
+                .allowed_origin_fn(move |origin, _| {
+                    origin
+                        .to_str()
+                        .is_ok_and(|origin| origins.allows_origin(origin))
+                })
                 .allow_any_header()
                 .allowed_methods(vec!["GET", "POST"])
                 .supports_credentials()
@@ -130,18 +215,35 @@ async fn main() -> std::io::Result<()> {
                 secret: cfgc.secret_key.as_bytes().to_vec(),
             }))
             .app_data(Data::new(cfgc.clone()))
+            .app_data(Data::from(settings.clone()))
+            .app_data(Data::from(store.clone()))
             .app_data(Data::new(fonts()))
+            .app_data(openapi.clone())
+            .app_data(Data::new(AuditLogDBService::new(dbc.clone())))
             .app_data(Data::new(AuthTokenDBService::new(dbc.clone())))
             .app_data(Data::new(ChapterDBService::new(dbc.clone())))
             .app_data(Data::new(ChapterVersionDBService::new(dbc.clone())))
+            .app_data(Data::new(CommentDBService::new(dbc.clone())))
             .app_data(Data::new(MangaDBService::new(dbc.clone())))
             .app_data(Data::new(MangaKindDBService::new(dbc.clone())))
             .app_data(Data::new(MangaListDBService::new(dbc.clone())))
+            .app_data(Data::new(MangaRelationDBService::new(dbc.clone())))
+            .app_data(Data::new(MangaRevisionDBService::new(dbc.clone())))
+            .app_data(Data::new(NotificationDBService::new(dbc.clone())))
+            .app_data(Data::from(notification_hub.clone()))
             .app_data(Data::new(PageDBService::new(dbc.clone())))
             .app_data(Data::new(ProgressDBService::new(dbc.clone())))
+            .app_data(Data::from(progress_hub.clone()))
+            .app_data(Data::new(RatingDBService::new(dbc.clone())))
+            .app_data(Data::from(recommendations.clone()))
+            .app_data(Data::new(ReadingStatsDBService::new(dbc.clone())))
             .app_data(Data::new(ScrapeAccountDBService::new(dbc.clone())))
             .app_data(Data::new(ScrapeListDBService::new(dbc.clone())))
             .app_data(Data::new(TagDBService::new(dbc.clone())))
+            .app_data(Data::from(temp_uploads.clone()))
+            .app_data(Data::new(TranslationDBService::new(dbc.clone())))
+            .app_data(Data::new(TranslationJobDBService::new(dbc.clone())))
+            .app_data(Data::from(translation_queue.clone()))
             .app_data(Data::new(UserDBService::new(dbc.clone())))
             .app_data(Data::new(VersionDBService::new(dbc.clone())))
             .app_data(Data::new(external))
@@ -168,6 +270,7 @@ async fn main() -> std::io::Result<()> {
             .service(routes::frontend::frontend_ep)
             .service(
                 web::scope("/api")
+                    .configure(routes::openapi::config)
                     .service(get_fonts)
                     .service(get_font)
                     .service(routes::image::upload_images)
@@ -176,19 +279,64 @@ async fn main() -> std::io::Result<()> {
                     .service(routes::user::sign_in_route)
                     .service(routes::user::reset_password_route)
                     .service(routes::user::request_reset_password_route)
+                    .service(routes::manga::progress_sync_route)
+                    .service(routes::manga::cover_get_route)
+                    .service(routes::manga::chapter_page_get_route)
                     .service(
                         web::scope("")
                             .wrap(HttpAuthentication::bearer(validator))
                             .service(routes::user::refresh_route) //ALL
                             .service(routes::user::activate_route) //NotVerified
+                            .service(routes::user::set_role_route) //Admin
+                            .service(routes::user::profile_route) //min User
+                            .service(routes::user::edit_profile_route) //min User
+                            .service(routes::user::set_user_image_route) //min User
+                            .service(routes::user::user_image_route) //min User
+                            .service(routes::image::sign_image_urls_route) //min User
+                            .service(routes::user::stats_route) //min User
+                            .service(routes::user::history_route) //min User
+                            .service(routes::user::delete_history_entry_route) //min User
+                            .service(routes::user::clear_history_route) //min User
+                            .service(routes::admin::audit_log_route) //Admin
+                            .service(routes::library::import_library_route) //min User
+                            .service(routes::library::export_library_route) //min User
+                            .service(routes::comment::comments_route) //min User
+                            .service(routes::comment::add_comment_route) //min User
+                            .service(routes::comment::edit_comment_route) //min User
+                            .service(routes::comment::delete_comment_route) //min User
+                            .service(routes::comment::moderate_comment_route) //min Moderator
+                            .service(routes::notification::notifications_route) //min User
+                            .service(routes::notification::mark_notifications_read_route) //min User
+                            .service(routes::notification::notification_settings_route) //min User
+                            .service(routes::notification::edit_notification_settings_route) //min User
+                            .service(routes::notification::mute_manga_route) //min User
+                            .service(routes::notification::notification_stream_route) //min User
                             .service(routes::manga::home_route) //min User
                             .service(routes::manga::search_route) //min User
                             .service(routes::manga::cover_route) //min User
                             .service(routes::manga::info_route) //min User
+                            .service(routes::manga::ratings_route) //min User
+                            .service(routes::manga::rate_manga_route) //min User
+                            .service(routes::manga::delete_rating_route) //min User
+                            .service(routes::manga::add_relation_route) //min Moderator
+                            .service(routes::manga::delete_relation_route) //min Moderator
+                            .service(routes::manga::edit_manga_route) //min User
+                            .service(routes::manga::manga_history_route) //min Moderator
+                            .service(routes::manga::rollback_manga_route) //min Moderator
+                            .service(routes::manga::visibility_route) //min Moderator
                             .service(routes::manga::reader_info_route) //min User
                             .service(routes::manga::pages_route) //min User
+                            .service(routes::manga::set_progress_route) //min User
+                            .service(routes::manga::sync_ticket_route) //min User
                             .service(routes::manga::chapter_page_route) //min User
                             .service(routes::manga::translation_route) //min User
+                            .service(routes::page::translation_areas_route) //min Moderator
+                            .service(routes::page::add_translation_area_route) //min Moderator
+                            .service(routes::page::edit_translation_area_route) //min Moderator
+                            .service(routes::page::delete_translation_area_route) //min Moderator
+                            .service(routes::page::translation_history_route) //min Moderator
+                            .service(routes::page::queue_translation_route) //min Moderator
+                            .service(routes::page::translation_jobs_route) //min Moderator
                             .service(routes::manga::external_search) //min User
                             .service(routes::manga::available_external_search_sites), //min User
                     ),
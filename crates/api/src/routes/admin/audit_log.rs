@@ -0,0 +1,42 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::audit_log::AuditLogDBService;
+use crate::services::db::user::UserDBService;
+use crate::util::page_size;
+use actix_web::post;
+use actix_web::web::{Data, Json};
+use actix_web_grants::protect;
+use api_structure::audit::{AuditEntry, AuditLogRequest};
+
+#[post("/admin/audit_log")]
+#[protect(
+    any("api_structure::auth::role::Role::Admin"),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn search(
+    Json(mut data): Json<AuditLogRequest>,
+    audit_s: Data<AuditLogDBService>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<Json<Vec<AuditEntry>>> {
+    data.limit = page_size(data.limit);
+    let actor = match &data.actor {
+        Some(v) => Some(user_s.get_id(v, false).await?),
+        None => None,
+    };
+    let mut result = vec![];
+    for entry in audit_s.search(actor, &data).await? {
+        result.push(AuditEntry {
+            actor: user_s
+                .get_username(&entry.data.actor.thing.id().to_string())
+                .await
+                .ok_or(ApiError::db_error())?,
+            action: entry.data.action.try_into()?,
+            target: entry.data.target,
+            before: entry.data.before,
+            after: entry.data.after,
+            ip: entry.data.ip,
+            forwarded_for: entry.data.forwarded_for,
+            created: entry.data.created.to_string(),
+        });
+    }
+    Ok(Json(result))
+}
@@ -0,0 +1,3 @@
+mod audit_log;
+
+pub use audit_log::search as audit_log_route;
@@ -0,0 +1,214 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::chapter::{Chapter, ChapterDBService};
+use crate::services::db::comment::{Comment, CommentDBService};
+use crate::services::db::manga::MangaDBService;
+use crate::services::db::user::UserDBService;
+use crate::util::page_size;
+use actix_web::post;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::auth::role::Role;
+use api_structure::comment::{
+    AddCommentRequest, CommentTarget, CommentsRequest, DeleteCommentRequest, EditCommentRequest,
+};
+use std::collections::HashMap;
+use surrealdb::sql::Thing;
+use surrealdb_extras::{RecordData, SurrealTableInfo, ThingType};
+
+const MAX_COMMENT_LEN: usize = 5000;
+
+fn is_moderator(role: Role) -> bool {
+    matches!(role, Role::Admin | Role::CoAdmin | Role::Moderator)
+}
+
+fn validate(text: &str) -> ApiResult<()> {
+    if text.trim().is_empty() {
+        return Err(ApiError::invalid_input("Comment is empty"));
+    }
+    if text.chars().count() > MAX_COMMENT_LEN {
+        return Err(ApiError::invalid_input("Comment is too long"));
+    }
+    Ok(())
+}
+
+/// hides the text of deleted and hidden comments.
+/// moderators and the author still see hidden comments
+async fn to_public(
+    comment: RecordData<Comment>,
+    replies: &HashMap<String, u32>,
+    viewer: &Claim,
+    user_s: &UserDBService,
+) -> ApiResult<api_structure::comment::Comment> {
+    let comment_id = comment.id.id().to_string();
+    let author_id = comment.data.author.thing.id().to_string();
+    let visible = is_moderator(viewer.role)
+        || (!comment.data.deleted && (!comment.data.hidden || author_id == viewer.id));
+    Ok(api_structure::comment::Comment {
+        target: comment.data.target()?,
+        parent: comment.data.parent.map(|v| v.thing.id().to_string()),
+        author_name: user_s.get_username(&author_id).await,
+        author_id,
+        text: Some(comment.data.text).filter(|_| visible),
+        spoiler: comment.data.spoiler,
+        deleted: comment.data.deleted,
+        hidden: comment.data.hidden,
+        edited: comment.data.edited,
+        replies: replies.get(&comment_id).copied().unwrap_or_default(),
+        comment_id,
+        created: comment.data.created.to_string(),
+    })
+}
+
+#[post("/comments")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn list(
+    Json(data): Json<CommentsRequest>,
+    user: ReqData<Claim>,
+    comment_s: Data<CommentDBService>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<Json<Vec<api_structure::comment::Comment>>> {
+    let comments = comment_s
+        .list(
+            &data.target,
+            data.parent.as_deref(),
+            page_size(data.limit),
+            data.page,
+        )
+        .await?;
+    let ids: Vec<_> = comments.iter().map(|v| v.id.id().to_string()).collect();
+    let replies = comment_s.replies(&ids).await?;
+    let mut result = vec![];
+    for comment in comments {
+        result.push(to_public(comment, &replies, &user, &user_s).await?);
+    }
+    Ok(Json(result))
+}
+
+#[post("/comment/add")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn add(
+    Json(data): Json<AddCommentRequest>,
+    user: ReqData<Claim>,
+    comment_s: Data<CommentDBService>,
+    manga_s: Data<MangaDBService>,
+    chapter_s: Data<ChapterDBService>,
+) -> ApiResult<Json<String>> {
+    validate(&data.text)?;
+    match &data.target {
+        CommentTarget::Manga(id) => {
+            manga_s.get(id).await?;
+        }
+        CommentTarget::Chapter(id) => {
+            chapter_s
+                .get_reader(ThingType::from(Thing::from((Chapter::name(), id.as_str()))))
+                .await
+                .map_err(|_| ApiError::invalid_input("Chapter does not exist"))?;
+        }
+    }
+    if let Some(parent) = &data.parent {
+        let parent = comment_s.get(parent).await?;
+        if parent.data.target()? != data.target {
+            return Err(ApiError::invalid_input(
+                "Parent belongs to a different manga or chapter",
+            ));
+        }
+    }
+    let id = comment_s
+        .add(
+            &data.target,
+            data.parent.as_deref(),
+            &user.id,
+            data.text,
+            data.spoiler,
+        )
+        .await?;
+    Ok(Json(id))
+}
+
+#[post("/comment/edit")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn edit(
+    Json(data): Json<EditCommentRequest>,
+    user: ReqData<Claim>,
+    comment_s: Data<CommentDBService>,
+) -> ApiResult<Json<()>> {
+    validate(&data.text)?;
+    let comment = comment_s.get(&data.comment_id).await?;
+    if comment.data.author.thing.id().to_string() != user.id {
+        return Err(ApiError::unothorized_error(
+            "Not allowed to edit this comment",
+            "only the author can edit a comment",
+        ));
+    }
+    if comment.data.deleted {
+        return Err(ApiError::invalid_input("Comment was deleted"));
+    }
+    comment_s
+        .edit(&data.comment_id, data.text, data.spoiler)
+        .await?;
+    Ok(Json(()))
+}
+
+#[post("/comment/delete")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn delete(
+    Json(data): Json<DeleteCommentRequest>,
+    user: ReqData<Claim>,
+    comment_s: Data<CommentDBService>,
+) -> ApiResult<Json<()>> {
+    let comment = comment_s.get(&data.comment_id).await?;
+    if comment.data.author.thing.id().to_string() != user.id {
+        return Err(ApiError::unothorized_error(
+            "Not allowed to delete this comment",
+            "only the author can delete a comment. moderators use comment/moderate",
+        ));
+    }
+    let replies = comment_s.replies(&[data.comment_id.clone()]).await?;
+    match replies.is_empty() {
+        // keeps the thread intact
+        false => {
+            comment_s
+                .set_flags(&data.comment_id, true, comment.data.hidden)
+                .await?
+        }
+        true => comment_s.delete(&data.comment_id).await?,
+    }
+    Ok(Json(()))
+}
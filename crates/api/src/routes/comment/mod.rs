@@ -0,0 +1,8 @@
+mod comments;
+mod moderate;
+
+pub use comments::add as add_comment_route;
+pub use comments::delete as delete_comment_route;
+pub use comments::edit as edit_comment_route;
+pub use comments::list as comments_route;
+pub use moderate::moderate as moderate_comment_route;
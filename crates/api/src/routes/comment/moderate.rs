@@ -0,0 +1,52 @@
+use crate::errors::ApiResult;
+use crate::services::db::audit_log::{to_json, AuditLogDBService};
+use crate::services::db::comment::{Comment, CommentDBService};
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpRequest};
+use actix_web_grants::protect;
+use api_structure::audit::AuditAction;
+use api_structure::auth::jwt::Claim;
+use api_structure::comment::ModerateCommentRequest;
+use surrealdb::sql::Thing;
+use surrealdb_extras::SurrealTableInfo;
+
+#[post("/comment/moderate")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn moderate(
+    Json(data): Json<ModerateCommentRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    comment_s: Data<CommentDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<()>> {
+    let comment = comment_s.get(&data.comment_id).await?;
+    let before = (comment.data.deleted, comment.data.hidden);
+    let after = (
+        data.deleted.unwrap_or(before.0),
+        data.hidden.unwrap_or(before.1),
+    );
+    if before == after {
+        return Ok(Json(()));
+    }
+    comment_s
+        .set_flags(&data.comment_id, after.0, after.1)
+        .await?;
+    audit_s
+        .log(
+            &req,
+            &user.id,
+            AuditAction::CommentModerate,
+            Thing::from((Comment::name(), data.comment_id.as_str())),
+            to_json(&before)?,
+            to_json(&after)?,
+        )
+        .await?;
+    Ok(Json(()))
+}
@@ -1,25 +1,58 @@
 mod multipart;
 mod save;
+mod sign;
 
 use crate::env::config::Config;
+use crate::env::settings::Settings;
 use crate::errors::ApiResult;
+use crate::services::crypto_service::CryptoService;
+use crate::services::db::temp_upload::TempUploadDBService;
 use actix_files::NamedFile;
 use actix_multipart::Multipart;
-use actix_web::post;
+use actix_web::http::header::AUTHORIZATION;
 use actix_web::web::{Data, Json};
+use actix_web::{post, HttpRequest};
 use std::path::PathBuf;
 
+pub use sign::sign_urls as sign_image_urls_route;
+
+/// used before sign up, so a session is optional
 #[post("/upload_images")]
 pub async fn upload_images(
+    req: HttpRequest,
     data: Multipart,
     config: Data<Config>,
+    crypto: Data<CryptoService>,
+    temp_s: Data<TempUploadDBService>,
 ) -> ApiResult<Json<Vec<(String, String)>>> {
-    multipart::upload_images(data, config).await.map(Json)
+    multipart::upload_images(data, config, &temp_s, owner(&req, &crypto))
+        .await
+        .map(Json)
+}
+
+/// user id of a valid access token or the ip of the client
+pub fn owner(req: &HttpRequest, crypto: &CryptoService) -> String {
+    let claim = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .and_then(|v| crypto.decode_claim(v).ok());
+    match claim {
+        Some(claim) => claim.id,
+        // forwarded headers are set by the client, so they can't be used for a quota
+        None => format!(
+            "ip:{}",
+            req.peer_addr()
+                .map(|v| v.ip().to_string())
+                .unwrap_or_default()
+        ),
+    }
 }
 
 #[post("/spinner")]
-pub async fn spinner(config: Data<Config>) -> ApiResult<NamedFile> {
-    let spinner: PathBuf = config.spinner.clone().into();
+pub async fn spinner(config: Data<Config>, settings: Data<Settings>) -> ApiResult<NamedFile> {
+    let spinner: PathBuf = settings.spinner().into();
     let spinner = config.root_folder.join(spinner);
     let file = NamedFile::open(spinner)?;
     Ok(file)
@@ -1,6 +1,7 @@
 use crate::env::config::{random_string, Config};
 use crate::errors::{ApiError, ApiResult};
 use crate::routes::image::save::write_file;
+use crate::services::db::temp_upload::TempUploadDBService;
 use actix_multipart::Multipart;
 use actix_web::web::Data;
 use api_structure::now_timestamp;
@@ -9,8 +10,11 @@ use futures_util::{StreamExt, TryStreamExt};
 pub async fn upload_images(
     mut payload: Multipart,
     config: Data<Config>,
+    temp_s: &TempUploadDBService,
+    owner: String,
 ) -> ApiResult<Vec<(String, String)>> {
     let mut images = vec![];
+    let mut usage = temp_s.usage(&owner).await?;
     while let Some(Ok(mut field)) = payload.next().await {
         let field_name = match field.content_disposition().get_name() {
             Some(v) => v.to_string(),
@@ -37,9 +41,24 @@ pub async fn upload_images(
                     .map_err(ApiError::multipart_read_error)?
                 {
                     file_data.extend_from_slice(&chunk);
+                    if usage + file_data.len() as u64 > config.temp_uploads.quota {
+                        return Err(quota_exceeded());
+                    }
                 }
 
                 let name = write_file(temp_name, &file_name, file_data, &config).await?;
+                let path = config.root_folder.join("temp").join(&name);
+                // the stored file can be larger than the upload and other uploads
+                // of the owner may have finished in the meantime
+                let size = std::fs::metadata(&path)?.len();
+                let added = temp_s
+                    .add_within(name.clone(), owner.clone(), size, config.temp_uploads.quota)
+                    .await?;
+                if !added {
+                    std::fs::remove_file(&path)?;
+                    return Err(quota_exceeded());
+                }
+                usage += size;
                 images.push((file_name, name));
             }
             _ => {
@@ -54,3 +73,7 @@ pub async fn upload_images(
         false => Ok(images),
     }
 }
+
+fn quota_exceeded() -> ApiError {
+    ApiError::invalid_input("Upload quota exceeded. Use or wait for the expiry of older uploads")
+}
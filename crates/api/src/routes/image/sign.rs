@@ -0,0 +1,41 @@
+use crate::errors::ApiResult;
+use crate::services::crypto_service::CryptoService;
+use actix_web::post;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::image::{SignImageUrlsRequest, SignImageUrlsResponse};
+use api_structure::now_timestamp;
+
+/// seconds a signed url is valid at least
+const SIGNED_URL_TTL: u64 = 15 * 60;
+
+#[post("/sign_image_urls")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn sign_urls(
+    Json(data): Json<SignImageUrlsRequest>,
+    user: ReqData<Claim>,
+    crypto: Data<CryptoService>,
+) -> ApiResult<Json<SignImageUrlsResponse>> {
+    // rounded up, so the urls stay the same for a while and can be cached
+    let expires = (now_timestamp()?.as_secs() / SIGNED_URL_TTL + 2) * SIGNED_URL_TTL;
+    let urls = data
+        .images
+        .iter()
+        .map(|v| {
+            let path = v.path();
+            let sig = crypto.sign_url(&path, &user.id, expires);
+            format!("{}?uid={}&exp={}&sig={}", path, user.id, expires, sig)
+        })
+        .collect();
+    Ok(Json(SignImageUrlsResponse { urls, expires }))
+}
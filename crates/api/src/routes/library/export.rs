@@ -0,0 +1,68 @@
+use crate::errors::ApiResult;
+use crate::services::db::manga::MangaDBService;
+use crate::services::db::manga_list::MangaListDBService;
+use crate::services::db::progress::ProgressDBService;
+use crate::services::library::{self, LibraryEntry, ReadingStatus};
+use actix_web::http::header::ContentDisposition;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpResponse};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::library::{ExportLibraryRequest, LibraryFormat};
+use std::collections::BTreeMap;
+
+/// mangas which were started but aren't part of a status list are exported as reading
+#[post("/library/export")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn export(
+    Json(data): Json<ExportLibraryRequest>,
+    user: ReqData<Claim>,
+    manga_s: Data<MangaDBService>,
+    list_s: Data<MangaListDBService>,
+    progress_s: Data<ProgressDBService>,
+) -> ApiResult<HttpResponse> {
+    let mut statuses = BTreeMap::new();
+    for list in list_s.of_user(&user.id).await? {
+        if let Some(status) = ReadingStatus::from_list_name(&list.data.name) {
+            for manga in list.data.mangas {
+                statuses.insert(manga.thing.id().to_string(), status);
+            }
+        }
+    }
+    for manga in progress_s.mangas_of(&user.id).await? {
+        statuses.entry(manga).or_insert(ReadingStatus::Reading);
+    }
+
+    let mut entries = vec![];
+    for (manga_id, status) in statuses {
+        // skips deleted mangas
+        let Ok(manga) = manga_s.get(&manga_id).await else {
+            continue;
+        };
+        entries.push(LibraryEntry {
+            titles: library::export_titles(manga.data.titles),
+            urls: manga.data.sources,
+            status,
+            chapters: progress_s.finished(&user.id, &manga_id).await?,
+            updated: None,
+        });
+    }
+
+    let (content_type, ext) = match data.format {
+        LibraryFormat::MyAnimeList => ("application/xml", "xml"),
+        LibraryFormat::AniList => ("application/json", "json"),
+    };
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(ContentDisposition::attachment(format!("library.{}", ext)))
+        .body(library::write(data.format, &entries)?))
+}
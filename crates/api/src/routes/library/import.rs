@@ -0,0 +1,166 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::chapter::{Chapter, ChapterDBService};
+use crate::services::db::manga::{MangaDBService, MangaLibrary};
+use crate::services::db::manga_list::MangaListDBService;
+use crate::services::db::progress::ProgressDBService;
+use crate::services::library::{self, normalize_title, normalize_url, LibraryEntry, ReadingStatus};
+use actix_multipart::Multipart;
+use actix_web::post;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::library::{ImportLibraryResponse, UnmatchedEntry};
+use futures_util::{StreamExt, TryStreamExt};
+use std::collections::HashMap;
+use surrealdb_extras::{RecordData, ThingType};
+
+struct IndexedManga {
+    id: String,
+    chapters: Vec<ThingType<Chapter>>,
+}
+
+/// finds local mangas by source url or by any of their titles
+struct Index {
+    mangas: Vec<IndexedManga>,
+    /// normalized title => manga. none if the title belongs to multiple mangas
+    titles: HashMap<String, Option<usize>>,
+    /// (normalized url, manga)
+    sources: Vec<(String, usize)>,
+}
+
+impl Index {
+    fn new(data: Vec<RecordData<MangaLibrary>>) -> Self {
+        let mut index = Self {
+            mangas: vec![],
+            titles: HashMap::new(),
+            sources: vec![],
+        };
+        for (pos, manga) in data.into_iter().enumerate() {
+            for title in manga.data.titles.values().flatten() {
+                let title = normalize_title(title);
+                if title.is_empty() {
+                    continue;
+                }
+                index
+                    .titles
+                    .entry(title)
+                    .and_modify(|v| {
+                        if *v != Some(pos) {
+                            *v = None
+                        }
+                    })
+                    .or_insert(Some(pos));
+            }
+            for source in &manga.data.sources {
+                index.sources.push((normalize_url(source), pos));
+            }
+            index.mangas.push(IndexedManga {
+                id: manga.id.id().to_string(),
+                chapters: manga.data.chapters,
+            });
+        }
+        index
+    }
+
+    fn find(&self, entry: &LibraryEntry) -> Option<&IndexedManga> {
+        let by_url = entry.urls.iter().find_map(|url| {
+            let url = normalize_url(url);
+            let prefix = format!("{}/", url);
+            self.sources
+                .iter()
+                .find(|(source, _)| source == &url || source.starts_with(&prefix))
+                .map(|v| v.1)
+        });
+        let pos = by_url.or_else(|| {
+            entry
+                .titles
+                .iter()
+                .find_map(|title| *self.titles.get(&normalize_title(title))?)
+        })?;
+        self.mangas.get(pos)
+    }
+}
+
+/// exports of large libraries are a few mb
+const MAX_FILE_SIZE: usize = 16 * 1024 * 1024;
+
+async fn read_file(mut payload: Multipart) -> ApiResult<String> {
+    while let Some(Ok(mut field)) = payload.next().await {
+        if field.content_disposition().get_name() != Some("file[]") {
+            continue;
+        }
+        let mut data = vec![];
+        while let Some(chunk) = field
+            .try_next()
+            .await
+            .map_err(ApiError::multipart_read_error)?
+        {
+            data.extend_from_slice(&chunk);
+            if data.len() > MAX_FILE_SIZE {
+                return Err(ApiError::invalid_input("File is too large"));
+            }
+        }
+        return String::from_utf8(data).map_err(|_| ApiError::invalid_input("File is not utf-8"));
+    }
+    Err(ApiError::invalid_input("No file given"))
+}
+
+/// moves matched mangas into the list of their status and marks the read chapters as finished.
+/// chapters which were read here keep their progress
+#[post("/library/import")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn import(
+    payload: Multipart,
+    user: ReqData<Claim>,
+    manga_s: Data<MangaDBService>,
+    chapter_s: Data<ChapterDBService>,
+    list_s: Data<MangaListDBService>,
+    progress_s: Data<ProgressDBService>,
+) -> ApiResult<Json<ImportLibraryResponse>> {
+    let entries = library::parse(&read_file(payload).await?)?;
+    let index = Index::new(manga_s.library().await?);
+    let lists: Vec<_> = ReadingStatus::ALL.iter().map(|v| v.list_name()).collect();
+    let mut imported = 0;
+    let mut unmatched = vec![];
+    for entry in entries {
+        let Some(manga) = index.find(&entry) else {
+            unmatched.push(UnmatchedEntry {
+                title: entry.titles.first().cloned().unwrap_or_default(),
+                url: entry.urls.first().cloned(),
+            });
+            continue;
+        };
+        list_s.remove(&user.id, &lists, &manga.id).await?;
+        list_s
+            .add(&user.id, entry.status.list_name(), &manga.id)
+            .await?;
+        if entry.chapters > 0 {
+            for chapter in chapter_s.numbers(manga.chapters.clone()).await? {
+                if chapter.data.chapter <= entry.chapters as f64 {
+                    progress_s
+                        .import(
+                            &user.id,
+                            &manga.id,
+                            &chapter.id.id().to_string(),
+                            entry.updated,
+                        )
+                        .await?;
+                }
+            }
+        }
+        imported += 1;
+    }
+    Ok(Json(ImportLibraryResponse {
+        imported,
+        unmatched,
+    }))
+}
@@ -0,0 +1,5 @@
+mod export;
+mod import;
+
+pub use export::export as export_library_route;
+pub use import::import as import_library_route;
@@ -1,8 +1,9 @@
-use crate::env::config::Config;
 use crate::errors::ApiResult;
-use actix_files::NamedFile;
-use actix_web::post;
-use actix_web::web::{Data, Json};
+use crate::services::auth_service::authorize_url;
+use crate::services::crypto_service::CryptoService;
+use crate::services::storage::{respond, respond_cached, BlobStore, REVALIDATE};
+use actix_web::web::{Data, Json, Path};
+use actix_web::{get, post, HttpRequest, HttpResponse};
 use actix_web_grants::protect;
 use api_structure::image::MangaCoverRequest;
 
@@ -18,13 +19,29 @@ use api_structure::image::MangaCoverRequest;
     ty = "api_structure::auth::role::Role"
 )]
 pub async fn cover_route(
+    req: HttpRequest,
     Json(data): Json<MangaCoverRequest>,
-    config: Data<Config>,
-) -> ApiResult<NamedFile> {
-    Ok(NamedFile::open(
-        config
-            .root_folder
-            .join("covers")
-            .join(format!("{}.{}", data.manga_id, data.file_ext)),
-    )?)
+    store: Data<dyn BlobStore>,
+) -> ApiResult<HttpResponse> {
+    respond(
+        &req,
+        &**store,
+        &format!("covers/{}.{}", data.manga_id, data.file_ext),
+    )
+    .await
+}
+
+/// cacheable version of [cover_route]. covers can be replaced, so they are revalidated.
+/// outside of the bearer scope to support signed urls
+#[get("/cover/{manga_id}.{file_ext}")]
+pub async fn cover_get_route(
+    req: HttpRequest,
+    data: Path<MangaCoverRequest>,
+    store: Data<dyn BlobStore>,
+    crypto: Data<CryptoService>,
+) -> ApiResult<HttpResponse> {
+    authorize_url(&req, &crypto, &data.path())?;
+    let key = format!("covers/{}.{}", data.manga_id, data.file_ext);
+    let version = store.stat(&key).await?.map(|v| v.version);
+    respond_cached(&req, &**store, &key, version.as_deref(), REVALIDATE).await
 }
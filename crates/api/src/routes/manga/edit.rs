@@ -0,0 +1,226 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::audit_log::{to_json, AuditLogDBService};
+use crate::services::db::manga::{Manga, MangaDBService, MangaMetadata};
+use crate::services::db::manga_revision::{apply, diff, MangaRevisionDBService};
+use crate::services::db::tag::TagDBService;
+use crate::services::db::user::{User, UserDBService};
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpRequest};
+use actix_web_grants::protect;
+use api_structure::audit::AuditAction;
+use api_structure::auth::jwt::Claim;
+use api_structure::auth::role::Role;
+use api_structure::info::{
+    EditMangaRequest, MangaFieldChange, MangaHistoryRequest, MangaRevision, RollbackMangaRequest,
+};
+use serde_json::{Map, Value};
+use surrealdb::sql::Thing;
+use surrealdb_extras::{SurrealTableInfo, ThingType};
+
+async fn users(names: Vec<String>, user_s: &UserDBService) -> ApiResult<Vec<ThingType<User>>> {
+    let mut result = vec![];
+    for name in names {
+        let id = user_s.get_id(&name, false).await?;
+        result.push(ThingType::from(Thing::from((User::name(), id.as_str()))));
+    }
+    Ok(result)
+}
+
+/// writes `new` and records the changed fields. does nothing if nothing changed
+async fn save(
+    manga_id: &str,
+    editor: &str,
+    old: &MangaMetadata,
+    new: MangaMetadata,
+    manga_s: &MangaDBService,
+    revision_s: &MangaRevisionDBService,
+) -> ApiResult<Option<(Map<String, Value>, Map<String, Value>)>> {
+    let (before, after) = diff(old, &new)?;
+    if after.is_empty() {
+        return Ok(None);
+    }
+    manga_s.set_metadata(manga_id, new).await?;
+    revision_s
+        .add(manga_id, editor, before.clone(), after.clone())
+        .await?;
+    Ok(Some((before, after)))
+}
+
+async fn log(
+    req: &HttpRequest,
+    action: AuditAction,
+    manga_id: &str,
+    editor: &str,
+    changes: Option<(Map<String, Value>, Map<String, Value>)>,
+    audit_s: &AuditLogDBService,
+) -> ApiResult<()> {
+    if let Some((before, after)) = changes {
+        audit_s
+            .log(
+                req,
+                editor,
+                action,
+                Thing::from((Manga::name(), manga_id)),
+                to_json(&before)?,
+                to_json(&after)?,
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+#[post("/manga/edit")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn edit(
+    Json(data): Json<EditMangaRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    manga_s: Data<MangaDBService>,
+    revision_s: Data<MangaRevisionDBService>,
+    audit_s: Data<AuditLogDBService>,
+    tag_s: Data<TagDBService>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<Json<()>> {
+    let manga = manga_s.get(&data.manga_id).await?;
+    let allowed = user.role as u32 >= Role::Moderator as u32
+        || manga.data.uploader.thing.id().to_string() == user.id
+        || manga
+            .data
+            .authors
+            .iter()
+            .any(|v| v.thing.id().to_string() == user.id);
+    if !allowed {
+        return Err(ApiError::unothorized_error(
+            "Not allowed to edit this manga",
+            "not uploader, author or moderator",
+        ));
+    }
+    let old = manga_s.get_metadata(&data.manga_id).await?;
+    let mut new = manga_s.get_metadata(&data.manga_id).await?;
+    if let Some(titles) = data.titles {
+        if titles.values().all(|v| v.is_empty()) {
+            return Err(ApiError::invalid_input("Manga needs at least one title"));
+        }
+        new.titles = titles;
+    }
+    if let Some(description) = data.description {
+        new.description = Some(description).filter(|v| !v.is_empty());
+    }
+    if let Some(tags) = data.tags {
+        let mut ids = vec![];
+        for tag in tags {
+            ids.push(tag_s.get_or_create(tag).await?);
+        }
+        new.tags = ids;
+    }
+    if let Some(status) = data.status {
+        new.status = status as u64;
+    }
+    if let Some(authors) = data.authors {
+        new.authors = users(authors, &user_s).await?;
+    }
+    if let Some(artists) = data.artists {
+        new.artists = users(artists, &user_s).await?;
+    }
+    if let Some(sources) = data.sources {
+        new.sources = sources;
+    }
+    let changes = save(&data.manga_id, &user.id, &old, new, &manga_s, &revision_s).await?;
+    log(
+        &req,
+        AuditAction::MangaEdit,
+        &data.manga_id,
+        &user.id,
+        changes,
+        &audit_s,
+    )
+    .await?;
+    Ok(Json(()))
+}
+
+#[post("/manga/history")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn history(
+    Json(data): Json<MangaHistoryRequest>,
+    revision_s: Data<MangaRevisionDBService>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<Json<Vec<MangaRevision>>> {
+    let mut result = vec![];
+    for revision in revision_s.history(&data.manga_id).await? {
+        let mut changes = vec![];
+        for (field, before, after) in revision.data.changes()? {
+            changes.push(MangaFieldChange {
+                field,
+                before: serde_json::to_string(&before)?,
+                after: serde_json::to_string(&after)?,
+            });
+        }
+        result.push(MangaRevision {
+            revision_id: revision.id.id().to_string(),
+            editor: user_s
+                .get_username(&revision.data.editor.thing.id().to_string())
+                .await
+                .ok_or(ApiError::db_error())?,
+            created: revision.data.created.to_string(),
+            changes,
+        });
+    }
+    Ok(Json(result))
+}
+
+#[post("/manga/rollback")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn rollback(
+    Json(data): Json<RollbackMangaRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    manga_s: Data<MangaDBService>,
+    revision_s: Data<MangaRevisionDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<()>> {
+    let history = revision_s.history(&data.manga_id).await?;
+    let pos = history
+        .iter()
+        .position(|v| v.id.id().to_string() == data.revision_id)
+        .ok_or(ApiError::invalid_input("Revision does not belong to manga"))?;
+    let old = manga_s.get_metadata(&data.manga_id).await?;
+    let mut new = manga_s.get_metadata(&data.manga_id).await?;
+    // undo every newer revision, newest first
+    for revision in &history[..pos] {
+        new = apply(&new, revision.data.before()?)?;
+    }
+    let changes = save(&data.manga_id, &user.id, &old, new, &manga_s, &revision_s).await?;
+    log(
+        &req,
+        AuditAction::MangaRollback,
+        &data.manga_id,
+        &user.id,
+        changes,
+        &audit_s,
+    )
+    .await?;
+    Ok(Json(()))
+}
@@ -3,6 +3,7 @@ use crate::services::db::manga::{Manga, MangaDBService};
 use crate::services::db::manga_kind::MangaKindDBService;
 use crate::services::db::tag::TagDBService;
 use crate::services::db::user::UserDBService;
+use crate::services::recommendation_service::RecommendationStore;
 use actix_web::post;
 use actix_web::web::{Data, Json, ReqData};
 use actix_web_grants::protect;
@@ -30,6 +31,7 @@ pub async fn home(
     user_service: Data<UserDBService>,
     kind_service: Data<MangaKindDBService>,
     tag_service: Data<TagDBService>,
+    recommendations: Data<RecommendationStore>,
 ) -> ApiResult<Json<HomeResponse>> {
     let generate = |order, desc, query| {
         let query = match query {
@@ -60,6 +62,13 @@ pub async fn home(
     // );
     let latest_updates = generate(Order::Updated, true, None);
     let random = generate(Order::Random, false, None);
+    // mangas which were deleted since the last computation are skipped
+    let ids = recommendations.get(&user.id);
+    let mut recommended = manga.get_many(&ids).await?;
+    recommended.sort_by_key(|v| {
+        let id = v.id.id().to_string();
+        ids.iter().position(|v| v == &id)
+    });
     Ok(Json(HomeResponse {
         trending: vec![], //format(manga.search(trending, &user.id).await?, &tags).await,
         newest: format(
@@ -92,6 +101,7 @@ pub async fn home(
             &tags,
         )
         .await?,
+        recommended: format(recommended, &tags).await?,
     }))
 }
 
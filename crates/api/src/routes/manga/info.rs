@@ -2,6 +2,8 @@ use crate::errors::{ApiError, ApiResult};
 use crate::services::db::chapter::Chapter;
 use crate::services::db::manga::MangaDBService;
 use crate::services::db::manga_kind::MangaKindDBService;
+use crate::services::db::manga_relation::MangaRelationDBService;
+use crate::services::db::rating::RatingDBService;
 use crate::services::db::tag::TagDBService;
 use crate::services::db::user::UserDBService;
 use crate::services::uri_service::UriService;
@@ -9,9 +11,12 @@ use actix_web::post;
 use actix_web::web::{Data, Json, ReqData};
 use actix_web_grants::protect;
 use api_structure::auth::jwt::Claim;
-use api_structure::info::{ExternalSite, MangaInfoRequest, MangaInfoResponse, Visibility};
+use api_structure::info::{
+    ExternalSite, MangaInfoRequest, MangaInfoResponse, MangaRelation, RelationKind, Visibility,
+};
 use api_structure::search::Status;
 use rand::Rng;
+use std::collections::HashMap;
 
 #[post("/info")]
 #[protect(
@@ -26,14 +31,16 @@ use rand::Rng;
 )]
 pub async fn info(
     Json(req): Json<MangaInfoRequest>,
-    manga: Data<MangaDBService>,
+    manga_s: Data<MangaDBService>,
+    relation_s: Data<MangaRelationDBService>,
+    rating_s: Data<RatingDBService>,
     tags_s: Data<TagDBService>,
     user: ReqData<Claim>,
     kind_s: Data<MangaKindDBService>,
     user_s: Data<UserDBService>,
     uri: Data<UriService>,
 ) -> ApiResult<Json<MangaInfoResponse>> {
-    let manga = manga.get(req.manga_id.as_str()).await?;
+    let manga = manga_s.get(req.manga_id.as_str()).await?;
     let kind = kind_s
         .get_kind(&manga.data.kind.thing.id().to_string())
         .await
@@ -96,6 +103,36 @@ pub async fn info(
         });
     }
 
+    let related = relation_s.get(&req.manga_id).await?;
+    let ids: Vec<String> = related
+        .iter()
+        .map(|v| v.data.related.thing.id().to_string())
+        .collect();
+    let mut mangas: HashMap<String, _> = manga_s
+        .get_many(&ids)
+        .await?
+        .into_iter()
+        .map(|v| (v.id.id().to_string(), v.data))
+        .collect();
+    let mut relations = vec![];
+    for (relation, manga_id) in related.into_iter().zip(ids) {
+        // the other manga was deleted
+        let Some(other) = mangas.remove(&manga_id) else {
+            continue;
+        };
+        let cover = match other.covers.len() {
+            0 => None,
+            len => Some(rand::thread_rng().gen_range(0..len)),
+        };
+        relations.push(MangaRelation {
+            manga_id,
+            kind: RelationKind::try_from(relation.data.kind)?,
+            titles: other.titles,
+            cover: cover.unwrap_or_default() as u32,
+            cover_ext: cover.map(|v| other.covers[v].clone()).unwrap_or_default(),
+        });
+    }
+
     Ok(Json(MangaInfoResponse {
         manga_id: manga.id.0.id.to_string(),
         titles: manga.data.titles,
@@ -124,12 +161,8 @@ pub async fn info(
             })
             .collect(),
         scraper: !manga.data.scraper.is_empty(),
-        relations: manga
-            .data
-            .relations
-            .into_iter()
-            .map(|v| (v.thing.id().to_string(), "".to_string()))
-            .collect(),
+        relations,
+        rating: rating_s.summary(&req.manga_id, &user.id).await?,
         favorite: false,
         progress: None,
     }))
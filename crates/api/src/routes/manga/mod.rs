@@ -1,18 +1,36 @@
 mod cover;
+mod edit;
 mod external;
 mod home;
 mod info;
+mod rating;
 mod reader;
+mod relation;
 mod search;
+mod sync;
+mod visibility;
 
+pub use cover::cover_get_route;
 pub use cover::cover_route;
+pub use edit::edit as edit_manga_route;
+pub use edit::history as manga_history_route;
+pub use edit::rollback as rollback_manga_route;
 pub use external::available_external_search_sites;
 pub use external::search as external_search;
 pub use home::home as home_route;
 pub use info::info as info_route;
+pub use rating::delete as delete_rating_route;
+pub use rating::list as ratings_route;
+pub use rating::set as rate_manga_route;
+pub use reader::chapter_page_get_route;
 pub use reader::chapter_page_route;
 pub use reader::get_pages as pages_route;
 pub use reader::info as reader_info_route;
+pub use reader::set_progress as set_progress_route;
 pub use reader::translation as translation_route;
+pub use relation::add as add_relation_route;
+pub use relation::delete as delete_relation_route;
 pub use search::search as search_route;
-pub use reader::is_valid_translation;
+pub use sync::progress_sync as progress_sync_route;
+pub use sync::sync_ticket as sync_ticket_route;
+pub use visibility::set_visibility as visibility_route;
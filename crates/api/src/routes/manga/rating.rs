@@ -0,0 +1,100 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::manga::MangaDBService;
+use crate::services::db::rating::RatingDBService;
+use crate::services::db::user::UserDBService;
+use actix_web::post;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::rating::{
+    DeleteRatingRequest, RateMangaRequest, Rating, RatingsRequest, MAX_SCORE, MIN_SCORE,
+};
+
+const MAX_REVIEW_LEN: usize = 10000;
+
+#[post("/rating/set")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn set(
+    Json(data): Json<RateMangaRequest>,
+    user: ReqData<Claim>,
+    manga_s: Data<MangaDBService>,
+    rating_s: Data<RatingDBService>,
+) -> ApiResult<Json<()>> {
+    if !(MIN_SCORE..=MAX_SCORE).contains(&data.score) {
+        return Err(ApiError::invalid_input("Score has to be between 1 and 10"));
+    }
+    let review = data.review.filter(|v| !v.trim().is_empty());
+    if review
+        .as_ref()
+        .is_some_and(|v| v.chars().count() > MAX_REVIEW_LEN)
+    {
+        return Err(ApiError::invalid_input("Review is too long"));
+    }
+    manga_s.get(&data.manga_id).await?;
+    rating_s
+        .set(&data.manga_id, &user.id, data.score, review)
+        .await?;
+    Ok(Json(()))
+}
+
+#[post("/rating/delete")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn delete(
+    Json(data): Json<DeleteRatingRequest>,
+    user: ReqData<Claim>,
+    rating_s: Data<RatingDBService>,
+) -> ApiResult<Json<()>> {
+    rating_s.delete(&data.manga_id, &user.id).await?;
+    Ok(Json(()))
+}
+
+#[post("/ratings")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn list(
+    Json(data): Json<RatingsRequest>,
+    rating_s: Data<RatingDBService>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<Json<Vec<Rating>>> {
+    let ratings = rating_s
+        .list(&data.manga_id, data.reviews_only, data.limit, data.page)
+        .await?;
+    let mut result = vec![];
+    for rating in ratings {
+        let user_id = rating.data.user.thing.id().to_string();
+        result.push(Rating {
+            user_name: user_s.get_username(&user_id).await,
+            user_id,
+            score: rating.data.score,
+            review: rating.data.review,
+            updated: rating.data.updated.to_string(),
+        });
+    }
+    Ok(Json(result))
+}
@@ -1,27 +1,28 @@
-use crate::env::config::Config;
 use crate::errors::{ApiError, ApiResult};
+use crate::services::auth_service::authorize_url;
+use crate::services::crypto_service::CryptoService;
 use crate::services::db::chapter::ChapterDBService;
 use crate::services::db::chapter_version::ChapterVersionDBService;
 use crate::services::db::manga::MangaDBService;
 use crate::services::db::manga_kind::MangaKindDBService;
 use crate::services::db::page::PageDBService;
-use crate::services::db::progress::ProgressDBService;
-use actix_files::NamedFile;
-use actix_web::post;
-use std::sync::Arc;
-use actix_web::web::{Data, Json, ReqData};
+use crate::services::db::progress::{ProgressDBService, FINISHED};
+use crate::services::db::reading_stats::ReadingStatsDBService;
+use crate::services::db::translation::TranslationDBService;
+use crate::services::storage::{page_key, respond, respond_cached, BlobStore, IMMUTABLE};
+use crate::services::sync_service::{ProgressChange, ProgressHub};
+use actix_web::web::{Data, Json, Path, ReqData};
+use actix_web::{get, post, HttpRequest, HttpResponse};
 use actix_web_grants::protect;
 use api_structure::auth::jwt::Claim;
 use api_structure::error::{ApiErr, ApiErrorType};
 use api_structure::image::MangaReaderImageRequest;
 use api_structure::reader::{
-    MangaReaderRequest, MangaReaderResponse, Progress, ReaderPage, ReaderPageRequest,
-    ReaderPageResponse, TranslationArea,
+    MangaReaderRequest, MangaReaderResponse, PageTranslationRequest, Progress, ProgressUpdate,
+    ReaderPage, ReaderPageRequest, ReaderPageResponse, SetProgressRequest, TranslationArea,
 };
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::read_to_string;
+use chrono::Utc;
+use std::sync::Arc;
 
 #[post("/pages")]
 #[protect(
@@ -40,9 +41,18 @@ pub async fn get_pages(
     page_s: Data<PageDBService>,
 ) -> ApiResult<Json<ReaderPageResponse>> {
     let mut pages = Vec::new();
+    let mut hide_top: Option<f64> = None;
+    let mut hide_bottom: Option<f64> = None;
     for page in cvs.get(&req.chapter_version_id).await? {
         let page_id = page.thing.id().to_string();
         let page = page_s.get(page).await?;
+        let crop = page.crop();
+        if page.height > 0 {
+            let top = crop.min_y as f64 / page.height as f64;
+            let bottom = (page.height - crop.max_y) as f64 / page.height as f64;
+            hide_top = Some(hide_top.map_or(top, |v| v.min(top)));
+            hide_bottom = Some(hide_bottom.map_or(bottom, |v| v.min(bottom)));
+        }
         pages.push((
             page.page,
             ReaderPage {
@@ -57,6 +67,7 @@ pub async fn get_pages(
                     width_end: 0.0,
                     height_end: 0.0,
                 },
+                crop,
             },
         ));
     }
@@ -87,9 +98,9 @@ pub async fn get_pages(
     }
     Ok(Json(ReaderPageResponse {
         version_id: req.chapter_version_id,
-        hide_top: 0.0,
-        hide_bottom: 0.0,
-        pages: pages.into_iter().map(|(a, b)|(a, Arc::new(b))).collect(),
+        hide_top: hide_top.unwrap_or_default(),
+        hide_bottom: hide_bottom.unwrap_or_default(),
+        pages: pages.into_iter().map(|(a, b)| (a, Arc::new(b))).collect(),
     }))
 }
 
@@ -161,19 +172,18 @@ pub async fn info(
     ty = "api_structure::auth::role::Role"
 )]
 pub async fn chapter_page_route(
+    req: HttpRequest,
     Json(data): Json<MangaReaderImageRequest>,
-    config: Data<Config>,
-) -> ApiResult<NamedFile> {
+    store: Data<dyn BlobStore>,
+) -> ApiResult<HttpResponse> {
     if let Some(version_id) = data.version_id.strip_prefix("chapter_versions:") {
-        Ok(NamedFile::open(
-            config
-                .root_folder
-                .join("mangas")
-                .join(data.manga_id)
-                .join(data.chapter_id)
-                .join(version_id)
-                .join(format!("{}.{}", data.page, data.file_ext)),
-        )?)
+        let key = page_key(
+            &data.manga_id,
+            &data.chapter_id,
+            version_id,
+            &format!("{}.{}", data.page, data.file_ext),
+        );
+        respond(&req, &**store, &key).await
     } else {
         Err(ApiErr {
             message: Some("invalid version_id_prefix".to_string()),
@@ -184,21 +194,29 @@ pub async fn chapter_page_route(
     }
 }
 
-impl From<Translation> for TranslationArea {
-    fn from(value: Translation) -> Self {
-        let mut hm = HashMap::new();
-        hm.insert("eng_ichigo".to_string(), value.translated_text);
-        Self {
-            translated_text: hm,
-            min_x: value.min_x,
-            min_y: value.min_y,
-            max_x: value.max_x,
-            max_y: value.max_y,
-            text_color: [0; 3],
-            outline_color: [255; 3],
-            background: value.background,
-        }
-    }
+/// cacheable version of [chapter_page_route]. `version_id` is without table prefix.
+/// the stored page hash is the ETag. outside of the bearer scope to support signed urls
+#[get("/chapter_page/{manga_id}/{chapter_id}/{version_id}/{page}.{file_ext}")]
+pub async fn chapter_page_get_route(
+    req: HttpRequest,
+    data: Path<MangaReaderImageRequest>,
+    store: Data<dyn BlobStore>,
+    crypto: Data<CryptoService>,
+    page_s: Data<PageDBService>,
+) -> ApiResult<HttpResponse> {
+    authorize_url(&req, &crypto, &data.path())?;
+    let hash = page_s
+        .hash(&data.chapter_id, &data.version_id, data.page)
+        .await?
+        .ok_or(ApiError::not_found("Page does not exist"))?;
+    let hash = Some(hash).filter(|v| !v.is_empty());
+    let key = page_key(
+        &data.manga_id,
+        &data.chapter_id,
+        &data.version_id,
+        &format!("{}.{}", data.page, data.file_ext),
+    );
+    respond_cached(&req, &**store, &key, hash.as_deref(), IMMUTABLE).await
 }
 
 #[post("page_translation")]
@@ -213,54 +231,79 @@ impl From<Translation> for TranslationArea {
     ty = "api_structure::auth::role::Role"
 )]
 async fn translation(
-    Json(data): Json<MangaReaderImageRequest>,
-    config: Data<Config>,
+    Json(data): Json<PageTranslationRequest>,
+    chapter_s: Data<ChapterDBService>,
+    cvs: Data<ChapterVersionDBService>,
+    page_s: Data<PageDBService>,
+    translation_s: Data<TranslationDBService>,
 ) -> ApiResult<Json<Vec<TranslationArea>>> {
-    if let Some(version_id) = data.version_id.strip_prefix("chapter_versions:") {
-        let s = read_to_string(File::open(
-            config
-                .root_folder
-                .join("mangas")
-                .join(data.manga_id)
-                .join(data.chapter_id)
-                .join(version_id)
-                .join(format!("{}.json", data.page)),
-        )?)?;
-        let mut v: TranslationResponse = serde_json::from_str(&s)?;
-        Ok(Json(
-            v.images.remove(0).into_iter().map(|v| v.into()).collect(),
-        ))
-    } else {
-        Err(ApiErr {
-            message: Some("invalid version_id_prefix".to_string()),
-            cause: None,
-            err_type: ApiErrorType::InvalidInput,
-        }
-        .into())
-    }
-}
-
-#[derive(Serialize, Deserialize)]
-struct Translation {
-    #[serde(rename = "translatedText")]
-    pub translated_text: String,
-    #[serde(rename = "minX")]
-    pub min_x: u32,
-    #[serde(rename = "minY")]
-    pub min_y: u32,
-    #[serde(rename = "maxX")]
-    pub max_x: u32,
-    #[serde(rename = "maxY")]
-    pub max_y: u32,
-    pub background: String,
-}
-
-#[derive(Serialize, Deserialize)]
-struct TranslationResponse {
-    pub images: Vec<Vec<Translation>>,
+    let connection = chapter_s
+        .get_version(&data.chapter_id, &data.version_id)
+        .await?;
+    let page = page_s.find(cvs.get(&connection).await?, data.page).await?;
+    let areas = translation_s
+        .get(&page.id.id().to_string(), data.language.as_deref())
+        .await?;
+    Ok(Json(
+        areas.into_iter().map(|v| v.data.to_public()).collect(),
+    ))
 }
 
-pub fn is_valid_translation(s: &str) -> bool{
-    let v: Result<TranslationResponse, _> = serde_json::from_str(s);
-    v.is_ok()
+#[post("/progress")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn set_progress(
+    Json(data): Json<SetProgressRequest>,
+    user: ReqData<Claim>,
+    manga_s: Data<MangaDBService>,
+    chapter_s: Data<ChapterDBService>,
+    cvs: Data<ChapterVersionDBService>,
+    progress_s: Data<ProgressDBService>,
+    stats_s: Data<ReadingStatsDBService>,
+    hub: Data<ProgressHub>,
+) -> ApiResult<Json<()>> {
+    if !(0.0..=1.0).contains(&data.progress) {
+        return Err(ApiError::invalid_input(
+            "Progress has to be between 0 and 1",
+        ));
+    }
+    // the stats and the sync use the manga, so it has to be the one of the chapter
+    if !manga_s
+        .has_chapter(&data.manga_id, &data.chapter_id)
+        .await?
+    {
+        return Err(ApiError::invalid_input("Chapter is not part of the manga"));
+    }
+    let connection = chapter_s
+        .get_version(&data.chapter_id, &data.version_id)
+        .await?;
+    let pages = cvs.get(&connection).await?.len() as f64;
+    let before = progress_s
+        .set_progress(&user.id, &data.manga_id, &data.chapter_id, data.progress)
+        .await?;
+    let read = ((data.progress - before).max(0.0) * pages).round() as u32;
+    let finished = (before < FINISHED && data.progress >= FINISHED) as u32;
+    stats_s
+        .record(&user.id, &data.manga_id, read, finished)
+        .await?;
+    hub.publish(ProgressChange {
+        user: user.id.clone(),
+        session: data.session,
+        update: ProgressUpdate {
+            manga_id: data.manga_id,
+            chapter_id: data.chapter_id,
+            version_id: data.version_id,
+            progress: data.progress,
+            updated: Utc::now().to_rfc3339(),
+        },
+    });
+    Ok(Json(()))
 }
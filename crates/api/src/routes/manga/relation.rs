@@ -0,0 +1,77 @@
+use crate::errors::ApiResult;
+use crate::services::db::audit_log::{to_json, AuditLogDBService};
+use crate::services::db::manga::{Manga, MangaDBService};
+use crate::services::db::manga_relation::MangaRelationDBService;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpRequest};
+use actix_web_grants::protect;
+use api_structure::audit::AuditAction;
+use api_structure::auth::jwt::Claim;
+use api_structure::info::{AddRelationRequest, DeleteRelationRequest};
+use surrealdb::sql::Thing;
+use surrealdb_extras::SurrealTableInfo;
+
+#[post("/relation/add")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn add(
+    Json(data): Json<AddRelationRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    manga_s: Data<MangaDBService>,
+    relation_s: Data<MangaRelationDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<()>> {
+    manga_s.get(&data.manga_id).await?;
+    manga_s.get(&data.related_id).await?;
+    relation_s
+        .add(&data.manga_id, &data.related_id, data.kind)
+        .await?;
+    audit_s
+        .log(
+            &req,
+            &user.id,
+            AuditAction::RelationAdd,
+            Thing::from((Manga::name(), data.manga_id.as_str())),
+            None,
+            to_json(&data)?,
+        )
+        .await?;
+    Ok(Json(()))
+}
+
+#[post("/relation/delete")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn delete(
+    Json(data): Json<DeleteRelationRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    relation_s: Data<MangaRelationDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<()>> {
+    relation_s.delete(&data.manga_id, &data.related_id).await?;
+    audit_s
+        .log(
+            &req,
+            &user.id,
+            AuditAction::RelationDelete,
+            Thing::from((Manga::name(), data.manga_id.as_str())),
+            to_json(&data)?,
+            None,
+        )
+        .await?;
+    Ok(Json(()))
+}
@@ -0,0 +1,104 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::sync_service::{ProgressChange, ProgressHub};
+use actix_web::web::{Data, Json, Payload, Query, ReqData};
+use actix_web::{get, post, HttpRequest, HttpResponse};
+use actix_web_grants::protect;
+use actix_ws::{Message, Session};
+use api_structure::auth::jwt::Claim;
+use api_structure::reader::SyncMessage;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::Receiver;
+
+#[derive(Deserialize)]
+pub struct SyncQuery {
+    ticket: String,
+}
+
+#[post("/sync/ticket")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn sync_ticket(user: ReqData<Claim>, hub: Data<ProgressHub>) -> Json<String> {
+    Json(hub.issue_ticket(user.id.clone()))
+}
+
+/// outside of the bearer scope. the ticket is checked here
+#[get("/sync/progress")]
+pub async fn progress_sync(
+    req: HttpRequest,
+    body: Payload,
+    Query(query): Query<SyncQuery>,
+    hub: Data<ProgressHub>,
+) -> ApiResult<HttpResponse> {
+    let user = hub.redeem_ticket(&query.ticket).ok_or_else(|| {
+        ApiError::unothorized_error("Invalid ticket", "needs a ticket of sync/ticket")
+    })?;
+    let (response, session, messages) =
+        actix_ws::handle(&req, body).map_err(|e| ApiError::invalid_input(e.to_string()))?;
+    actix_web::rt::spawn(forward(
+        session,
+        messages,
+        hub.subscribe(),
+        user,
+        nanoid::nanoid!(),
+    ));
+    Ok(response)
+}
+
+async fn forward(
+    mut session: Session,
+    mut messages: actix_ws::MessageStream,
+    mut changes: Receiver<ProgressChange>,
+    user: String,
+    id: String,
+) {
+    if send(
+        &mut session,
+        &SyncMessage::Hello {
+            session: id.clone(),
+        },
+    )
+    .await
+    .is_err()
+    {
+        return;
+    }
+    loop {
+        tokio::select! {
+            msg = messages.next() => match msg {
+                Some(Ok(Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // clients only listen
+                Some(Ok(_)) => {}
+            },
+            change = changes.recv() => match change {
+                Ok(change) if change.user == user && change.session.as_ref() != Some(&id) => {
+                    if send(&mut session, &SyncMessage::Progress(change.update)).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+    let _ = session.close(None).await;
+}
+
+async fn send(session: &mut Session, msg: &SyncMessage) -> Result<(), ()> {
+    let text = serde_json::to_string(msg).map_err(|_| ())?;
+    session.text(text).await.map_err(|_| ())
+}
@@ -0,0 +1,45 @@
+use crate::errors::ApiResult;
+use crate::services::db::audit_log::{to_json, AuditLogDBService};
+use crate::services::db::manga::{Manga, MangaDBService};
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpRequest};
+use actix_web_grants::protect;
+use api_structure::audit::{AuditAction, SetVisibilityRequest};
+use api_structure::auth::jwt::Claim;
+use api_structure::info::Visibility;
+use surrealdb::sql::Thing;
+use surrealdb_extras::SurrealTableInfo;
+
+#[post("/manga/visibility")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn set_visibility(
+    Json(data): Json<SetVisibilityRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    manga_s: Data<MangaDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<()>> {
+    let manga = manga_s.get(&data.manga_id).await?;
+    let before = Visibility::try_from(manga.data.visibility)?;
+    manga_s
+        .set_visibility(&data.manga_id, data.visibility as u64)
+        .await?;
+    audit_s
+        .log(
+            &req,
+            &user.id,
+            AuditAction::VisibilityChange,
+            Thing::from((Manga::name(), data.manga_id.as_str())),
+            to_json(&before)?,
+            to_json(&data.visibility)?,
+        )
+        .await?;
+    Ok(Json(()))
+}
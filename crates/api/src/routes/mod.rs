@@ -1,6 +1,11 @@
+pub mod admin;
 pub mod chapter;
+pub mod comment;
 pub mod frontend;
 pub mod image;
+pub mod library;
 pub mod manga;
+pub mod notification;
+pub mod openapi;
 pub mod page;
 pub mod user;
@@ -0,0 +1,96 @@
+use crate::errors::ApiResult;
+use crate::services::db::chapter::ChapterDBService;
+use crate::services::db::manga::MangaDBService;
+use crate::services::db::notification::NotificationDBService;
+use crate::util::page_size;
+use actix_web::post;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::notification::{
+    MarkNotificationsReadRequest, Notification, NotificationsRequest,
+};
+use std::collections::HashMap;
+use surrealdb_extras::ThingType;
+
+#[post("/notifications")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn list(
+    Json(data): Json<NotificationsRequest>,
+    user: ReqData<Claim>,
+    notification_s: Data<NotificationDBService>,
+    manga_s: Data<MangaDBService>,
+    chapter_s: Data<ChapterDBService>,
+) -> ApiResult<Json<Vec<Notification>>> {
+    let items = notification_s
+        .list(&user.id, data.unread_only, page_size(data.limit), data.page)
+        .await?;
+    let chapters: HashMap<String, f64> = chapter_s
+        .numbers(
+            items
+                .iter()
+                .map(|v| ThingType::from(v.data.chapter.thing.0.clone()))
+                .collect(),
+        )
+        .await?
+        .into_iter()
+        .map(|v| (v.id.id().to_string(), v.data.chapter))
+        .collect();
+    let mut titles = HashMap::new();
+    let mut result = vec![];
+    for item in items {
+        let chapter_id = item.data.chapter.thing.id().to_string();
+        // chapter might have been deleted since
+        let Some(chapter) = chapters.get(&chapter_id).copied() else {
+            continue;
+        };
+        let manga_id = item.data.manga.thing.id().to_string();
+        if !titles.contains_key(&manga_id) {
+            // manga might have been deleted since
+            let t = match manga_s.get(&manga_id).await {
+                Ok(v) => v.data.titles,
+                Err(_) => HashMap::new(),
+            };
+            titles.insert(manga_id.clone(), t);
+        }
+        result.push(Notification {
+            notification_id: item.id.id().to_string(),
+            titles: titles.get(&manga_id).cloned().unwrap_or_default(),
+            manga_id,
+            chapter_id,
+            chapter,
+            read: item.data.read,
+            created: item.data.created.to_string(),
+        });
+    }
+    Ok(Json(result))
+}
+
+#[post("/notifications/read")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn mark_read(
+    Json(data): Json<MarkNotificationsReadRequest>,
+    user: ReqData<Claim>,
+    notification_s: Data<NotificationDBService>,
+) -> ApiResult<Json<u32>> {
+    notification_s.mark_read(&user.id, data.ids).await?;
+    Ok(Json(notification_s.unread(&user.id).await?))
+}
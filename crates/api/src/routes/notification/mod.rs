@@ -0,0 +1,10 @@
+mod list;
+mod settings;
+mod stream;
+
+pub use list::list as notifications_route;
+pub use list::mark_read as mark_notifications_read_route;
+pub use settings::edit_settings as edit_notification_settings_route;
+pub use settings::mute as mute_manga_route;
+pub use settings::settings as notification_settings_route;
+pub use stream::stream as notification_stream_route;
@@ -0,0 +1,67 @@
+use crate::errors::ApiResult;
+use crate::services::db::notification::NotificationDBService;
+use actix_web::post;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::notification::{MuteMangaRequest, NotificationSettings};
+
+#[post("/notifications/settings")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn settings(
+    user: ReqData<Claim>,
+    notification_s: Data<NotificationDBService>,
+) -> ApiResult<Json<NotificationSettings>> {
+    Ok(Json(notification_s.get_settings(&user.id).await?))
+}
+
+#[post("/notifications/settings/edit")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn edit_settings(
+    Json(data): Json<NotificationSettings>,
+    user: ReqData<Claim>,
+    notification_s: Data<NotificationDBService>,
+) -> ApiResult<Json<()>> {
+    notification_s.set_settings(&user.id, data).await?;
+    Ok(Json(()))
+}
+
+#[post("/notifications/mute")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn mute(
+    Json(data): Json<MuteMangaRequest>,
+    user: ReqData<Claim>,
+    notification_s: Data<NotificationDBService>,
+) -> ApiResult<Json<()>> {
+    notification_s
+        .mute(&user.id, &data.manga_id, data.muted)
+        .await?;
+    Ok(Json(()))
+}
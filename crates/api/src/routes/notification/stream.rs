@@ -0,0 +1,73 @@
+use crate::errors::ApiResult;
+use crate::services::db::notification::NotificationDBService;
+use crate::services::notification_service::NotificationHub;
+use actix_web::get;
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::web::{Bytes, Data, ReqData};
+use actix_web::HttpResponse;
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::notification::NotificationEvent;
+use futures_util::{stream, StreamExt};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+/// comment which is sent when nothing happened. keeps proxies from closing the connection
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+fn event(data: &NotificationEvent) -> ApiResult<Bytes> {
+    Ok(Bytes::from(format!(
+        "data: {}\n\n",
+        serde_json::to_string(data)?
+    )))
+}
+
+/// server sent events. starts with the unread count
+#[get("/notifications/stream")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn stream(
+    user: ReqData<Claim>,
+    hub: Data<NotificationHub>,
+    notification_s: Data<NotificationDBService>,
+) -> ApiResult<HttpResponse> {
+    let receiver = hub.subscribe();
+    let first = event(&NotificationEvent {
+        unread: notification_s.unread(&user.id).await?,
+        notification: None,
+    })?;
+    let user_id = user.id.clone();
+    let events = stream::unfold(receiver, move |mut receiver| {
+        let user_id = user_id.clone();
+        async move {
+            loop {
+                match tokio::time::timeout(KEEP_ALIVE, receiver.recv()).await {
+                    Err(_) => return Some((Bytes::from_static(b": keep-alive\n\n"), receiver)),
+                    Ok(Ok((user, data))) if user == user_id => {
+                        if let Ok(v) = event(&data) {
+                            return Some((v, receiver));
+                        }
+                    }
+                    Ok(Ok(_)) | Ok(Err(RecvError::Lagged(_))) => {}
+                    Ok(Err(RecvError::Closed)) => return None,
+                }
+            }
+        }
+    });
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .streaming(
+            stream::once(async move { first })
+                .chain(events)
+                .map(Ok::<_, actix_web::Error>),
+        ))
+}
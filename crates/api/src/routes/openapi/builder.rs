@@ -0,0 +1,209 @@
+use api_structure::auth::role::Role;
+use api_structure::error::ApiErr;
+use api_structure::RequestImpl;
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
+
+/// openapi 3 document. paths are relative to `/api`
+pub struct Spec {
+    gen: SchemaGenerator,
+    paths: Map<String, Value>,
+}
+
+impl Default for Spec {
+    fn default() -> Self {
+        Self {
+            gen: SchemaSettings::openapi3().into_generator(),
+            paths: Map::new(),
+        }
+    }
+}
+
+impl Spec {
+    fn schema<T: JsonSchema>(&mut self) -> Value {
+        serde_json::to_value(self.gen.subschema_for::<T>()).unwrap_or_default()
+    }
+
+    /// route, method and auth of `R`. `role` is the minimum role if `R::AUTH`
+    pub fn op<R: RequestImpl>(&mut self, role: Role) -> Operation<'_> {
+        let role = R::AUTH.then_some(role);
+        self.raw(R::METHOD, R::ROUTE, role)
+    }
+
+    /// route without [RequestImpl]. public if `role` is none
+    pub fn raw(&mut self, method: &str, path: &str, role: Option<Role>) -> Operation<'_> {
+        let path = format!("/{}", path.trim_start_matches('/'));
+        let mut op = Map::new();
+        op.insert(
+            "operationId".to_string(),
+            json!(format!("{} {}", method.to_lowercase(), path)),
+        );
+        op.insert(
+            "tags".to_string(),
+            json!([path.split('/').nth(1).unwrap_or_default()]),
+        );
+        if let Some(role) = role {
+            op.insert("security".to_string(), json!([{ "bearer": [] }]));
+            let description = match role {
+                Role::NotVerified => "any session".to_string(),
+                role => format!("min {}", role),
+            };
+            op.insert("description".to_string(), json!(description));
+        }
+        Operation {
+            spec: self,
+            method: method.to_lowercase(),
+            path,
+            op,
+            params: vec![],
+        }
+    }
+
+    /// adds a schema which isn't part of a route, e.g. messages of a websocket
+    pub fn define<T: JsonSchema>(&mut self) {
+        self.gen.subschema_for::<T>();
+    }
+
+    pub fn build(mut self) -> Value {
+        let error = self.schema::<ApiErr>();
+        for op in self.paths.values_mut().filter_map(|v| v.as_object_mut()) {
+            for op in op.values_mut() {
+                op["responses"]["default"] = json!({
+                    "description": "error",
+                    "content": { "application/json": { "schema": error } }
+                });
+            }
+        }
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "ManRead",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "servers": [{ "url": "/api" }],
+            "paths": self.paths,
+            "components": {
+                "schemas": self.gen.take_definitions(),
+                "securitySchemes": {
+                    "bearer": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" }
+                }
+            }
+        })
+    }
+}
+
+/// added to the [Spec] by its response
+pub struct Operation<'a> {
+    spec: &'a mut Spec,
+    method: String,
+    path: String,
+    op: Map<String, Value>,
+    params: Vec<Value>,
+}
+
+impl Operation<'_> {
+    pub fn summary(mut self, summary: &str) -> Self {
+        self.op.insert("summary".to_string(), json!(summary));
+        self
+    }
+
+    /// json request body
+    pub fn body<T: JsonSchema>(mut self) -> Self {
+        let schema = self.spec.schema::<T>();
+        self.op.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": true,
+                "content": { "application/json": { "schema": schema } }
+            }),
+        );
+        self
+    }
+
+    /// multipart/form-data request body with any number of files
+    pub fn files(mut self) -> Self {
+        self.op.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": true,
+                "content": { "multipart/form-data": { "schema": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string", "format": "binary" }
+                }}}
+            }),
+        );
+        self
+    }
+
+    /// every field of `T` is a parameter `location`, e.g. `path` or `query`
+    pub fn params<T: JsonSchema>(mut self, location: &str) -> Self {
+        let root = serde_json::to_value(self.spec.gen.root_schema_for::<T>()).unwrap_or_default();
+        let required = root["required"].as_array().cloned().unwrap_or_default();
+        if let Some(properties) = root["properties"].as_object() {
+            for (name, schema) in properties {
+                self.params.push(json!({
+                    "name": name,
+                    "in": location,
+                    "required": location == "path" || required.contains(&json!(name)),
+                    "schema": schema,
+                }));
+            }
+        }
+        self
+    }
+
+    pub fn param(mut self, name: &str, location: &str, description: &str) -> Self {
+        self.params.push(json!({
+            "name": name,
+            "in": location,
+            "required": location == "path",
+            "description": description,
+            "schema": { "type": "string" },
+        }));
+        self
+    }
+
+    /// the route accepts urls of `sign_image_urls` instead of the bearer token
+    pub fn signed(self) -> Self {
+        let mut op = self
+            .param("uid", "query", "user of a signed url")
+            .param("exp", "query", "expiration of a signed url")
+            .param("sig", "query", "signature of a signed url");
+        op.op
+            .insert("security".to_string(), json!([{ "bearer": [] }, {}]));
+        op
+    }
+
+    /// json response
+    pub fn json<T: JsonSchema>(self) {
+        let schema = self.spec.schema::<T>();
+        self.content(json!({ "application/json": { "schema": schema } }))
+    }
+
+    /// response with a file or a stream
+    pub fn file(self, mime: &str) {
+        self.content(json!({ mime: { "schema": { "type": "string", "format": "binary" } } }))
+    }
+
+    fn content(self, content: Value) {
+        self.response(json!({ "200": { "description": "success", "content": content } }))
+    }
+
+    pub fn websocket(self) {
+        self.response(json!({ "101": { "description": "websocket" } }))
+    }
+
+    fn response(mut self, responses: Value) {
+        if !self.params.is_empty() {
+            self.op.insert("parameters".to_string(), json!(self.params));
+        }
+        self.op.insert("responses".to_string(), responses);
+        let path = self
+            .spec
+            .paths
+            .entry(self.path)
+            .or_insert_with(|| json!({}));
+        path[self.method.as_str()] = Value::Object(self.op);
+    }
+}
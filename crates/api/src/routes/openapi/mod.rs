@@ -0,0 +1,488 @@
+mod builder;
+
+use actix_web::web::{Data, ServiceConfig};
+use actix_web::{get, HttpResponse};
+use api_structure::audit::{AuditEntry, AuditLogRequest, SetRoleRequest, SetVisibilityRequest};
+use api_structure::auth::activate::ActivateRequest;
+use api_structure::auth::jwt::JWTs;
+use api_structure::auth::login::LoginRequest;
+use api_structure::auth::register::NewUserRequest;
+use api_structure::auth::reset_password::{RequestResetPasswordRequest, ResetPasswordRequest};
+use api_structure::auth::role::Role;
+use api_structure::comment::{
+    AddCommentRequest, Comment, CommentsRequest, DeleteCommentRequest, EditCommentRequest,
+    ModerateCommentRequest,
+};
+use api_structure::fonts::FontRequest;
+use api_structure::history::{
+    ClearHistoryRequest, DeleteHistoryEntryRequest, HistorySession, ReadingHistoryRequest,
+};
+use api_structure::home::HomeResponse;
+use api_structure::image::{
+    MangaCoverRequest, MangaReaderImageRequest, SignImageUrlsRequest, SignImageUrlsResponse,
+};
+use api_structure::info::{
+    AddRelationRequest, DeleteRelationRequest, EditMangaRequest, MangaHistoryRequest,
+    MangaInfoRequest, MangaInfoResponse, MangaRevision, RollbackMangaRequest,
+};
+use api_structure::library::{ExportLibraryRequest, ImportLibraryResponse};
+use api_structure::notification::{
+    MarkNotificationsReadRequest, MuteMangaRequest, Notification, NotificationEvent,
+    NotificationSettings, NotificationSettingsRequest, NotificationStreamRequest,
+    NotificationsRequest,
+};
+use api_structure::rating::{DeleteRatingRequest, RateMangaRequest, Rating, RatingsRequest};
+use api_structure::reader::{
+    MangaReaderRequest, MangaReaderResponse, PageTranslationRequest, ProgressSyncRequest,
+    ReaderPageRequest, ReaderPageResponse, SetProgressRequest, SyncMessage, SyncTicketRequest,
+    TranslationArea,
+};
+use api_structure::scraper::{ExternalSearchRequest, ScrapeSearchResult, ValidSearches};
+use api_structure::search::{SearchRequest, SearchResponse};
+use api_structure::stats::{ReadingStats, ReadingStatsRequest};
+use api_structure::translation::{
+    AddTranslationAreaRequest, DeleteTranslationAreaRequest, EditTranslationAreaRequest,
+    EditorTranslationArea, TranslateChapterRequest, TranslationAreasRequest,
+    TranslationHistoryRequest, TranslationJob, TranslationJobsRequest, TranslationRevision,
+};
+use api_structure::user::{
+    EditProfileRequest, SetUserImageRequest, UserImageRequest, UserProfile, UserProfileRequest,
+};
+use api_structure::SearchUris;
+use builder::Spec;
+use std::collections::HashMap;
+
+/// json of the openapi document. built once at startup
+pub struct OpenApi(String);
+
+/// every route registered in main.rs. keep both in sync
+pub fn document() -> OpenApi {
+    use Role::{Admin, Moderator, NotVerified, User};
+    let mut spec = Spec::default();
+
+    spec.raw("POST", "fonts", None)
+        .summary("names of the fonts for translations")
+        .json::<Vec<String>>();
+    spec.raw("POST", "font", None)
+        .body::<FontRequest>()
+        .file("font/ttf");
+    spec.raw("POST", "upload_images", None)
+        .summary("uploads temporary images. returns (original name, temp name)")
+        .files()
+        .json::<Vec<(String, String)>>();
+    spec.raw("POST", "spinner", None)
+        .summary("loading animation")
+        .file("image/gif");
+    spec.op::<NewUserRequest>(NotVerified)
+        .body::<NewUserRequest>()
+        .json::<JWTs>();
+    spec.op::<LoginRequest>(NotVerified)
+        .body::<LoginRequest>()
+        .json::<JWTs>();
+    spec.op::<ResetPasswordRequest>(NotVerified)
+        .body::<ResetPasswordRequest>()
+        .json::<JWTs>();
+    spec.op::<RequestResetPasswordRequest>(NotVerified)
+        .body::<RequestResetPasswordRequest>()
+        .json::<()>();
+    spec.op::<ProgressSyncRequest>(NotVerified)
+        .summary("websocket which pushes progress of other sessions")
+        .param("ticket", "query", "single use ticket of sync/ticket")
+        .websocket();
+    spec.define::<SyncMessage>();
+    spec.raw("GET", "cover/{manga_id}.{file_ext}", Some(User))
+        .summary("cacheable cover")
+        .params::<MangaCoverRequest>("path")
+        .signed()
+        .file("image/*");
+    spec.raw(
+        "GET",
+        "chapter_page/{manga_id}/{chapter_id}/{version_id}/{page}.{file_ext}",
+        Some(User),
+    )
+    .summary("cacheable page. version_id is without table prefix")
+    .params::<MangaReaderImageRequest>("path")
+    .signed()
+    .file("image/*");
+
+    spec.op::<JWTs>(NotVerified)
+        .summary("new tokens. needs the refresh token")
+        .json::<JWTs>();
+    spec.op::<ActivateRequest>(NotVerified)
+        .body::<ActivateRequest>()
+        .json::<JWTs>();
+    spec.op::<SetRoleRequest>(Admin)
+        .body::<SetRoleRequest>()
+        .json::<()>();
+    spec.op::<UserProfileRequest>(User)
+        .body::<UserProfileRequest>()
+        .json::<UserProfile>();
+    spec.op::<EditProfileRequest>(User)
+        .body::<EditProfileRequest>()
+        .json::<()>();
+    spec.op::<SetUserImageRequest>(User)
+        .body::<SetUserImageRequest>()
+        .json::<()>();
+    spec.op::<UserImageRequest>(User)
+        .body::<UserImageRequest>()
+        .file("image/*");
+    spec.op::<SignImageUrlsRequest>(User)
+        .body::<SignImageUrlsRequest>()
+        .json::<SignImageUrlsResponse>();
+    spec.op::<ReadingStatsRequest>(User)
+        .body::<ReadingStatsRequest>()
+        .json::<ReadingStats>();
+    spec.op::<ReadingHistoryRequest>(User)
+        .body::<ReadingHistoryRequest>()
+        .json::<Vec<HistorySession>>();
+    spec.op::<DeleteHistoryEntryRequest>(User)
+        .body::<DeleteHistoryEntryRequest>()
+        .json::<()>();
+    spec.op::<ClearHistoryRequest>(User).json::<()>();
+    spec.op::<AuditLogRequest>(Admin)
+        .body::<AuditLogRequest>()
+        .json::<Vec<AuditEntry>>();
+    spec.raw("POST", "library/import", Some(User))
+        .summary("imports a MyAnimeList or AniList export")
+        .files()
+        .json::<ImportLibraryResponse>();
+    spec.op::<ExportLibraryRequest>(User)
+        .summary("xml for MyAnimeList, json for AniList")
+        .body::<ExportLibraryRequest>()
+        .file("application/octet-stream");
+    spec.op::<CommentsRequest>(User)
+        .body::<CommentsRequest>()
+        .json::<Vec<Comment>>();
+    spec.op::<AddCommentRequest>(User)
+        .summary("returns the id of the comment")
+        .body::<AddCommentRequest>()
+        .json::<String>();
+    spec.op::<EditCommentRequest>(User)
+        .body::<EditCommentRequest>()
+        .json::<()>();
+    spec.op::<DeleteCommentRequest>(User)
+        .body::<DeleteCommentRequest>()
+        .json::<()>();
+    spec.op::<ModerateCommentRequest>(Moderator)
+        .body::<ModerateCommentRequest>()
+        .json::<()>();
+    spec.op::<NotificationsRequest>(User)
+        .body::<NotificationsRequest>()
+        .json::<Vec<Notification>>();
+    spec.op::<MarkNotificationsReadRequest>(User)
+        .summary("returns the number of changed notifications")
+        .body::<MarkNotificationsReadRequest>()
+        .json::<u32>();
+    spec.op::<NotificationSettingsRequest>(User)
+        .json::<NotificationSettings>();
+    spec.op::<NotificationSettings>(User)
+        .body::<NotificationSettings>()
+        .json::<()>();
+    spec.op::<MuteMangaRequest>(User)
+        .body::<MuteMangaRequest>()
+        .json::<()>();
+    spec.op::<NotificationStreamRequest>(User)
+        .summary("server sent events. every event is a json encoded NotificationEvent")
+        .file("text/event-stream");
+    spec.define::<NotificationEvent>();
+    spec.op::<HomeResponse>(User).json::<HomeResponse>();
+    spec.op::<SearchRequest>(User)
+        .body::<SearchRequest>()
+        .json::<Vec<SearchResponse>>();
+    spec.op::<MangaCoverRequest>(User)
+        .body::<MangaCoverRequest>()
+        .file("image/*");
+    spec.op::<MangaInfoRequest>(User)
+        .body::<MangaInfoRequest>()
+        .json::<MangaInfoResponse>();
+    spec.op::<RatingsRequest>(User)
+        .body::<RatingsRequest>()
+        .json::<Vec<Rating>>();
+    spec.op::<RateMangaRequest>(User)
+        .body::<RateMangaRequest>()
+        .json::<()>();
+    spec.op::<DeleteRatingRequest>(User)
+        .body::<DeleteRatingRequest>()
+        .json::<()>();
+    spec.op::<AddRelationRequest>(Moderator)
+        .body::<AddRelationRequest>()
+        .json::<()>();
+    spec.op::<DeleteRelationRequest>(Moderator)
+        .body::<DeleteRelationRequest>()
+        .json::<()>();
+    spec.op::<EditMangaRequest>(User)
+        .summary("moderators edit directly. other users create a revision")
+        .body::<EditMangaRequest>()
+        .json::<()>();
+    spec.op::<MangaHistoryRequest>(Moderator)
+        .body::<MangaHistoryRequest>()
+        .json::<Vec<MangaRevision>>();
+    spec.op::<RollbackMangaRequest>(Moderator)
+        .body::<RollbackMangaRequest>()
+        .json::<()>();
+    spec.op::<SetVisibilityRequest>(Moderator)
+        .body::<SetVisibilityRequest>()
+        .json::<()>();
+    spec.op::<MangaReaderRequest>(User)
+        .body::<MangaReaderRequest>()
+        .json::<MangaReaderResponse>();
+    spec.op::<ReaderPageRequest>(User)
+        .body::<ReaderPageRequest>()
+        .json::<ReaderPageResponse>();
+    spec.op::<SetProgressRequest>(User)
+        .body::<SetProgressRequest>()
+        .json::<()>();
+    spec.op::<SyncTicketRequest>(User)
+        .summary("ticket for the progress websocket")
+        .json::<String>();
+    spec.op::<MangaReaderImageRequest>(User)
+        .body::<MangaReaderImageRequest>()
+        .file("image/*");
+    spec.op::<PageTranslationRequest>(User)
+        .body::<PageTranslationRequest>()
+        .json::<Vec<TranslationArea>>();
+    spec.op::<TranslationAreasRequest>(Moderator)
+        .body::<TranslationAreasRequest>()
+        .json::<Vec<EditorTranslationArea>>();
+    spec.op::<AddTranslationAreaRequest>(Moderator)
+        .summary("returns the id of the area")
+        .body::<AddTranslationAreaRequest>()
+        .json::<String>();
+    spec.op::<EditTranslationAreaRequest>(Moderator)
+        .body::<EditTranslationAreaRequest>()
+        .json::<()>();
+    spec.op::<DeleteTranslationAreaRequest>(Moderator)
+        .body::<DeleteTranslationAreaRequest>()
+        .json::<()>();
+    spec.op::<TranslationHistoryRequest>(Moderator)
+        .body::<TranslationHistoryRequest>()
+        .json::<Vec<TranslationRevision>>();
+    spec.op::<TranslateChapterRequest>(Moderator)
+        .summary("returns the id of the job")
+        .body::<TranslateChapterRequest>()
+        .json::<String>();
+    spec.op::<TranslationJobsRequest>(Moderator)
+        .body::<TranslationJobsRequest>()
+        .json::<Vec<TranslationJob>>();
+    spec.op::<ExternalSearchRequest>(User)
+        .body::<ExternalSearchRequest>()
+        .json::<Vec<ScrapeSearchResult>>();
+    spec.op::<SearchUris>(User)
+        .json::<HashMap<String, ValidSearches>>();
+
+    OpenApi(spec.build().to_string())
+}
+
+#[get("/openapi.json")]
+pub async fn openapi_route(doc: Data<OpenApi>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(doc.0.clone())
+}
+
+/// where [docs_route] loads redoc from
+#[cfg(feature = "bundled-redoc")]
+const REDOC_SCRIPT: &str = "redoc.standalone.js";
+#[cfg(not(feature = "bundled-redoc"))]
+const REDOC_SCRIPT: &str = "https://cdn.redoc.ly/redoc/v2.1.5/bundles/redoc.standalone.js";
+
+/// redoc page of [openapi_route]
+#[get("/docs")]
+pub async fn docs_route() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/html")
+        .body(include_str!("redoc.html").replace("{script}", REDOC_SCRIPT))
+}
+
+/// redoc bundle used by [docs_route]. see `tools/fetch_redoc.sh`
+#[cfg(feature = "bundled-redoc")]
+#[get("/redoc.standalone.js")]
+pub async fn redoc_route() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/javascript")
+        .insert_header(("Cache-Control", "public, max-age=604800"))
+        .body(&include_bytes!("redoc.standalone.js")[..])
+}
+
+/// the routes of the documentation. they aren't part of it
+pub fn config(cfg: &mut ServiceConfig) {
+    cfg.service(openapi_route).service(docs_route);
+    #[cfg(feature = "bundled-redoc")]
+    cfg.service(redoc_route);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::document;
+    use api_structure::auth::role::Role;
+    use serde_json::{json, Value};
+    use std::collections::{HashMap, HashSet};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// roles from the least to the most privileged
+    const ROLES: [(&str, Role); 6] = [
+        ("NotVerified", Role::NotVerified),
+        ("User", Role::User),
+        ("Author", Role::Author),
+        ("Moderator", Role::Moderator),
+        ("CoAdmin", Role::CoAdmin),
+        ("Admin", Role::Admin),
+    ];
+
+    /// an actix route. `roles` are the ones of its `#[protect]`
+    struct Handler {
+        method: &'static str,
+        path: String,
+        roles: Vec<Role>,
+    }
+
+    fn src() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src")
+    }
+
+    /// values of every `RequestImpl::ROUTE` below `dir`
+    fn routes(dir: &Path, found: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                routes(&path, found);
+                continue;
+            }
+            for line in fs::read_to_string(&path).unwrap().lines() {
+                if let Some(route) = line.trim().strip_prefix("const ROUTE: &'static str = \"") {
+                    found.push(route.trim_end_matches("\";").to_string());
+                }
+            }
+        }
+    }
+
+    /// routes of a file by the name of their function
+    fn handlers(file: &Path) -> HashMap<String, Handler> {
+        let mut found = HashMap::new();
+        let mut current: Option<Handler> = None;
+        for line in fs::read_to_string(file).unwrap().lines().map(str::trim) {
+            for method in ["get", "post"] {
+                if let Some(path) = line.strip_prefix(&format!("#[{}(\"", method)) {
+                    current = Some(Handler {
+                        method,
+                        path: path
+                            .split('"')
+                            .next()
+                            .unwrap()
+                            .trim_start_matches('/')
+                            .to_string(),
+                        roles: vec![],
+                    });
+                }
+            }
+            let Some(handler) = current.as_mut() else {
+                continue;
+            };
+            for role in line.split("Role::").skip(1) {
+                let name: String = role.chars().take_while(|c| c.is_alphanumeric()).collect();
+                let (_, role) = ROLES.iter().find(|(v, _)| *v == name).unwrap();
+                handler.roles.push(*role);
+            }
+            if let Some(name) = line.split("async fn ").nth(1) {
+                let name = name.split('(').next().unwrap().to_string();
+                found.insert(name, current.take().unwrap());
+            }
+        }
+        found
+    }
+
+    /// route which main.rs registers as `name`. follows the `pub use` of the route modules
+    fn resolve(name: &str) -> Handler {
+        let (file, function) = match name.strip_prefix("routes::") {
+            None => (src().join("main.rs"), name.to_string()),
+            Some(name) => {
+                let (module, name) = name.split_once("::").unwrap();
+                let dir = src().join("routes").join(module);
+                let reexport = fs::read_to_string(dir.join("mod.rs"))
+                    .unwrap()
+                    .lines()
+                    .filter_map(|v| v.strip_prefix("pub use ")?.strip_suffix(';'))
+                    .find_map(|v| {
+                        let (path, alias) = v.split_once(" as ").unwrap_or((v, v));
+                        let (file, function) = path.split_once("::")?;
+                        let alias = alias.rsplit("::").next()?;
+                        (alias == name).then(|| (file.to_string(), function.to_string()))
+                    });
+                match reexport {
+                    Some((file, function)) => (dir.join(format!("{}.rs", file)), function),
+                    None => (dir.join("mod.rs"), name.to_string()),
+                }
+            }
+        };
+        handlers(&file)
+            .remove(&function)
+            .unwrap_or_else(|| panic!("{} is not a route", name))
+    }
+
+    /// (route, needs a session) for every `.service` of the `/api` scope in main.rs
+    fn registered() -> Vec<(Handler, bool)> {
+        let main = fs::read_to_string(src().join("main.rs")).unwrap();
+        let api = main.split("web::scope(\"/api\")").nth(1).unwrap();
+        let api = api.split(".bind(").next().unwrap();
+        let (public, session) = api.split_once("HttpAuthentication::bearer").unwrap();
+        let services = |part: &str| {
+            part.split(".service(")
+                .skip(1)
+                .map(|v| v.split(')').next().unwrap().trim().to_string())
+                .filter(|v| !v.contains('('))
+                .collect::<Vec<_>>()
+        };
+        let public = services(public).into_iter().map(|v| (resolve(&v), false));
+        let session = services(session).into_iter().map(|v| (resolve(&v), true));
+        public.chain(session).collect()
+    }
+
+    #[test]
+    fn every_route_is_documented() {
+        let doc: Value = serde_json::from_str(&document().0).unwrap();
+        let mut found = vec![];
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../api_structure/src");
+        routes(&dir, &mut found);
+        assert!(!found.is_empty());
+        let missing: Vec<_> = found
+            .iter()
+            .filter(|route| doc["paths"].get(format!("/{}", route)).is_none())
+            .collect();
+        assert!(missing.is_empty(), "undocumented routes: {:?}", missing);
+    }
+
+    #[test]
+    fn documentation_matches_registered_routes() {
+        let doc: Value = serde_json::from_str(&document().0).unwrap();
+        let registered = registered();
+        assert!(!registered.is_empty());
+        let mut routes = HashSet::new();
+        for (handler, session) in registered {
+            let route = format!("{} /{}", handler.method, handler.path);
+            let op = &doc["paths"][format!("/{}", handler.path)][handler.method];
+            assert!(op.is_object(), "{} isn't documented", route);
+            let security = op["security"].as_array();
+            if session {
+                let role = handler.roles.iter().min_by_key(|v| **v as u32);
+                let description = match role {
+                    None | Some(Role::NotVerified) => "any session".to_string(),
+                    Some(role) => format!("min {}", role),
+                };
+                assert!(security.is_some(), "{} needs a session", route);
+                assert_eq!(op["description"], json!(description), "role of {}", route);
+            } else {
+                // signed urls are the only way to use a public route with a session
+                let optional = security.map_or(true, |v| v.contains(&json!({})));
+                assert!(optional, "{} is public", route);
+            }
+            routes.insert(route);
+        }
+        for (path, ops) in doc["paths"].as_object().unwrap() {
+            for method in ops.as_object().unwrap().keys() {
+                let route = format!("{} {}", method, path);
+                assert!(routes.contains(&route), "{} isn't registered", route);
+            }
+        }
+    }
+}
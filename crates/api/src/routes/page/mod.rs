@@ -1 +1,9 @@
+mod translation;
 
+pub use translation::add as add_translation_area_route;
+pub use translation::areas as translation_areas_route;
+pub use translation::delete as delete_translation_area_route;
+pub use translation::edit as edit_translation_area_route;
+pub use translation::history as translation_history_route;
+pub use translation::jobs as translation_jobs_route;
+pub use translation::queue as queue_translation_route;
@@ -0,0 +1,321 @@
+use crate::env::config::Config;
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::audit_log::{to_json, AuditLogDBService};
+use crate::services::db::chapter::{Chapter, ChapterDBService};
+use crate::services::db::page::PageDBService;
+use crate::services::db::translation::{PageTranslation, TranslationDBService};
+use crate::services::db::translation_job::TranslationJobDBService;
+use crate::services::db::user::UserDBService;
+use crate::services::translation_service::TranslationQueue;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpRequest};
+use actix_web_grants::protect;
+use api_structure::audit::AuditAction;
+use api_structure::auth::jwt::Claim;
+use api_structure::translation::{
+    AddTranslationAreaRequest, DeleteTranslationAreaRequest, EditTranslationAreaRequest,
+    EditorTranslationArea, TranslateChapterRequest, TranslationAction, TranslationAreaData,
+    TranslationAreasRequest, TranslationHistoryRequest, TranslationJob, TranslationJobsRequest,
+    TranslationRevision,
+};
+use surrealdb::sql::Thing;
+use surrealdb_extras::SurrealTableInfo;
+
+/// source of areas which were created in the editor
+const EDITOR_SOURCE: &str = "manual";
+
+fn validate(area: &TranslationAreaData) -> ApiResult<()> {
+    if area.min_x >= area.max_x || area.min_y >= area.max_y {
+        return Err(ApiError::invalid_input("Invalid bounding box"));
+    }
+    if area.translated_text.is_empty() {
+        return Err(ApiError::invalid_input("Area needs at least one text"));
+    }
+    Ok(())
+}
+
+#[post("/translation/areas")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn areas(
+    Json(data): Json<TranslationAreasRequest>,
+    translation_s: Data<TranslationDBService>,
+) -> ApiResult<Json<Vec<EditorTranslationArea>>> {
+    Ok(Json(
+        translation_s
+            .get(&data.page_id, None)
+            .await?
+            .into_iter()
+            .map(|v| EditorTranslationArea {
+                area_id: v.id.id().to_string(),
+                area: v.data.to_editor(),
+                source: v.data.source,
+            })
+            .collect(),
+    ))
+}
+
+#[post("/translation/area/add")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn add(
+    Json(data): Json<AddTranslationAreaRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    page_s: Data<PageDBService>,
+    translation_s: Data<TranslationDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<String>> {
+    validate(&data.area)?;
+    if !page_s.exists(&data.page_id).await? {
+        return Err(ApiError::invalid_input("Page does not exist"));
+    }
+    let first = translation_s.get(&data.page_id, None).await?.is_empty();
+    let area = PageTranslation::from_public(&data.page_id, EDITOR_SOURCE.to_string(), data.area);
+    let after = area.to_editor();
+    let id = translation_s.add(area.clone()).await?.id.id().to_string();
+    translation_s
+        .add_revision(
+            &area,
+            &id,
+            &user.id,
+            TranslationAction::Add,
+            None,
+            Some(after.clone()),
+        )
+        .await?;
+    audit_s
+        .log(
+            &req,
+            &user.id,
+            AuditAction::TranslationAreaAdd,
+            Thing::from((PageTranslation::name(), id.as_str())),
+            None,
+            to_json(&after)?,
+        )
+        .await?;
+    if first {
+        page_s.set_translation(&data.page_id, true).await?;
+    }
+    Ok(Json(id))
+}
+
+#[post("/translation/area/edit")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn edit(
+    Json(data): Json<EditTranslationAreaRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    translation_s: Data<TranslationDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<()>> {
+    validate(&data.area)?;
+    let area = translation_s.get_area(&data.area_id).await?;
+    let before = area.data.to_editor();
+    let after = data.area.clone();
+    translation_s
+        .update(&data.area_id, data.area.into())
+        .await?;
+    translation_s
+        .add_revision(
+            &area.data,
+            &data.area_id,
+            &user.id,
+            TranslationAction::Edit,
+            Some(before.clone()),
+            Some(after.clone()),
+        )
+        .await?;
+    audit_s
+        .log(
+            &req,
+            &user.id,
+            AuditAction::TranslationAreaEdit,
+            Thing::from((PageTranslation::name(), data.area_id.as_str())),
+            to_json(&before)?,
+            to_json(&after)?,
+        )
+        .await?;
+    Ok(Json(()))
+}
+
+#[post("/translation/area/delete")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn delete(
+    Json(data): Json<DeleteTranslationAreaRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    page_s: Data<PageDBService>,
+    translation_s: Data<TranslationDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<()>> {
+    let area = translation_s.get_area(&data.area_id).await?;
+    let page_id = area.data.page.thing.id().to_string();
+    let before = area.data.to_editor();
+    translation_s
+        .add_revision(
+            &area.data,
+            &data.area_id,
+            &user.id,
+            TranslationAction::Delete,
+            Some(before.clone()),
+            None,
+        )
+        .await?;
+    audit_s
+        .log(
+            &req,
+            &user.id,
+            AuditAction::TranslationAreaDelete,
+            Thing::from((PageTranslation::name(), data.area_id.as_str())),
+            to_json(&before)?,
+            None,
+        )
+        .await?;
+    translation_s.delete(area).await?;
+    if translation_s.get(&page_id, None).await?.is_empty() {
+        page_s.set_translation(&page_id, false).await?;
+    }
+    Ok(Json(()))
+}
+
+#[post("/translation/history")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn history(
+    Json(data): Json<TranslationHistoryRequest>,
+    translation_s: Data<TranslationDBService>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<Json<Vec<TranslationRevision>>> {
+    let mut result = vec![];
+    for revision in translation_s.history(&data.page_id).await? {
+        let parse = |v: &Option<String>| -> ApiResult<Option<TranslationAreaData>> {
+            Ok(match v {
+                Some(v) => Some(serde_json::from_str(v)?),
+                None => None,
+            })
+        };
+        result.push(TranslationRevision {
+            area_id: revision.data.area.thing.id().to_string(),
+            action: revision.data.action(),
+            editor: user_s
+                .get_username(&revision.data.editor.thing.id().to_string())
+                .await
+                .ok_or(ApiError::db_error())?,
+            before: parse(&revision.data.before)?,
+            after: parse(&revision.data.after)?,
+            created: revision.data.created.to_string(),
+        });
+    }
+    Ok(Json(result))
+}
+
+#[post("/translation/queue")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn queue(
+    Json(data): Json<TranslateChapterRequest>,
+    req: HttpRequest,
+    user: ReqData<Claim>,
+    config: Data<Config>,
+    chapter_s: Data<ChapterDBService>,
+    job_s: Data<TranslationJobDBService>,
+    queue: Data<TranslationQueue>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<String>> {
+    if config.translation_backend.is_none() {
+        return Err(ApiError::invalid_input("No translation backend configured"));
+    }
+    if data.language.is_empty() {
+        return Err(ApiError::invalid_input("Language is missing"));
+    }
+    let connection = chapter_s
+        .get_version(&data.chapter_id, &data.version_id)
+        .await?;
+    let id = job_s
+        .add(
+            &data.manga_id,
+            &data.chapter_id,
+            &data.version_id,
+            &connection,
+            &user.id,
+            data.language.clone(),
+        )
+        .await?
+        .ok_or(ApiError::invalid_input(
+            "Version is already queued for this language",
+        ))?;
+    queue.wake();
+    audit_s
+        .log(
+            &req,
+            &user.id,
+            AuditAction::TranslationQueue,
+            Thing::from((Chapter::name(), data.chapter_id.as_str())),
+            None,
+            to_json(&data)?,
+        )
+        .await?;
+    Ok(Json(id))
+}
+
+#[post("/translation/jobs")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn jobs(
+    Json(data): Json<TranslationJobsRequest>,
+    job_s: Data<TranslationJobDBService>,
+) -> ApiResult<Json<Vec<TranslationJob>>> {
+    Ok(Json(
+        job_s
+            .for_chapter(&data.chapter_id)
+            .await?
+            .into_iter()
+            .map(|v| v.data.to_public(v.id.id().to_string()))
+            .collect(),
+    ))
+}
@@ -1,14 +1,18 @@
 use crate::errors::ApiResult;
 use crate::services::crypto_service::CryptoService;
+use crate::services::db::audit_log::{to_json, AuditLogDBService};
 use crate::services::db::auth_tokens::AuthTokenDBService;
-use crate::services::db::user::UserDBService;
-use actix_web::post;
+use crate::services::db::user::{User, UserDBService};
 use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpRequest};
 use actix_web_grants::protect;
+use api_structure::audit::AuditAction;
 use api_structure::auth::activate::ActivateRequest;
 use api_structure::auth::jwt::{Claim, JWTs};
 use api_structure::auth::role::Role;
 use api_structure::error::{ApiErr, ApiErrorType};
+use surrealdb::sql::Thing;
+use surrealdb_extras::SurrealTableInfo;
 
 #[post("/auth/activate")]
 #[protect(
@@ -18,7 +22,9 @@ use api_structure::error::{ApiErr, ApiErrorType};
 async fn activate(
     claim: ReqData<Claim>,
     Json(data): Json<ActivateRequest>,
+    req: HttpRequest,
     user: Data<UserDBService>,
+    audit_s: Data<AuditLogDBService>,
     crypto: Data<CryptoService>,
     activation: Data<AuthTokenDBService>,
 ) -> ApiResult<Json<JWTs>> {
@@ -39,6 +45,16 @@ async fn activate(
     }
 
     user.set_role(claim.id.as_str(), kind.kind).await?;
+    audit_s
+        .log(
+            &req,
+            &claim.id,
+            AuditAction::Activate,
+            Thing::from((User::name(), claim.id.as_str())),
+            to_json(&claim.role)?,
+            to_json(&kind.kind)?,
+        )
+        .await?;
 
     Ok(Json(JWTs {
         access_token: crypto.encode_claim(&Claim::new_access(claim.id.clone(), kind.kind)?)?,
@@ -0,0 +1,123 @@
+use crate::errors::ApiResult;
+use crate::services::db::chapter::ChapterDBService;
+use crate::services::db::manga::MangaDBService;
+use crate::services::db::progress::ProgressDBService;
+use crate::services::db::reading_stats::ReadingStatsDBService;
+use actix_web::post;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::history::{
+    DeleteHistoryEntryRequest, HistoryEntry, HistorySession, ReadingHistoryRequest,
+};
+use chrono::Duration;
+use std::collections::HashMap;
+
+/// break after which a new session starts
+const SESSION_GAP: i64 = 30;
+
+#[post("/me/history")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn history(
+    Json(data): Json<ReadingHistoryRequest>,
+    user: ReqData<Claim>,
+    progress_s: Data<ProgressDBService>,
+    manga_s: Data<MangaDBService>,
+    chapter_s: Data<ChapterDBService>,
+) -> ApiResult<Json<Vec<HistorySession>>> {
+    let mut titles = HashMap::new();
+    let mut sessions: Vec<HistorySession> = vec![];
+    let mut last = None;
+    for item in progress_s.history(&user.id, data.limit, data.page).await? {
+        let manga_id = item.data.manga.thing.id().to_string();
+        if !titles.contains_key(&manga_id) {
+            // manga might have been deleted since
+            let t = match manga_s.get(&manga_id).await {
+                Ok(v) => v.data.titles,
+                Err(_) => HashMap::new(),
+            };
+            titles.insert(manga_id.clone(), t);
+        }
+        // chapter might have been deleted since
+        let chapter = match chapter_s.get_reader(item.data.chapter).await {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        let updated = item.data.updated.0;
+        let entry = HistoryEntry {
+            entry_id: item.id.id().to_string(),
+            titles: titles.get(&manga_id).cloned().unwrap_or_default(),
+            manga_id,
+            chapter_id: chapter.chapter_id,
+            chapter: chapter.chapter,
+            progress: item.data.progress,
+            updated: item.data.updated.to_string(),
+        };
+        match (last, sessions.last_mut()) {
+            (Some(last), Some(session)) if last - updated <= Duration::minutes(SESSION_GAP) => {
+                session.start = entry.updated.clone();
+                session.entries.push(entry);
+            }
+            _ => sessions.push(HistorySession {
+                start: entry.updated.clone(),
+                end: entry.updated.clone(),
+                entries: vec![entry],
+            }),
+        }
+        last = Some(updated);
+    }
+    Ok(Json(sessions))
+}
+
+#[post("/me/history/delete")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn delete(
+    Json(data): Json<DeleteHistoryEntryRequest>,
+    user: ReqData<Claim>,
+    progress_s: Data<ProgressDBService>,
+    stats_s: Data<ReadingStatsDBService>,
+) -> ApiResult<Json<()>> {
+    if let Some(manga) = progress_s.delete_entry(&user.id, &data.entry_id).await? {
+        stats_s.forget_manga(&user.id, &manga).await?;
+    }
+    Ok(Json(()))
+}
+
+#[post("/me/history/clear")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn clear(
+    user: ReqData<Claim>,
+    progress_s: Data<ProgressDBService>,
+    stats_s: Data<ReadingStatsDBService>,
+) -> ApiResult<Json<()>> {
+    progress_s.clear(&user.id).await?;
+    stats_s.forget_mangas(&user.id).await?;
+    Ok(Json(()))
+}
@@ -1,12 +1,25 @@
 mod activate;
+mod history;
+mod profile;
 mod refresh;
 mod reset_password;
+mod role;
 mod sign_in;
 mod sign_up;
+mod stats;
 
 pub use activate::activate as activate_route;
+pub use history::clear as clear_history_route;
+pub use history::delete as delete_history_entry_route;
+pub use history::history as history_route;
+pub use profile::edit_profile as edit_profile_route;
+pub use profile::image as user_image_route;
+pub use profile::profile as profile_route;
+pub use profile::set_image as set_user_image_route;
 pub use refresh::refresh_ as refresh_route;
 pub use reset_password::request_reset_password as request_reset_password_route;
 pub use reset_password::reset_password as reset_password_route;
+pub use role::set_role as set_role_route;
 pub use sign_in::login as sign_in_route;
 pub use sign_up::sign_up_route;
+pub use stats::stats as stats_route;
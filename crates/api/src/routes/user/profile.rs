@@ -0,0 +1,178 @@
+use crate::env::config::Config;
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::temp_upload::TempUploadDBService;
+use crate::services::db::user::{UserBio, UserDBService};
+use crate::services::storage::{respond, BlobStore};
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpRequest, HttpResponse};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::auth::role::Role;
+use api_structure::user::{
+    EditProfileRequest, SetUserImageRequest, UserImageKind, UserImageRequest, UserProfile,
+    UserProfileRequest,
+};
+use reqwest::Url;
+
+const MAX_BIO_LEN: usize = 2000;
+const MAX_LINKS: usize = 10;
+
+#[post("/user/profile")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn profile(
+    Json(data): Json<UserProfileRequest>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<Json<UserProfile>> {
+    let user_id = match (data.user_id, data.name) {
+        (Some(id), _) => id,
+        (None, Some(name)) => user_s.get_id(&name, false).await?,
+        (None, None) => return Err(ApiError::invalid_input("Missing user_id or name")),
+    };
+    let user = user_s.get_profile(&user_id).await?;
+    Ok(Json(UserProfile {
+        user_id,
+        names: user.names,
+        role: Role::from(user.role),
+        bio: user.bio,
+        links: user.links,
+        icon_ext: user.icon_ext,
+        banner_ext: user.thumb_ext,
+        created: user.created.to_string(),
+    }))
+}
+
+#[post("/user/profile/edit")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn edit_profile(
+    Json(data): Json<EditProfileRequest>,
+    user: ReqData<Claim>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<Json<()>> {
+    let old = user_s.get_profile(&user.id).await?;
+    let mut new = UserBio {
+        bio: old.bio,
+        links: old.links,
+    };
+    if let Some(bio) = data.bio {
+        if bio.chars().count() > MAX_BIO_LEN {
+            return Err(ApiError::invalid_input("Bio is too long"));
+        }
+        new.bio = Some(bio).filter(|v| !v.trim().is_empty());
+    }
+    if let Some(links) = data.links {
+        if links.len() > MAX_LINKS {
+            return Err(ApiError::invalid_input("Too many links"));
+        }
+        for link in &links {
+            match Url::parse(link) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+                _ => return Err(ApiError::invalid_input(format!("Invalid link: {}", link))),
+            }
+        }
+        new.links = links;
+    }
+    user_s.set_bio(&user.id, new).await?;
+    Ok(Json(()))
+}
+
+#[post("/user/image/set")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn set_image(
+    Json(data): Json<SetUserImageRequest>,
+    user: ReqData<Claim>,
+    config: Data<Config>,
+    store: Data<dyn BlobStore>,
+    user_s: Data<UserDBService>,
+    temp_s: Data<TempUploadDBService>,
+) -> ApiResult<Json<()>> {
+    let ext = match data.temp_name.split_once('.') {
+        Some((name, ext))
+            if !name.is_empty()
+                && !ext.is_empty()
+                && !data.temp_name.contains(['/', '\\'])
+                && !ext.contains('.') =>
+        {
+            ext.to_string()
+        }
+        _ => return Err(ApiError::invalid_input("Invalid file name")),
+    };
+    let temp = config.root_folder.join("temp").join(&data.temp_name);
+    if !temp.is_file() {
+        return Err(ApiError::invalid_input("File does not exist"));
+    }
+    temp_s.claim(&data.temp_name, &user.id).await?;
+    let old = user_s.get_profile(&user.id).await?;
+    let (old_ext, field) = match data.kind {
+        UserImageKind::Icon => (old.icon_ext, "icon_ext"),
+        UserImageKind::Banner => (old.thumb_ext, "thumb_ext"),
+    };
+    let folder = format!("users/{}", data.kind.folder());
+    store
+        .put(
+            &format!("{}/{}.{}", folder, user.id, ext),
+            tokio::fs::read(&temp).await?,
+        )
+        .await?;
+    std::fs::remove_file(temp)?;
+    if let Some(old_ext) = old_ext.filter(|v| v != &ext) {
+        let _ = store
+            .delete(&format!("{}/{}.{}", folder, user.id, old_ext))
+            .await;
+    }
+    user_s.set_image_ext(&user.id, field, ext).await?;
+    Ok(Json(()))
+}
+
+#[post("/user/image")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn image(
+    req: HttpRequest,
+    Json(data): Json<UserImageRequest>,
+    store: Data<dyn BlobStore>,
+    user_s: Data<UserDBService>,
+) -> ApiResult<HttpResponse> {
+    let user = user_s.get_profile(&data.user_id).await?;
+    let ext = match data.kind {
+        UserImageKind::Icon => user.icon_ext,
+        UserImageKind::Banner => user.thumb_ext,
+    }
+    .ok_or(ApiError::invalid_input("User has no image"))?;
+    let key = format!("users/{}/{}.{}", data.kind.folder(), data.user_id, ext);
+    respond(&req, &**store, &key).await
+}
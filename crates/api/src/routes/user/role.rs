@@ -0,0 +1,40 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::audit_log::{to_json, AuditLogDBService};
+use crate::services::db::user::{User, UserDBService};
+use actix_web::web::{Data, Json, ReqData};
+use actix_web::{post, HttpRequest};
+use actix_web_grants::protect;
+use api_structure::audit::{AuditAction, SetRoleRequest};
+use api_structure::auth::jwt::Claim;
+use surrealdb::sql::Thing;
+use surrealdb_extras::SurrealTableInfo;
+
+#[post("/user/role")]
+#[protect(
+    any("api_structure::auth::role::Role::Admin"),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn set_role(
+    Json(data): Json<SetRoleRequest>,
+    req: HttpRequest,
+    claim: ReqData<Claim>,
+    user_s: Data<UserDBService>,
+    audit_s: Data<AuditLogDBService>,
+) -> ApiResult<Json<()>> {
+    if data.user_id == claim.id {
+        return Err(ApiError::invalid_input("Cant change own role"));
+    }
+    let before = user_s.get_role(&data.user_id).await?;
+    user_s.set_role(&data.user_id, data.role).await?;
+    audit_s
+        .log(
+            &req,
+            &claim.id,
+            AuditAction::RoleChange,
+            Thing::from((User::name(), data.user_id.as_str())),
+            to_json(&before)?,
+            to_json(&data.role)?,
+        )
+        .await?;
+    Ok(Json(()))
+}
@@ -1,9 +1,12 @@
 use crate::env::config::Config;
 use crate::errors::ApiResult;
+use crate::routes::image;
 use crate::services::crypto_service::CryptoService;
+use crate::services::db::temp_upload::TempUploadDBService;
 use crate::services::db::user::UserDBService;
-use actix_web::post;
+use crate::services::storage::BlobStore;
 use actix_web::web::{Data, Json};
+use actix_web::{post, HttpRequest};
 use api_structure::auth::jwt::{Claim, JWTs};
 use api_structure::auth::register::NewUserRequest;
 use api_structure::auth::role::Role;
@@ -11,10 +14,13 @@ use api_structure::error::{ApiErr, ApiErrorType};
 
 #[post("/auth/sign_up")]
 async fn sign_up_route(
+    req: HttpRequest,
     Json(data): Json<NewUserRequest>,
     crypto: Data<CryptoService>,
     config: Data<Config>,
     db: Data<UserDBService>,
+    temp_s: Data<TempUploadDBService>,
+    store: Data<dyn BlobStore>,
 ) -> ApiResult<Json<JWTs>> {
     if !config
         .root_folder
@@ -56,6 +62,9 @@ async fn sign_up_route(
             err_type: ApiErrorType::InvalidInput,
         }),
     }?;
+    temp_s
+        .claim(&data.icon_temp_name, &image::owner(&req, &crypto))
+        .await?;
     let user = db
         .new_user(
             data.name,
@@ -71,10 +80,14 @@ async fn sign_up_route(
 
     let name = format!("{}.{}", id, ext);
 
-    std::fs::rename(
-        config.root_folder.join("temp").join(data.icon_temp_name),
-        config.root_folder.join("users").join("icon").join(name),
-    )?;
+    let temp = config.root_folder.join("temp").join(&data.icon_temp_name);
+    store
+        .put(
+            &format!("users/icon/{}", name),
+            tokio::fs::read(&temp).await?,
+        )
+        .await?;
+    std::fs::remove_file(temp)?;
     Ok(Json(JWTs {
         access_token: crypto.encode_claim(&Claim::new_access(id.clone(), Role::NotVerified)?)?,
         refresh_token: crypto.encode_claim(&Claim::new_refresh(id.clone(), Role::NotVerified)?)?,
@@ -0,0 +1,132 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::manga::MangaDBService;
+use crate::services::db::manga_kind::MangaKindDBService;
+use crate::services::db::progress::ProgressDBService;
+use crate::services::db::reading_stats::{DailyStats, ReadingStatsDBService};
+use crate::services::db::tag::TagDBService;
+use actix_web::post;
+use actix_web::web::{Data, Json, ReqData};
+use actix_web_grants::protect;
+use api_structure::auth::jwt::Claim;
+use api_structure::stats::{DayStats, MangaCompletion, ReadingStats, ReadingStatsRequest};
+use chrono::{Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+
+/// entries of the tag and kind ranking
+const TOP: usize = 10;
+
+fn ranking(map: HashMap<String, u32>) -> Vec<(String, u32)> {
+    let mut v: Vec<_> = map.into_iter().collect();
+    v.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    v.truncate(TOP);
+    v
+}
+
+/// (current, longest)
+fn streaks(days: &[DailyStats]) -> (u32, u32) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut last: Option<NaiveDate> = None;
+    for date in days
+        .iter()
+        .filter(|v| v.pages > 0 || v.chapters > 0)
+        .filter_map(|v| NaiveDate::parse_from_str(&v.day, "%Y-%m-%d").ok())
+    {
+        run = match last {
+            Some(last) if date - last == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        last = Some(date);
+    }
+    let today = Utc::now().date_naive();
+    let current = match last {
+        Some(last) if today - last <= Duration::days(1) => run,
+        _ => 0,
+    };
+    (current, longest)
+}
+
+#[post("/me/stats")]
+#[protect(
+    any(
+        "api_structure::auth::role::Role::Admin",
+        "api_structure::auth::role::Role::CoAdmin",
+        "api_structure::auth::role::Role::Moderator",
+        "api_structure::auth::role::Role::Author",
+        "api_structure::auth::role::Role::User"
+    ),
+    ty = "api_structure::auth::role::Role"
+)]
+pub async fn stats(
+    Json(data): Json<ReadingStatsRequest>,
+    user: ReqData<Claim>,
+    stats_s: Data<ReadingStatsDBService>,
+    progress_s: Data<ProgressDBService>,
+    manga_s: Data<MangaDBService>,
+    tag_s: Data<TagDBService>,
+    kind_s: Data<MangaKindDBService>,
+) -> ApiResult<Json<ReadingStats>> {
+    for v in [&data.from, &data.to].into_iter().flatten() {
+        if NaiveDate::parse_from_str(v, "%Y-%m-%d").is_err() {
+            return Err(ApiError::invalid_input("Dates have to be YYYY-MM-DD"));
+        }
+    }
+    let days = stats_s
+        .range(&user.id, data.from.as_deref(), data.to.as_deref())
+        .await?;
+    let (current_streak, longest_streak) = streaks(&days);
+    let mut hours = vec![0; 24];
+    let mut mangas: HashMap<String, u32> = HashMap::new();
+    let mut result = vec![];
+    for v in days {
+        for (hour, pages) in v.hours.iter().enumerate().take(24) {
+            hours[hour] += pages;
+        }
+        for (manga, pages) in v.mangas {
+            *mangas.entry(manga).or_default() += pages;
+        }
+        result.push(DayStats {
+            day: v.day,
+            pages: v.pages,
+            chapters: v.chapters,
+        });
+    }
+    let mut tags: HashMap<String, u32> = HashMap::new();
+    let mut kinds: HashMap<String, u32> = HashMap::new();
+    let mut completion = vec![];
+    let ids: Vec<String> = mangas.keys().cloned().collect();
+    let finished = progress_s.finished_by_manga(&user.id).await?;
+    // deleted mangas are missing
+    for manga in manga_s.get_many(&ids).await? {
+        let manga_id = manga.id.id().to_string();
+        let manga = manga.data;
+        let pages = mangas.get(&manga_id).copied().unwrap_or_default();
+        for tag in &manga.tags {
+            if let Some(tag) = tag_s.get_tag(&tag.thing.id().to_string()).await {
+                *tags.entry(tag.tag).or_default() += pages;
+            }
+        }
+        if let Some(kind) = kind_s.get_kind(&manga.kind.thing.id().to_string()).await {
+            *kinds.entry(kind.kind).or_default() += pages;
+        }
+        completion.push(MangaCompletion {
+            chapters_read: finished.get(&manga_id).copied().unwrap_or_default(),
+            chapters_total: manga.chapters.len() as u32,
+            manga_id,
+            titles: manga.titles,
+        });
+    }
+    completion.sort_by(|a, b| a.manga_id.cmp(&b.manga_id));
+    Ok(Json(ReadingStats {
+        total_pages: result.iter().map(|v| v.pages).sum(),
+        total_chapters: result.iter().map(|v| v.chapters).sum(),
+        days: result,
+        hours,
+        tags: ranking(tags),
+        kinds: ranking(kinds),
+        completion,
+        current_streak,
+        longest_streak,
+    }))
+}
@@ -1,10 +1,14 @@
+use crate::errors::{ApiError, ApiResult};
 use crate::services::crypto_service::CryptoService;
 use actix_web::dev::ServiceRequest;
-use actix_web::web::Data;
-use actix_web::{Error, HttpMessage};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::web::{Data, Query};
+use actix_web::{Error, HttpMessage, HttpRequest};
 use actix_web_grants::authorities::AttachAuthorities;
 use actix_web_httpauth::extractors::bearer::BearerAuth;
 use api_structure::auth::jwt::JwtType;
+use api_structure::auth::role::Role;
+use serde::Deserialize;
 
 pub async fn validator(
     req: ServiceRequest,
@@ -27,3 +31,37 @@ pub async fn validator(
         Err(e) => Err((e.into(), req)),
     }
 }
+
+/// query of a url signed with [CryptoService::sign_url]
+#[derive(Deserialize)]
+struct UrlSignature {
+    uid: String,
+    exp: u64,
+    sig: String,
+}
+
+/// for routes outside of the bearer scope which accept a signed url or an access token
+/// of a verified user. `path` is the signed path relative to `/api/`. returns the user id
+pub fn authorize_url(req: &HttpRequest, crypto: &CryptoService, path: &str) -> ApiResult<String> {
+    if let Ok(Query(v)) = Query::<UrlSignature>::from_query(req.query_string()) {
+        crypto.verify_url(path, &v.uid, v.exp, &v.sig)?;
+        return Ok(v.uid);
+    }
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or(ApiError::unothorized_error(
+            "Missing token",
+            "needs a Bearer header or a signed url",
+        ))?;
+    let claim = crypto.decode_claim(token)?;
+    if !matches!(claim.jwt_type, JwtType::AccessToken) || matches!(claim.role, Role::NotVerified) {
+        return Err(ApiError::unothorized_error(
+            "Invalid token",
+            "needs an access token of a verified user",
+        ));
+    }
+    Ok(claim.id)
+}
@@ -3,7 +3,9 @@ use api_structure::auth::jwt::Claim;
 use api_structure::error::{ApiErr, ApiErrorType};
 use api_structure::now_timestamp;
 use bcrypt::{hash, verify, DEFAULT_COST};
+use hmac::{Hmac, Mac};
 use jsonwebtoken::{decode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use sha2::Sha256;
 
 #[derive(Debug, Clone)]
 pub struct CryptoService {
@@ -45,4 +47,89 @@ impl CryptoService {
             .into()
         })
     }
+
+    fn url_mac(&self, path: &str, uid: &str, exp: u64) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("hmac accepts every key size");
+        mac.update(format!("{}\n{}\n{}", path, uid, exp).as_bytes());
+        mac
+    }
+
+    /// hex signature of a url `path` for user `uid`. valid until `exp` in seconds
+    pub fn sign_url(&self, path: &str, uid: &str, exp: u64) -> String {
+        self.url_mac(path, uid, exp)
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    pub fn verify_url(&self, path: &str, uid: &str, exp: u64, sig: &str) -> ApiResult<()> {
+        if exp < now_timestamp()?.as_secs() {
+            return Err(ApiError::expired_token_error("Url expired"));
+        }
+        let sig = (0..sig.len())
+            .step_by(2)
+            .map(|i| {
+                sig.get(i..i + 2)
+                    .and_then(|v| u8::from_str_radix(v, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .unwrap_or_default();
+        self.url_mac(path, uid, exp)
+            .verify_slice(&sig)
+            .map_err(|e| ApiError::unothorized_error("Invalid signature", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CryptoService;
+    use api_structure::now_timestamp;
+
+    const PATH: &str = "/api/image/page";
+
+    fn service(secret: &str) -> CryptoService {
+        CryptoService {
+            secret: secret.as_bytes().to_vec(),
+        }
+    }
+
+    fn in_a_minute() -> u64 {
+        now_timestamp().unwrap().as_secs() + 60
+    }
+
+    #[test]
+    fn signed_url() {
+        let crypto = service("secret");
+        let exp = in_a_minute();
+        let sig = crypto.sign_url(PATH, "user", exp);
+        assert_eq!(sig.len(), 64);
+        let valid = |path, uid, exp| crypto.verify_url(path, uid, exp, &sig).is_ok();
+        assert!(valid(PATH, "user", exp));
+        assert!(!valid("/api/image/cover", "user", exp));
+        assert!(!valid(PATH, "other", exp));
+        assert!(!valid(PATH, "user", exp + 1));
+        let other = service("other");
+        assert!(other.verify_url(PATH, "user", exp, &sig).is_err());
+    }
+
+    #[test]
+    fn expired_url() {
+        let crypto = service("secret");
+        let exp = now_timestamp().unwrap().as_secs() - 1;
+        let sig = crypto.sign_url(PATH, "user", exp);
+        assert!(crypto.verify_url(PATH, "user", exp, &sig).is_err());
+    }
+
+    #[test]
+    fn malformed_signature() {
+        let crypto = service("secret");
+        let exp = in_a_minute();
+        let sig = crypto.sign_url(PATH, "user", exp);
+        for sig in ["", "zz", &sig[1..], &sig[..62], &format!("{}0", sig)] {
+            assert!(crypto.verify_url(PATH, "user", exp, sig).is_err());
+        }
+    }
 }
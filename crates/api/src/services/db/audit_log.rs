@@ -0,0 +1,120 @@
+use crate::errors::ApiResult;
+use crate::services::db::user::User;
+use actix_web::HttpRequest;
+use api_structure::audit::{AuditAction, AuditLogRequest};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingType};
+
+/// append only. entries cant be changed or removed
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("audit_log")]
+#[sql(["DEFINE EVENT audit_log_append_only ON TABLE audit_log WHEN $event = \"UPDATE\" OR $event = \"DELETE\" THEN (THROW \"audit_log is append only\");"])]
+pub struct AuditEntry {
+    pub actor: ThingType<User>,
+    /// [AuditAction]
+    pub action: u32,
+    /// record id. e.g. mangas:abc
+    pub target: String,
+    /// json
+    pub before: Option<String>,
+    /// json
+    pub after: Option<String>,
+    /// address of the connection
+    pub ip: Option<String>,
+    /// untrusted. forwarded headers can be set by the client
+    pub forwarded_for: Option<String>,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+pub struct AuditLogDBService {
+    conn: Arc<Surreal<Db>>,
+}
+
+impl AuditLogDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    /// `before` and `after` are json. see [to_json]
+    pub async fn log(
+        &self,
+        req: &HttpRequest,
+        actor: &str,
+        action: AuditAction,
+        target: Thing,
+        before: Option<String>,
+        after: Option<String>,
+    ) -> ApiResult<()> {
+        AuditEntry {
+            actor: ThingType::from(Thing::from((User::name(), actor))),
+            action: action as u32,
+            target: target.to_string(),
+            before,
+            after,
+            ip: req.peer_addr().map(|v| v.ip().to_string()),
+            forwarded_for: ["x-forwarded-for", "forwarded"]
+                .into_iter()
+                .find_map(|v| req.headers().get(v))
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string()),
+            created: Default::default(),
+        }
+        .add_i(&*self.conn)
+        .await?;
+        Ok(())
+    }
+
+    /// `actor` is the id of the user
+    pub async fn search(
+        &self,
+        actor: Option<String>,
+        req: &AuditLogRequest,
+    ) -> ApiResult<Vec<RecordData<AuditEntry>>> {
+        let mut filter = vec![];
+        if let Some(actor) = actor {
+            filter.push(format!(
+                "actor = {}",
+                Thing::from((User::name(), actor.as_str()))
+            ));
+        }
+        if let Some(action) = req.action {
+            filter.push(format!("action = {}", action as u32));
+        }
+        if let Some(from) = req.from {
+            filter.push(format!("created >= {}", datetime(from)));
+        }
+        if let Some(to) = req.to {
+            filter.push(format!("created <= {}", datetime(to)));
+        }
+        let filter = match filter.is_empty() {
+            true => "".to_string(),
+            false => format!("WHERE {} ", filter.join(" AND ")),
+        };
+        Ok(AuditEntry::search(
+            &*self.conn,
+            Some(format!(
+                "{}ORDER BY created DESC LIMIT {} START {}",
+                filter,
+                req.limit,
+                (req.page.max(1) - 1) * req.limit
+            )),
+        )
+        .await?)
+    }
+}
+
+pub fn to_json(v: &impl Serialize) -> ApiResult<Option<String>> {
+    Ok(Some(serde_json::to_string(v)?))
+}
+
+fn datetime(millis: i64) -> Datetime {
+    Datetime::from(
+        DateTime::<Utc>::from_timestamp_millis(millis).unwrap_or(DateTime::<Utc>::MIN_UTC),
+    )
+}
@@ -6,9 +6,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use surrealdb::engine::local::Db;
-use surrealdb::sql::Datetime;
+use surrealdb::sql::{Datetime, Thing};
 use surrealdb::Surreal;
-use surrealdb_extras::{RecordData, SurrealSelect, SurrealTable, ThingType};
+use surrealdb_extras::{RecordData, SurrealSelect, SurrealTable, SurrealTableInfo, ThingType};
 
 #[derive(SurrealTable, Serialize, Deserialize, Debug)]
 #[db("chapters")]
@@ -36,6 +36,17 @@ pub struct ChapterReaderPart {
     pub versions: HashMap<String, ThingType<ChapterVersion>>,
 }
 
+#[derive(SurrealSelect, Deserialize)]
+pub struct ChapterCreated {
+    pub chapter: f64,
+    pub created: Datetime,
+}
+
+#[derive(SurrealSelect, Deserialize)]
+pub struct ChapterNumber {
+    pub chapter: f64,
+}
+
 pub struct ChapterDBService {
     conn: Arc<Surreal<Db>>,
 }
@@ -64,4 +75,50 @@ impl ChapterDBService {
                 .collect(),
         })
     }
+
+    /// id of the chapter_version_connection for a version of a chapter
+    pub async fn get_version(&self, chapter_id: &str, version_id: &str) -> ApiResult<String> {
+        let id: ThingType<Chapter> = ThingType::from(Thing::from((Chapter::name(), chapter_id)));
+        let res: RecordData<ChapterReaderPart> = id
+            .get_part(&*self.conn)
+            .await?
+            .ok_or(ApiError::db_error())?;
+        res.data
+            .versions
+            .get(version_id)
+            .map(|v| v.thing.id().to_string())
+            .ok_or(ApiError::invalid_input("Version does not exist"))
+    }
+
+    pub async fn numbers(
+        &self,
+        chapters: Vec<ThingType<Chapter>>,
+    ) -> ApiResult<Vec<RecordData<ChapterNumber>>> {
+        let ids: Vec<Thing> = chapters.into_iter().map(|v| v.thing.0).collect();
+        Ok(self
+            .conn
+            .query(format!(
+                "SELECT id, chapter FROM {} WHERE id INSIDE $ids",
+                Chapter::name()
+            ))
+            .bind(("ids", ids))
+            .await?
+            .take(0)?)
+    }
+
+    /// oldest first
+    pub async fn created_since(
+        &self,
+        since: &Datetime,
+    ) -> ApiResult<Vec<RecordData<ChapterCreated>>> {
+        Ok(self
+            .conn
+            .query(format!(
+                "SELECT id, chapter, created FROM {} WHERE created > $since ORDER BY created ASC",
+                Chapter::name()
+            ))
+            .bind(("since", since.clone()))
+            .await?
+            .take(0)?)
+    }
 }
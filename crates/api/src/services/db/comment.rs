@@ -0,0 +1,186 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::chapter::Chapter;
+use crate::services::db::manga::Manga;
+use crate::services::db::user::User;
+use api_structure::comment::CommentTarget;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingFunc, ThingType};
+
+/// either `manga` or `chapter` is set
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("comments")]
+pub struct Comment {
+    pub manga: Option<ThingType<Manga>>,
+    pub chapter: Option<ThingType<Chapter>>,
+    pub parent: Option<ThingType<Comment>>,
+    pub author: ThingType<User>,
+    pub text: String,
+    pub spoiler: bool,
+    pub deleted: bool,
+    pub hidden: bool,
+    pub edited: bool,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+impl Comment {
+    pub fn target(&self) -> ApiResult<CommentTarget> {
+        match (&self.manga, &self.chapter) {
+            (Some(v), _) => Ok(CommentTarget::Manga(v.thing.id().to_string())),
+            (None, Some(v)) => Ok(CommentTarget::Chapter(v.thing.id().to_string())),
+            (None, None) => Err(ApiError::db_error()),
+        }
+    }
+}
+
+fn target_filter(target: &CommentTarget) -> String {
+    match target {
+        CommentTarget::Manga(id) => {
+            format!("manga = {}", Thing::from((Manga::name(), id.as_str())))
+        }
+        CommentTarget::Chapter(id) => {
+            format!("chapter = {}", Thing::from((Chapter::name(), id.as_str())))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ReplyCount {
+    parent: Thing,
+    count: u32,
+}
+
+pub struct CommentDBService {
+    conn: Arc<Surreal<Db>>,
+}
+
+impl CommentDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    pub async fn add(
+        &self,
+        target: &CommentTarget,
+        parent: Option<&str>,
+        author: &str,
+        text: String,
+        spoiler: bool,
+    ) -> ApiResult<String> {
+        let (manga, chapter) = match target {
+            CommentTarget::Manga(id) => (
+                Some(ThingType::from(Thing::from((Manga::name(), id.as_str())))),
+                None,
+            ),
+            CommentTarget::Chapter(id) => (
+                None,
+                Some(ThingType::from(Thing::from((Chapter::name(), id.as_str())))),
+            ),
+        };
+        let record = Comment {
+            manga,
+            chapter,
+            parent: parent.map(|v| ThingType::from(Thing::from((Comment::name(), v)))),
+            author: ThingType::from(Thing::from((User::name(), author))),
+            text,
+            spoiler,
+            deleted: false,
+            hidden: false,
+            edited: false,
+            created: Default::default(),
+        }
+        .add_i(&*self.conn)
+        .await?;
+        Ok(record.id.id().to_string())
+    }
+
+    pub async fn get(&self, id: &str) -> ApiResult<RecordData<Comment>> {
+        ThingFunc::from((Comment::name(), id))
+            .get(&*self.conn)
+            .await?
+            .ok_or(ApiError::invalid_input("Comment does not exist"))
+    }
+
+    /// top level comments newest first, replies oldest first
+    pub async fn list(
+        &self,
+        target: &CommentTarget,
+        parent: Option<&str>,
+        limit: u32,
+        page: u32,
+    ) -> ApiResult<Vec<RecordData<Comment>>> {
+        let (parent, order) = match parent {
+            Some(v) => (Thing::from((Comment::name(), v)).to_string(), "ASC"),
+            None => ("NONE".to_string(), "DESC"),
+        };
+        Ok(Comment::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE {} AND parent = {} ORDER BY created {} LIMIT {} START {}",
+                target_filter(target),
+                parent,
+                order,
+                limit,
+                (page.max(1) - 1) * limit
+            )),
+        )
+        .await?)
+    }
+
+    /// comment id => number of direct replies
+    pub async fn replies(&self, ids: &[String]) -> ApiResult<HashMap<String, u32>> {
+        let ids: Vec<_> = ids
+            .iter()
+            .map(|v| Thing::from((Comment::name(), v.as_str())))
+            .collect();
+        let res: Vec<ReplyCount> = self
+            .conn
+            .query(format!(
+                "SELECT parent, count() AS count FROM {} WHERE parent IN $ids GROUP BY parent",
+                Comment::name()
+            ))
+            .bind(("ids", ids))
+            .await?
+            .take(0)?;
+        Ok(res
+            .into_iter()
+            .map(|v| (v.parent.id.to_raw(), v.count))
+            .collect())
+    }
+
+    pub async fn edit(&self, id: &str, text: String, spoiler: bool) -> ApiResult<()> {
+        self.conn
+            .query("UPDATE $id SET text = $text, spoiler = $spoiler, edited = true")
+            .bind(("id", Thing::from((Comment::name(), id))))
+            .bind(("text", text))
+            .bind(("spoiler", spoiler))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    pub async fn set_flags(&self, id: &str, deleted: bool, hidden: bool) -> ApiResult<()> {
+        self.conn
+            .query("UPDATE $id SET deleted = $deleted, hidden = $hidden")
+            .bind(("id", Thing::from((Comment::name(), id))))
+            .bind(("deleted", deleted))
+            .bind(("hidden", hidden))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, id: &str) -> ApiResult<()> {
+        self.conn
+            .query("DELETE $id")
+            .bind(("id", Thing::from((Comment::name(), id))))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
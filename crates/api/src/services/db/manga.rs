@@ -1,6 +1,7 @@
 use crate::errors::{ApiError, ApiResult};
 use crate::services::db::chapter::Chapter;
 use crate::services::db::manga_kind::{Kind, MangaKindDBService};
+use crate::services::db::rating::average_sql;
 use crate::services::db::tag::{Tag, TagDBService};
 use crate::services::db::user::{User, UserDBService};
 use crate::services::db::version::Version;
@@ -13,11 +14,12 @@ use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use surrealdb::engine::local::Db;
-use surrealdb::sql::Datetime;
+use surrealdb::opt::PatchOp;
+use surrealdb::sql::{Datetime, Thing};
 use surrealdb::Surreal;
 use surrealdb_extras::{
-    RecordData, SurrealSelect, SurrealSelectInfo, SurrealTable, SurrealTableInfo, ThingFunc,
-    ThingType,
+    Record, RecordData, SurrealSelect, SurrealSelectInfo, SurrealTable, SurrealTableInfo,
+    ThingFunc, ThingType,
 };
 
 #[derive(SurrealTable, Serialize, Deserialize, Debug)]
@@ -36,7 +38,6 @@ pub struct Manga {
     pub covers: Vec<String>,
     pub chapters: Vec<ThingType<Chapter>>,
     pub sources: Vec<String>,
-    pub relations: Vec<ThingType<Manga>>,
     pub scraper: Vec<ThingType<Version>>,
     #[opt(exclude = true)]
     pub updated: Datetime,
@@ -44,12 +45,44 @@ pub struct Manga {
     pub created: Datetime,
 }
 
+/// part of a [Manga] which can be edited
+#[derive(SurrealSelect, Serialize, Deserialize)]
+pub struct MangaMetadata {
+    pub titles: HashMap<String, Vec<String>>,
+    pub description: Option<String>,
+    pub tags: Vec<ThingType<Tag>>,
+    pub status: u64,
+    pub artists: Vec<ThingType<User>>,
+    pub authors: Vec<ThingType<User>>,
+    pub sources: Vec<String>,
+}
+
 #[derive(SurrealSelect, Deserialize)]
 pub struct MangaSearch {
     pub titles: HashMap<String, Vec<String>>,
     pub tags: Vec<ThingType<Tag>>,
 }
 
+/// used to build taste profiles
+#[derive(SurrealSelect, Deserialize)]
+pub struct MangaProfile {
+    pub kind: ThingType<Kind>,
+    pub tags: Vec<ThingType<Tag>>,
+}
+
+/// used to match entries of a library import
+#[derive(SurrealSelect, Deserialize)]
+pub struct MangaLibrary {
+    pub titles: HashMap<String, Vec<String>>,
+    pub sources: Vec<String>,
+    pub chapters: Vec<ThingType<Chapter>>,
+}
+
+#[derive(SurrealSelect, Deserialize)]
+pub struct MangaTitles {
+    pub titles: HashMap<String, Vec<String>>,
+}
+
 impl Hash for Manga {
     fn hash<H: Hasher>(&self, _: &mut H) {
         unimplemented!()
@@ -82,6 +115,78 @@ impl MangaDBService {
         })?)
     }
 
+    /// mangas which still exist. in no particular order
+    pub async fn get_many(&self, ids: &[String]) -> ApiResult<Vec<RecordData<Manga>>> {
+        let ids: Vec<Thing> = ids
+            .iter()
+            .map(|v| Thing::from((Manga::name(), v.as_str())))
+            .collect();
+        Ok(self
+            .conn
+            .query("SELECT * FROM $ids")
+            .bind(("ids", ids))
+            .await?
+            .take(0)?)
+    }
+
+    /// whether the chapter is part of the manga
+    pub async fn has_chapter(&self, manga: &str, chapter: &str) -> ApiResult<bool> {
+        let res: Option<Thing> = self
+            .conn
+            .query("SELECT VALUE id FROM $manga WHERE chapters CONTAINS $chapter")
+            .bind(("manga", Thing::from((Manga::name(), manga))))
+            .bind(("chapter", Thing::from((Chapter::name(), chapter))))
+            .await?
+            .take(0)?;
+        Ok(res.is_some())
+    }
+
+    pub async fn get_metadata(&self, id: &str) -> ApiResult<MangaMetadata> {
+        let thing = ThingFunc::from((Manga::name(), id));
+        let v: RecordData<MangaMetadata> = thing
+            .get_part(&*self.conn)
+            .await?
+            .ok_or(ApiError::invalid_input("Manga does not exist"))?;
+        Ok(v.data)
+    }
+
+    pub async fn set_metadata(&self, id: &str, data: MangaMetadata) -> ApiResult<()> {
+        let thing = ThingFunc::from((Manga::name(), id));
+        let _: Option<Record> = thing.update(&*self.conn, data).await?;
+        Ok(())
+    }
+
+    pub async fn set_visibility(&self, id: &str, visibility: u64) -> ApiResult<()> {
+        let thing = ThingFunc::from((Manga::name(), id));
+        let _: Option<Record> = thing
+            .patch(&*self.conn, PatchOp::replace("visibility", visibility))
+            .await?;
+        Ok(())
+    }
+
+    /// kind and tags of every visible manga
+    pub async fn profiles(&self) -> ApiResult<Vec<RecordData<MangaProfile>>> {
+        Ok(Manga::search(&*self.conn, Some("WHERE visibility = 0".to_string())).await?)
+    }
+
+    /// titles and sources of every visible manga
+    pub async fn library(&self) -> ApiResult<Vec<RecordData<MangaLibrary>>> {
+        Ok(Manga::search(&*self.conn, Some("WHERE visibility = 0".to_string())).await?)
+    }
+
+    /// manga which contains the chapter
+    pub async fn by_chapter(&self, chapter_id: &str) -> ApiResult<Option<RecordData<MangaTitles>>> {
+        let mut res: Vec<RecordData<MangaTitles>> = Manga::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE chapters CONTAINS {} LIMIT 1",
+                Thing::from((Chapter::name(), chapter_id))
+            )),
+        )
+        .await?;
+        Ok(res.pop())
+    }
+
     pub async fn search(
         &self,
         search: SearchRequest,
@@ -113,6 +218,7 @@ enum ItemDataDefined {
     Uploader(String),
     Chapters(ItemValue),
     Uploaded(ItemValue),
+    Rating(ItemValue),
     Kind(String),
     Source(String),
     Status(i64),
@@ -174,6 +280,18 @@ impl ItemDataDefined {
                 display_eq(*bigger, *eq, not),
                 DateTime::<Utc>::from_timestamp_millis(*value).unwrap_or(DateTime::<Utc>::MIN_UTC)
             )),
+            ItemDataDefined::Rating(ItemValue::CmpFloat { bigger, eq, value }) => Ok(format!(
+                "{} {} {}",
+                average_sql(),
+                display_eq(*bigger, *eq, not),
+                value
+            )),
+            ItemDataDefined::Rating(ItemValue::CmpInt { bigger, eq, value }) => Ok(format!(
+                "{} {} {}",
+                average_sql(),
+                display_eq(*bigger, *eq, not),
+                value
+            )),
             ItemDataDefined::Kind(v) => {
                 Ok(format!("kind {}= {}", not2, kind_service.get_id(v).await?))
             }
@@ -188,6 +306,7 @@ impl ItemDataDefined {
             )),
             ItemDataDefined::Chapters(_) => unreachable!(),
             ItemDataDefined::Uploaded(_) => unreachable!(),
+            ItemDataDefined::Rating(_) => unreachable!(),
         }
     }
 }
@@ -240,6 +359,13 @@ impl TryFrom<ItemData> for ItemDataDefined {
             return Ok(ItemDataDefined::Chapters(value.value));
         } else if key == "uploaded" && matches!(value.value, ItemValue::CmpInt { .. }) {
             return Ok(ItemDataDefined::Uploaded(value.value));
+        } else if key == "rating"
+            && matches!(
+                value.value,
+                ItemValue::CmpFloat { .. } | ItemValue::CmpInt { .. }
+            )
+        {
+            return Ok(ItemDataDefined::Rating(value.value));
         } else if key == "kind" {
             if let ItemValue::String(s) = value.value {
                 return Ok(ItemDataDefined::Kind(s));
@@ -317,6 +443,7 @@ async fn query_builder(
                     Order::LastRead => unreachable!(),
                     Order::Popularity => "list_count",
                     Order::Random => unreachable!(),
+                    Order::Rating => "rating",
                 },
                 asc
             ),
@@ -339,7 +466,10 @@ async fn query_builder(
     };
     let query = to_sql(r.query, user_id, user_service, kind_service, tag_service).await?;
     let limit = format!("LIMIT {} START {}", r.limit, (r.page - 1) * r.limit);
-    let base = format!("SELECT {fields} FROM {table}");
+    let base = match r.order {
+        Order::Rating => format!("SELECT {fields}, {} AS rating FROM {table}", average_sql()),
+        _ => format!("SELECT {fields} FROM {table}"),
+    };
     if query.is_empty() {
         Ok(format!("{base} {order} {limit}"))
     } else {
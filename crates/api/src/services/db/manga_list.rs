@@ -1,12 +1,13 @@
+use crate::errors::ApiResult;
 use crate::services::db::manga::Manga;
 use crate::services::db::user::User;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::sync::Arc;
 use surrealdb::engine::local::Db;
-use surrealdb::sql::Datetime;
+use surrealdb::sql::{Datetime, Thing};
 use surrealdb::Surreal;
-use surrealdb_extras::{SurrealTable, ThingType};
+use surrealdb_extras::{RecordData, SurrealSelect, SurrealTable, SurrealTableInfo, ThingType};
 
 #[derive(SurrealTable, Serialize, Deserialize, Debug)]
 #[db("manga_lists")]
@@ -21,6 +22,18 @@ pub struct MangaList {
     pub created: Datetime,
 }
 
+#[derive(SurrealSelect, Deserialize)]
+pub struct UserList {
+    pub name: String,
+    pub mangas: Vec<ThingType<Manga>>,
+}
+
+#[derive(SurrealSelect, Deserialize)]
+pub struct ListMangas {
+    pub user: ThingType<User>,
+    pub mangas: Vec<ThingType<Manga>>,
+}
+
 impl MangaList {
     fn new(name: String, user: ThingType<User>) -> Self {
         Self {
@@ -41,4 +54,58 @@ impl MangaListDBService {
     pub fn new(conn: Arc<Surreal<Db>>) -> Self {
         Self { conn }
     }
+
+    pub async fn of_user(&self, user: &str) -> ApiResult<Vec<RecordData<UserList>>> {
+        Ok(MangaList::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE user = {}",
+                Thing::from((User::name(), user))
+            )),
+        )
+        .await?)
+    }
+
+    /// creates the list if it doesn't exist
+    pub async fn add(&self, user: &str, name: &str, manga: &str) -> ApiResult<()> {
+        let user = Thing::from((User::name(), user));
+        let manga = Thing::from((Manga::name(), manga));
+        let updated: Vec<Thing> = self
+            .conn
+            .query(format!(
+                "UPDATE {} SET mangas = array::union(mangas, [$manga]) WHERE name = $name AND user = $user RETURN VALUE id",
+                MangaList::name()
+            ))
+            .bind(("manga", manga.clone()))
+            .bind(("name", name.to_string()))
+            .bind(("user", user.clone()))
+            .await?
+            .take(0)?;
+        if updated.is_empty() {
+            let mut list = MangaList::new(name.to_string(), ThingType::from(user));
+            list.mangas.insert(ThingType::from(manga));
+            list.add_i(&*self.conn).await?;
+        }
+        Ok(())
+    }
+
+    /// removes the manga from every list of the user which is called like one of `names`
+    pub async fn remove(&self, user: &str, names: &[&str], manga: &str) -> ApiResult<()> {
+        self.conn
+            .query(format!(
+                "UPDATE {} SET mangas -= $manga WHERE name INSIDE $names AND user = $user",
+                MangaList::name()
+            ))
+            .bind(("manga", Thing::from((Manga::name(), manga))))
+            .bind(("names", names.to_vec()))
+            .bind(("user", Thing::from((User::name(), user))))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// favorite lists of all users
+    pub async fn favorites(&self) -> ApiResult<Vec<RecordData<ListMangas>>> {
+        Ok(MangaList::search(&*self.conn, Some("WHERE name = \"Favorites\"".to_string())).await?)
+    }
 }
@@ -0,0 +1,82 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::manga::Manga;
+use api_structure::info::RelationKind;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingType};
+
+/// directed edge. every relation is stored twice, once from each side
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("manga_relations")]
+pub struct MangaRelation {
+    pub manga: ThingType<Manga>,
+    pub related: ThingType<Manga>,
+    /// [RelationKind] of `related` from the view of `manga`
+    pub kind: u64,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+impl MangaRelation {
+    fn new(manga_id: &str, related_id: &str, kind: RelationKind) -> Self {
+        Self {
+            manga: ThingType::from(Thing::from((Manga::name(), manga_id))),
+            related: ThingType::from(Thing::from((Manga::name(), related_id))),
+            kind: kind as u64,
+            created: Default::default(),
+        }
+    }
+}
+
+pub struct MangaRelationDBService {
+    conn: Arc<Surreal<Db>>,
+}
+
+impl MangaRelationDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    pub async fn get(&self, manga_id: &str) -> ApiResult<Vec<RecordData<MangaRelation>>> {
+        Ok(MangaRelation::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE manga = {} ORDER BY created ASC",
+                Thing::from((Manga::name(), manga_id))
+            )),
+        )
+        .await?)
+    }
+
+    /// links both mangas. replaces an existing relation between them
+    pub async fn add(&self, manga_id: &str, related_id: &str, kind: RelationKind) -> ApiResult<()> {
+        if manga_id == related_id {
+            return Err(ApiError::invalid_input("Manga cant relate to itself"));
+        }
+        self.delete(manga_id, related_id).await?;
+        MangaRelation::new(manga_id, related_id, kind)
+            .add_i(&*self.conn)
+            .await?;
+        MangaRelation::new(related_id, manga_id, kind.inverse())
+            .add_i(&*self.conn)
+            .await?;
+        Ok(())
+    }
+
+    /// removes the relation in both directions
+    pub async fn delete(&self, manga_id: &str, related_id: &str) -> ApiResult<()> {
+        self.conn
+            .query(format!(
+                "DELETE {table} WHERE (manga = {a} AND related = {b}) OR (manga = {b} AND related = {a})",
+                table = MangaRelation::name(),
+                a = Thing::from((Manga::name(), manga_id)),
+                b = Thing::from((Manga::name(), related_id))
+            ))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::manga::{Manga, MangaMetadata};
+use crate::services::db::user::User;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingType};
+
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("manga_revisions")]
+pub struct MangaRevision {
+    pub manga: ThingType<Manga>,
+    pub editor: ThingType<User>,
+    /// json object with the changed fields of [MangaMetadata] before the edit
+    pub before: String,
+    /// json object with the changed fields of [MangaMetadata] after the edit
+    pub after: String,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+impl MangaRevision {
+    pub fn before(&self) -> ApiResult<Map<String, Value>> {
+        Ok(serde_json::from_str(&self.before)?)
+    }
+
+    pub fn after(&self) -> ApiResult<Map<String, Value>> {
+        Ok(serde_json::from_str(&self.after)?)
+    }
+
+    /// field, before, after
+    pub fn changes(&self) -> ApiResult<Vec<(String, Value, Value)>> {
+        let mut before = self.before()?;
+        Ok(self
+            .after()?
+            .into_iter()
+            .map(|(key, after)| {
+                let before = before.remove(&key).unwrap_or(Value::Null);
+                (key, before, after)
+            })
+            .collect())
+    }
+}
+
+/// changed fields as (before, after)
+pub fn diff(
+    old: &MangaMetadata,
+    new: &MangaMetadata,
+) -> ApiResult<(Map<String, Value>, Map<String, Value>)> {
+    let mut old = to_map(old)?;
+    let mut before = Map::new();
+    let mut after = Map::new();
+    for (key, value) in to_map(new)? {
+        let prev = old.remove(&key).unwrap_or(Value::Null);
+        if prev != value {
+            before.insert(key.clone(), prev);
+            after.insert(key, value);
+        }
+    }
+    Ok((before, after))
+}
+
+/// overwrites the fields in `data` with `fields`
+pub fn apply(data: &MangaMetadata, fields: Map<String, Value>) -> ApiResult<MangaMetadata> {
+    let mut data = to_map(data)?;
+    data.extend(fields);
+    Ok(serde_json::from_value(Value::Object(data))?)
+}
+
+fn to_map(data: &MangaMetadata) -> ApiResult<Map<String, Value>> {
+    match serde_json::to_value(data)? {
+        Value::Object(v) => Ok(v),
+        _ => Err(ApiError::db_error()),
+    }
+}
+
+pub struct MangaRevisionDBService {
+    conn: Arc<Surreal<Db>>,
+}
+
+impl MangaRevisionDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    pub async fn add(
+        &self,
+        manga_id: &str,
+        editor: &str,
+        before: Map<String, Value>,
+        after: Map<String, Value>,
+    ) -> ApiResult<()> {
+        MangaRevision {
+            manga: ThingType::from(Thing::from((Manga::name(), manga_id))),
+            editor: ThingType::from(Thing::from((User::name(), editor))),
+            before: serde_json::to_string(&before)?,
+            after: serde_json::to_string(&after)?,
+            created: Default::default(),
+        }
+        .add_i(&*self.conn)
+        .await?;
+        Ok(())
+    }
+
+    /// newest first
+    pub async fn history(&self, manga_id: &str) -> ApiResult<Vec<RecordData<MangaRevision>>> {
+        Ok(MangaRevision::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE manga = {} ORDER BY created DESC",
+                Thing::from((Manga::name(), manga_id))
+            )),
+        )
+        .await?)
+    }
+}
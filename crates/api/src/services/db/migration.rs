@@ -0,0 +1,589 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::manga::Manga;
+use crate::services::db::manga_relation::MangaRelation;
+use crate::services::db::page::Page;
+use crate::services::db::progress::{UserProgress, FINISHED};
+use crate::services::db::reading_stats::{day, key_query, DailyStats};
+use crate::services::db::translation::{PageTranslation, TranslationResponse};
+use crate::services::storage::{page_key, BlobStore};
+use api_structure::info::RelationKind;
+use chrono::Timelike;
+use futures_util::future::BoxFuture;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingType};
+
+/// single record `schema_version:current`
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("schema_version")]
+pub struct SchemaVersion {
+    /// version of the last migration which ran
+    pub version: u32,
+    #[opt(exclude = true)]
+    pub updated: Datetime,
+}
+
+fn current() -> Thing {
+    Thing::from((SchemaVersion::name(), "current"))
+}
+
+type Run = for<'a> fn(&'a Context<'a>) -> BoxFuture<'a, ApiResult<()>>;
+
+/// has to be idempotent, because a failed run is repeated on the next start
+struct Migration {
+    /// the database has this version after the migration
+    version: u32,
+    name: &'static str,
+    run: Run,
+}
+
+/// ordered by version. never change a migration which was released, add a new one
+const MIGRATIONS: [Migration; 8] = [
+    Migration {
+        version: 1,
+        name: "baseline",
+        run: |_| Box::pin(async { Ok(()) }),
+    },
+    Migration {
+        version: 2,
+        name: "page hash",
+        run: |ctx| Box::pin(page_hash(ctx)),
+    },
+    Migration {
+        version: 3,
+        name: "page margins",
+        run: |ctx| Box::pin(page_margins(ctx)),
+    },
+    Migration {
+        version: 4,
+        name: "manga relations",
+        run: |ctx| Box::pin(manga_relations(ctx)),
+    },
+    Migration {
+        version: 5,
+        name: "hidden history",
+        run: |ctx| Box::pin(hidden_history(ctx)),
+    },
+    Migration {
+        version: 6,
+        name: "reading stats",
+        run: |ctx| Box::pin(reading_stats(ctx)),
+    },
+    Migration {
+        version: 7,
+        name: "legacy translations",
+        run: |ctx| Box::pin(legacy_translations(ctx)),
+    },
+    Migration {
+        version: 8,
+        name: "daily stats keys",
+        run: |ctx| Box::pin(daily_stats_keys(ctx)),
+    },
+];
+
+/// schema version of this binary
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|v| v.version).unwrap_or_default()
+}
+
+struct Context<'a> {
+    db: &'a Surreal<Db>,
+    store: &'a dyn BlobStore,
+    /// changes are only counted
+    dry_run: bool,
+}
+
+impl Context<'_> {
+    /// returns the number of records which are or would be changed
+    async fn update(&self, table: &str, set: &str, filter: &str) -> ApiResult<usize> {
+        let ids: Vec<Thing> = match self.dry_run {
+            true => self
+                .db
+                .query(format!("SELECT VALUE id FROM {} WHERE {}", table, filter))
+                .await?
+                .take(0)?,
+            false => self
+                .db
+                .query(format!(
+                    "UPDATE {} SET {} WHERE {} RETURN VALUE id",
+                    table, set, filter
+                ))
+                .await?
+                .take(0)?,
+        };
+        Ok(ids.len())
+    }
+}
+
+#[derive(Deserialize)]
+struct DbInfo {
+    tables: HashMap<String, String>,
+}
+
+pub async fn version(db: &Surreal<Db>) -> ApiResult<u32> {
+    // a strict datastore fails to select from a table which isn't defined yet
+    let info: Option<DbInfo> = db.query("INFO FOR DB").await?.take(0)?;
+    if !info.is_some_and(|v| v.tables.contains_key(SchemaVersion::name())) {
+        return Ok(0);
+    }
+    let version: Option<u32> = db
+        .query("SELECT VALUE version FROM $id")
+        .bind(("id", current()))
+        .await?
+        .take(0)?;
+    Ok(version.unwrap_or_default())
+}
+
+async fn set_version(db: &Surreal<Db>, version: u32) -> ApiResult<()> {
+    db.query("UPDATE $id SET version = $version, updated = time::now()")
+        .bind(("id", current()))
+        .bind(("version", version))
+        .await?
+        .check()?;
+    Ok(())
+}
+
+/// fails if the database was migrated by a newer binary. returns the version
+pub async fn check_version(db: &Surreal<Db>) -> ApiResult<u32> {
+    let current = version(db).await?;
+    if current > latest_version() {
+        return Err(ApiError::invalid_input(format!(
+            "Database schema version {} is newer than {}. Update the server",
+            current,
+            latest_version()
+        )));
+    }
+    Ok(current)
+}
+
+/// runs every pending migration. fails if the database was migrated by a newer binary
+pub async fn migrate(db: &Surreal<Db>, store: &dyn BlobStore, dry_run: bool) -> ApiResult<()> {
+    let current = check_version(db).await?;
+    let ctx = Context { db, store, dry_run };
+    for migration in MIGRATIONS.iter().filter(|v| v.version > current) {
+        info!(
+            "{} migration {} ({})",
+            if dry_run { "Checking" } else { "Running" },
+            migration.version,
+            migration.name
+        );
+        (migration.run)(&ctx).await?;
+        if !dry_run {
+            set_version(db, migration.version).await?;
+        }
+    }
+    Ok(())
+}
+
+/// pages which were added before hashing existed. `api fsck --fix` computes the hashes
+async fn page_hash(ctx: &Context<'_>) -> ApiResult<()> {
+    let count = ctx
+        .update(Page::name(), "hash = \"\"", "hash = NONE")
+        .await?;
+    info!("{} pages without hash", count);
+    Ok(())
+}
+
+/// pages which were added before margins were detected
+async fn page_margins(ctx: &Context<'_>) -> ApiResult<()> {
+    let fields = ["margin_top", "margin_bottom", "margin_left", "margin_right"];
+    let set: Vec<_> = fields
+        .iter()
+        .map(|v| format!("{0} = {0} ?? 0", v))
+        .collect();
+    let filter: Vec<_> = fields.iter().map(|v| format!("{} = NONE", v)).collect();
+    let count = ctx
+        .update(Page::name(), &set.join(", "), &filter.join(" OR "))
+        .await?;
+    info!("{} pages without margins", count);
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct OldRelations {
+    id: Thing,
+    relations: Vec<Thing>,
+}
+
+/// relations were a list on the manga before they had a kind. every pair becomes two
+/// [MangaRelation] edges of kind [RelationKind::Related], unless they are already related
+async fn manga_relations(ctx: &Context<'_>) -> ApiResult<()> {
+    let old: Vec<OldRelations> = ctx
+        .db
+        .query(format!(
+            "SELECT id, relations FROM {} WHERE relations != NONE",
+            Manga::name()
+        ))
+        .await?
+        .take(0)?;
+    let mut pairs = HashSet::new();
+    for manga in old {
+        for related in manga.relations.into_iter().filter(|v| v != &manga.id) {
+            match manga.id.to_string() < related.to_string() {
+                true => pairs.insert((manga.id.clone(), related)),
+                false => pairs.insert((related, manga.id.clone())),
+            };
+        }
+    }
+    info!("{} manga relations", pairs.len());
+    if ctx.dry_run {
+        return Ok(());
+    }
+    let kind = RelationKind::Related as u64;
+    for (a, b) in pairs {
+        let existing: Vec<Thing> = ctx
+            .db
+            .query(format!(
+                "SELECT VALUE id FROM {} WHERE (manga = $a AND related = $b) OR (manga = $b AND related = $a)",
+                MangaRelation::name()
+            ))
+            .bind(("a", &a))
+            .bind(("b", &b))
+            .await?
+            .take(0)?;
+        if !existing.is_empty() {
+            continue;
+        }
+        ctx.db
+            .query(format!(
+                "CREATE {table} SET manga = $a, related = $b, kind = $kind, created = time::now(); \
+                 CREATE {table} SET manga = $b, related = $a, kind = $kind, created = time::now()",
+                table = MangaRelation::name()
+            ))
+            .bind(("a", &a))
+            .bind(("b", &b))
+            .bind(("kind", kind))
+            .await?
+            .check()?;
+    }
+    ctx.update(Manga::name(), "relations = NONE", "relations != NONE")
+        .await?;
+    Ok(())
+}
+
+/// progress from before history entries could be hidden
+async fn hidden_history(ctx: &Context<'_>) -> ApiResult<()> {
+    let count = ctx
+        .update(UserProgress::name(), "hidden = false", "hidden = NONE")
+        .await?;
+    info!("{} progress entries without visibility", count);
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct ReadChapter {
+    user: Thing,
+    manga: Thing,
+    progress: f64,
+    updated: Datetime,
+    versions: Option<BTreeMap<String, Thing>>,
+}
+
+#[derive(Deserialize)]
+struct VersionPages {
+    id: Thing,
+    pages: u32,
+}
+
+/// [DailyStats] are recorded since progress is saved through them. users without any
+/// get them from their progress. every chapter counts on the day it was read last
+async fn reading_stats(ctx: &Context<'_>) -> ApiResult<()> {
+    let read: Vec<ReadChapter> = ctx
+        .db
+        .query(format!(
+            "LET $done = (SELECT VALUE user FROM {} GROUP BY user); \
+             SELECT user, manga, progress, updated, chapter.versions AS versions \
+             FROM {} WHERE user NOTINSIDE $done",
+            DailyStats::name(),
+            UserProgress::name()
+        ))
+        .await?
+        .take(1)?;
+    let versions: HashSet<Thing> = read
+        .iter()
+        .filter_map(|v| v.versions.as_ref()?.values().next().cloned())
+        .collect();
+    let pages: Vec<VersionPages> = ctx
+        .db
+        .query("SELECT id, array::len(pages) AS pages FROM $versions")
+        .bind(("versions", versions.into_iter().collect::<Vec<_>>()))
+        .await?
+        .take(0)?;
+    let pages: HashMap<Thing, u32> = pages.into_iter().map(|v| (v.id, v.pages)).collect();
+    let mut days: HashMap<(Thing, String), DailyStats> = HashMap::new();
+    for v in read {
+        let version = v.versions.as_ref().and_then(|v| v.values().next());
+        let total = version
+            .and_then(|v| pages.get(v))
+            .copied()
+            .unwrap_or_default();
+        let read_pages = (v.progress * total as f64).round() as u32;
+        let stats = days
+            .entry((v.user.clone(), day(v.updated.0)))
+            .or_insert_with(|| DailyStats {
+                user: ThingType::from(v.user.clone()),
+                day: day(v.updated.0),
+                pages: 0,
+                chapters: 0,
+                hours: vec![0; 24],
+                mangas: HashMap::new(),
+            });
+        stats.pages += read_pages;
+        stats.chapters += (v.progress >= FINISHED) as u32;
+        stats.hours[v.updated.0.hour() as usize] += read_pages;
+        *stats.mangas.entry(v.manga.id.to_raw()).or_default() += read_pages;
+    }
+    info!("{} days of reading stats", days.len());
+    if ctx.dry_run || days.is_empty() {
+        return Ok(());
+    }
+    // a single statement, so a failed run doesn't leave users with half of their stats
+    ctx.db
+        .query(format!("INSERT INTO {} $rows", DailyStats::name()))
+        .bind(("rows", days.into_values().collect::<Vec<_>>()))
+        .await?
+        .check()?;
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct MangaChapters {
+    id: Thing,
+    chapters: Vec<Thing>,
+}
+
+#[derive(Deserialize)]
+struct ChapterVersions {
+    id: Thing,
+    versions: BTreeMap<String, Thing>,
+}
+
+#[derive(Deserialize)]
+struct PageNumber {
+    id: Thing,
+    page: u32,
+}
+
+/// [PageTranslation] without the timestamps, which are set by the database
+#[derive(Serialize)]
+struct NewArea {
+    page: Thing,
+    source: String,
+    translated_text: HashMap<String, String>,
+    min_x: u32,
+    min_y: u32,
+    max_x: u32,
+    max_y: u32,
+    text_color: u32,
+    outline_color: u32,
+    background: String,
+}
+
+impl From<PageTranslation> for NewArea {
+    fn from(v: PageTranslation) -> Self {
+        Self {
+            page: v.page.thing.0,
+            source: v.source,
+            translated_text: v.translated_text,
+            min_x: v.min_x,
+            min_y: v.min_y,
+            max_x: v.max_x,
+            max_y: v.max_y,
+            text_color: v.text_color,
+            outline_color: v.outline_color,
+            background: v.background,
+        }
+    }
+}
+
+/// translations of the external tool were json files next to the pages. they are imported
+/// into pages without areas. the files are kept
+async fn legacy_translations(ctx: &Context<'_>) -> ApiResult<()> {
+    let mangas: Vec<MangaChapters> = ctx
+        .db
+        .query(format!("SELECT id, chapters FROM {}", Manga::name()))
+        .await?
+        .take(0)?;
+    let mut count = 0;
+    for manga in mangas {
+        let chapters: Vec<ChapterVersions> = ctx
+            .db
+            .query("SELECT id, versions FROM $chapters")
+            .bind(("chapters", manga.chapters))
+            .await?
+            .take(0)?;
+        for chapter in chapters {
+            for (version, connection) in chapter.versions {
+                // the reader only looked for them in these folders
+                let Some(folder) = version.strip_prefix("chapter_versions:") else {
+                    continue;
+                };
+                let pages: Option<Vec<Thing>> = ctx
+                    .db
+                    .query("SELECT VALUE pages FROM $connection")
+                    .bind(("connection", connection))
+                    .await?
+                    .take(0)?;
+                let pages: Vec<PageNumber> = ctx
+                    .db
+                    .query("SELECT id, page FROM $pages")
+                    .bind(("pages", pages.unwrap_or_default()))
+                    .await?
+                    .take(0)?;
+                for page in pages {
+                    let key = page_key(
+                        &manga.id.id.to_raw(),
+                        &chapter.id.id.to_raw(),
+                        folder,
+                        &format!("{}.json", page.page),
+                    );
+                    let data = match ctx.store.read(&key).await {
+                        Ok(v) => v,
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                        Err(e) => return Err(e.into()),
+                    };
+                    let mut legacy: TranslationResponse = match serde_json::from_slice(&data) {
+                        Ok(v) => v,
+                        Err(e) => {
+                            warn!("Skipping invalid translation {}: {}", key, e);
+                            continue;
+                        }
+                    };
+                    if legacy.images.is_empty() {
+                        continue;
+                    }
+                    let existing: Option<Thing> = ctx
+                        .db
+                        .query(format!(
+                            "SELECT VALUE id FROM {} WHERE page = $page LIMIT 1",
+                            PageTranslation::name()
+                        ))
+                        .bind(("page", &page.id))
+                        .await?
+                        .take(0)?;
+                    if existing.is_some() {
+                        continue;
+                    }
+                    count += 1;
+                    if ctx.dry_run {
+                        continue;
+                    }
+                    let page_id = page.id.id.to_raw();
+                    let areas: Vec<NewArea> = legacy
+                        .images
+                        .remove(0)
+                        .into_iter()
+                        .map(|v| PageTranslation::from_legacy(&page_id, v).into())
+                        .collect();
+                    // a page is imported completely or not at all
+                    ctx.db
+                        .query(format!(
+                            "BEGIN TRANSACTION; \
+                             INSERT INTO {} $areas; \
+                             UPDATE $page SET translation = true; \
+                             COMMIT TRANSACTION;",
+                            PageTranslation::name()
+                        ))
+                        .bind(("areas", areas))
+                        .bind(("page", &page.id))
+                        .await?
+                        .check()?;
+                }
+            }
+        }
+    }
+    info!("{} pages with legacy translations", count);
+    Ok(())
+}
+
+fn merge(stats: &mut DailyStats, other: DailyStats) {
+    stats.pages += other.pages;
+    stats.chapters += other.chapters;
+    stats.hours.resize(24, 0);
+    for (hour, pages) in stats.hours.iter_mut().zip(other.hours) {
+        *hour += pages;
+    }
+    for (manga, pages) in other.mangas {
+        *stats.mangas.entry(manga).or_default() += pages;
+    }
+}
+
+/// [DailyStats] had random ids. they get the id `[user, day]`, so progress can be added to
+/// them in a single statement. rows of the same day are merged
+async fn daily_stats_keys(ctx: &Context<'_>) -> ApiResult<()> {
+    let old: Vec<RecordData<DailyStats>> = ctx
+        .db
+        .query(format!(
+            "SELECT * FROM {} WHERE id != {}",
+            DailyStats::name(),
+            key_query("user", "day")
+        ))
+        .await?
+        .take(0)?;
+    let mut days: HashMap<(Thing, String), (Vec<Thing>, DailyStats)> = HashMap::new();
+    for row in old {
+        let key = (row.data.user.thing.0.clone(), row.data.day.clone());
+        match days.get_mut(&key) {
+            Some((ids, stats)) => {
+                ids.push(row.id);
+                merge(stats, row.data);
+            }
+            None => {
+                days.insert(key, (vec![row.id], row.data));
+            }
+        }
+    }
+    info!("{} days of reading stats without key", days.len());
+    if ctx.dry_run {
+        return Ok(());
+    }
+    for ((user, day), (ids, stats)) in days {
+        ctx.db
+            .query(format!(
+                "BEGIN TRANSACTION; \
+                 DELETE $ids; \
+                 CREATE {} CONTENT $stats; \
+                 COMMIT TRANSACTION;",
+                key_query("$user", "$day")
+            ))
+            .bind(("ids", ids))
+            .bind(("user", user))
+            .bind(("day", day))
+            .bind(("stats", stats))
+            .await?
+            .check()?;
+    }
+    // defined after the merge, because the old rows can contain duplicates
+    ctx.db
+        .query(format!(
+            "DEFINE INDEX user_daily_stats_day ON TABLE {} COLUMNS user, day UNIQUE",
+            DailyStats::name()
+        ))
+        .await?
+        .check()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_increase() {
+        for pair in MIGRATIONS.windows(2) {
+            assert!(
+                pair[0].version < pair[1].version,
+                "{} has to be newer than {}",
+                pair[1].name,
+                pair[0].name
+            );
+        }
+        // 0 is the version of a new database
+        assert!(MIGRATIONS[0].version > 0);
+    }
+}
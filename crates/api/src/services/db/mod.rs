@@ -1,14 +1,28 @@
+use crate::errors::ApiResult;
+use crate::services::db::audit_log::AuditEntry;
 use crate::services::db::auth_tokens::AuthToken;
 use crate::services::db::chapter::Chapter;
 use crate::services::db::chapter_version::ChapterVersion;
+use crate::services::db::comment::Comment;
 use crate::services::db::manga::Manga;
 use crate::services::db::manga_kind::Kind;
 use crate::services::db::manga_list::MangaList;
+use crate::services::db::manga_relation::MangaRelation;
+use crate::services::db::manga_revision::MangaRevision;
+use crate::services::db::migration::SchemaVersion;
+use crate::services::db::notification::{
+    NotificationCursor, UserNotification, UserNotificationSettings,
+};
 use crate::services::db::page::Page;
 use crate::services::db::progress::UserProgress;
+use crate::services::db::rating::MangaRating;
+use crate::services::db::reading_stats::DailyStats;
 use crate::services::db::scrape_account::ScrapeAccount;
 use crate::services::db::scrape_list::ScrapeItem;
 use crate::services::db::tag::Tag;
+use crate::services::db::temp_upload::TempUpload;
+use crate::services::db::translation::{PageTranslation, TranslationRevision};
+use crate::services::db::translation_job::TranslationJob;
 use crate::services::db::user::User;
 use crate::services::db::version::Version;
 use std::path::PathBuf;
@@ -17,45 +31,97 @@ use surrealdb::opt::Config;
 use surrealdb::Surreal;
 use surrealdb_extras::SurrealTableInfo;
 
+pub mod audit_log;
 pub mod auth_tokens;
 pub mod chapter;
 pub mod chapter_version;
+pub mod comment;
 pub mod manga;
 pub mod manga_kind;
 pub mod manga_list;
+pub mod manga_relation;
+pub mod manga_revision;
+pub mod migration;
+pub mod notification;
 pub mod page;
 pub mod progress;
+pub mod rating;
+pub mod reading_stats;
 pub mod scrape_account;
 pub mod scrape_list;
 pub mod tag;
+pub mod temp_upload;
+pub mod translation;
+pub mod translation_job;
 pub mod user;
 pub mod version;
 
 pub async fn establish(path: PathBuf, strict: bool) -> surrealdb::Result<Surreal<Db>> {
+    let conn = open(path, strict).await?;
     match strict {
-        true => {
-            let conn = Surreal::new::<SpeeDb>((path.join("db"), Config::default().strict()));
-            let register = vec![
-                AuthToken::register().expect("Illegal AuthToken structure"),
-                Chapter::register().expect("Illegal Chapter structure"),
-                ChapterVersion::register().expect("Illegal ChapterVersion structure"),
-                Manga::register().expect("Illegal Manga structure"),
-                Kind::register().expect("Illegal Kind structure"),
-                MangaList::register().expect("Illegal MangaList structure"),
-                Page::register().expect("Illegal Page structure"),
-                UserProgress::register().expect("Illegal UserProgress structure"),
-                ScrapeAccount::register().expect("Illegal ScrapeAccount structure"),
-                ScrapeItem::register().expect("Illegal ScrapeItem structure"),
-                Tag::register().expect("Illegal Tag structure"),
-                User::register().expect("Illegal User structure"),
-                Version::register().expect("Illegal Version structure"),
-            ];
-            surrealdb_extras::use_ns_db(conn, "manread", "manread", register).await
-        }
-        false => {
-            let conn = Surreal::new::<SpeeDb>((path.join("db"), Config::default())).await?;
-            conn.use_ns("manread").use_db("manread").await?;
-            Ok(conn)
-        }
+        true => register(conn).await,
+        false => Ok(conn),
+    }
+}
+
+/// opens the datastore without registering the schema
+async fn open(path: PathBuf, strict: bool) -> surrealdb::Result<Surreal<Db>> {
+    let config = match strict {
+        true => Config::default().strict(),
+        false => Config::default(),
+    };
+    let conn = Surreal::new::<SpeeDb>((path.join("db"), config)).await?;
+    if strict {
+        // a strict datastore refuses namespaces and databases which aren't defined
+        conn.query("DEFINE NAMESPACE manread; USE NS manread; DEFINE DATABASE manread")
+            .await?
+            .check()?;
+    }
+    conn.use_ns("manread").use_db("manread").await?;
+    Ok(conn)
+}
+
+/// defines every table on an open connection
+async fn register(conn: Surreal<Db>) -> surrealdb::Result<Surreal<Db>> {
+    let register = vec![
+        AuditEntry::register().expect("Illegal AuditEntry structure"),
+        AuthToken::register().expect("Illegal AuthToken structure"),
+        Chapter::register().expect("Illegal Chapter structure"),
+        ChapterVersion::register().expect("Illegal ChapterVersion structure"),
+        Comment::register().expect("Illegal Comment structure"),
+        Manga::register().expect("Illegal Manga structure"),
+        Kind::register().expect("Illegal Kind structure"),
+        MangaList::register().expect("Illegal MangaList structure"),
+        MangaRelation::register().expect("Illegal MangaRelation structure"),
+        MangaRevision::register().expect("Illegal MangaRevision structure"),
+        SchemaVersion::register().expect("Illegal SchemaVersion structure"),
+        NotificationCursor::register().expect("Illegal NotificationCursor structure"),
+        UserNotification::register().expect("Illegal UserNotification structure"),
+        UserNotificationSettings::register().expect("Illegal UserNotificationSettings structure"),
+        Page::register().expect("Illegal Page structure"),
+        UserProgress::register().expect("Illegal UserProgress structure"),
+        MangaRating::register().expect("Illegal MangaRating structure"),
+        DailyStats::register().expect("Illegal DailyStats structure"),
+        ScrapeAccount::register().expect("Illegal ScrapeAccount structure"),
+        ScrapeItem::register().expect("Illegal ScrapeItem structure"),
+        Tag::register().expect("Illegal Tag structure"),
+        TempUpload::register().expect("Illegal TempUpload structure"),
+        PageTranslation::register().expect("Illegal PageTranslation structure"),
+        TranslationRevision::register().expect("Illegal TranslationRevision structure"),
+        TranslationJob::register().expect("Illegal TranslationJob structure"),
+        User::register().expect("Illegal User structure"),
+        Version::register().expect("Illegal Version structure"),
+    ];
+    surrealdb_extras::use_ns_db(async { Ok(conn) }, "manread", "manread", register).await
+}
+
+/// [establish], which refuses a database of a newer binary before anything is registered.
+/// registering would replace the definitions of the newer schema with the ones of this binary
+pub async fn establish_checked(path: PathBuf, strict: bool) -> ApiResult<Surreal<Db>> {
+    let conn = open(path, strict).await?;
+    migration::check_version(&conn).await?;
+    match strict {
+        true => Ok(register(conn).await?),
+        false => Ok(conn),
     }
 }
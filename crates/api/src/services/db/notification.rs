@@ -0,0 +1,275 @@
+use crate::errors::ApiResult;
+use crate::services::db::chapter::Chapter;
+use crate::services::db::manga::Manga;
+use crate::services::db::manga_list::MangaList;
+use crate::services::db::progress::UserProgress;
+use crate::services::db::user::User;
+use api_structure::notification::NotificationSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{Record, RecordData, SurrealTable, SurrealTableInfo, ThingType};
+
+/// a new chapter of a manga the user follows
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("notifications")]
+#[sql(["DEFINE INDEX notification_unique ON TABLE notifications COLUMNS user, chapter UNIQUE"])]
+pub struct UserNotification {
+    pub user: ThingType<User>,
+    pub manga: ThingType<Manga>,
+    pub chapter: ThingType<Chapter>,
+    pub read: bool,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+/// users without an entry use [NotificationSettings::default]
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("notification_settings")]
+pub struct UserNotificationSettings {
+    pub user: ThingType<User>,
+    pub muted: bool,
+    pub favorites: bool,
+    pub reading: bool,
+    pub muted_mangas: Vec<ThingType<Manga>>,
+}
+
+impl UserNotificationSettings {
+    fn to_public(&self) -> NotificationSettings {
+        NotificationSettings {
+            muted: self.muted,
+            favorites: self.favorites,
+            reading: self.reading,
+            muted_mangas: self
+                .muted_mangas
+                .iter()
+                .map(|v| v.thing.id().to_string())
+                .collect(),
+        }
+    }
+}
+
+/// single record `notification_cursor:current`
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("notification_cursor")]
+pub struct NotificationCursor {
+    /// creation time of the newest chapter which was processed
+    pub created: Datetime,
+}
+
+fn cursor() -> Thing {
+    Thing::from((NotificationCursor::name(), "current"))
+}
+
+pub struct NotificationDBService {
+    conn: Arc<Surreal<Db>>,
+}
+
+impl NotificationDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    /// returns none if the user was already notified about the chapter
+    pub async fn add(
+        &self,
+        user: &str,
+        manga: &str,
+        chapter: &str,
+    ) -> ApiResult<Option<RecordData<UserNotification>>> {
+        let user = Thing::from((User::name(), user));
+        let chapter = Thing::from((Chapter::name(), chapter));
+        let res: Vec<RecordData<UserNotification>> = UserNotification::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE user = {} AND chapter = {} LIMIT 1",
+                user, chapter
+            )),
+        )
+        .await?;
+        if !res.is_empty() {
+            return Ok(None);
+        }
+        let id = UserNotification {
+            user: ThingType::from(user),
+            manga: ThingType::from(Thing::from((Manga::name(), manga))),
+            chapter: ThingType::from(chapter),
+            read: false,
+            created: Default::default(),
+        }
+        .add_i(&*self.conn)
+        .await?
+        .id;
+        Ok(id.get(&*self.conn).await?)
+    }
+
+    /// newest first
+    pub async fn list(
+        &self,
+        user: &str,
+        unread_only: bool,
+        limit: u32,
+        page: u32,
+    ) -> ApiResult<Vec<RecordData<UserNotification>>> {
+        Ok(UserNotification::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE user = {}{} ORDER BY created DESC LIMIT {} START {}",
+                Thing::from((User::name(), user)),
+                match unread_only {
+                    true => " AND read = false",
+                    false => "",
+                },
+                limit,
+                (page.max(1) - 1) * limit
+            )),
+        )
+        .await?)
+    }
+
+    pub async fn unread(&self, user: &str) -> ApiResult<u32> {
+        let count: Option<u32> = self
+            .conn
+            .query(format!(
+                "SELECT count() FROM {} WHERE user = $user AND read = false GROUP ALL",
+                UserNotification::name()
+            ))
+            .bind(("user", Thing::from((User::name(), user))))
+            .await?
+            .take((0, "count"))?;
+        Ok(count.unwrap_or_default())
+    }
+
+    /// marks all notifications of the user as read if `ids` is none
+    pub async fn mark_read(&self, user: &str, ids: Option<Vec<String>>) -> ApiResult<()> {
+        let mut query = format!(
+            "UPDATE {} SET read = true WHERE user = $user AND read = false",
+            UserNotification::name()
+        );
+        let ids = ids.map(|ids| {
+            ids.iter()
+                .map(|v| Thing::from((UserNotification::name(), v.as_str())))
+                .collect::<Vec<_>>()
+        });
+        if ids.is_some() {
+            query.push_str(" AND id IN $ids");
+        }
+        self.conn
+            .query(query)
+            .bind(("user", Thing::from((User::name(), user))))
+            .bind(("ids", ids))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    async fn get_settings_record(
+        &self,
+        user: &str,
+    ) -> ApiResult<Option<RecordData<UserNotificationSettings>>> {
+        let mut res: Vec<RecordData<UserNotificationSettings>> = UserNotificationSettings::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE user = {} LIMIT 1",
+                Thing::from((User::name(), user))
+            )),
+        )
+        .await?;
+        Ok(res.pop())
+    }
+
+    pub async fn get_settings(&self, user: &str) -> ApiResult<NotificationSettings> {
+        Ok(self
+            .get_settings_record(user)
+            .await?
+            .map(|v| v.data.to_public())
+            .unwrap_or_default())
+    }
+
+    pub async fn set_settings(&self, user: &str, settings: NotificationSettings) -> ApiResult<()> {
+        let data = UserNotificationSettings {
+            user: ThingType::from(Thing::from((User::name(), user))),
+            muted: settings.muted,
+            favorites: settings.favorites,
+            reading: settings.reading,
+            muted_mangas: settings
+                .muted_mangas
+                .iter()
+                .map(|v| ThingType::from(Thing::from((Manga::name(), v.as_str()))))
+                .collect(),
+        };
+        match self.get_settings_record(user).await? {
+            Some(v) => {
+                let _: Option<Record> = v.id.update(&*self.conn, data).await?;
+            }
+            None => {
+                data.add_i(&*self.conn).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn mute(&self, user: &str, manga: &str, muted: bool) -> ApiResult<()> {
+        let mut settings = self.get_settings(user).await?;
+        settings.muted_mangas.retain(|v| v != manga);
+        if muted {
+            settings.muted_mangas.push(manga.to_string());
+        }
+        self.set_settings(user, settings).await
+    }
+
+    /// ids of the users who want to be notified about new chapters of the manga
+    pub async fn subscribers(&self, manga: &str) -> ApiResult<HashSet<String>> {
+        let manga = Thing::from((Manga::name(), manga));
+        let mut res = self
+            .conn
+            .query(format!(
+                "SELECT VALUE user FROM {} WHERE name = \"Favorites\" AND mangas CONTAINS $manga",
+                MangaList::name()
+            ))
+            .query(format!(
+                "SELECT VALUE user FROM {} WHERE manga = $manga",
+                UserProgress::name()
+            ))
+            .bind(("manga", manga.clone()))
+            .await?;
+        let favorites: Vec<Thing> = res.take(0)?;
+        let reading: Vec<Thing> = res.take(1)?;
+        let favorites: HashSet<_> = favorites.into_iter().map(|v| v.id.to_raw()).collect();
+        let reading: HashSet<_> = reading.into_iter().map(|v| v.id.to_raw()).collect();
+        let manga = manga.id.to_raw();
+        let mut users = HashSet::new();
+        for user in favorites.union(&reading) {
+            let settings = self.get_settings(user).await?;
+            let wanted = (settings.favorites && favorites.contains(user))
+                || (settings.reading && reading.contains(user));
+            if wanted && !settings.muted && !settings.muted_mangas.contains(&manga) {
+                users.insert(user.clone());
+            }
+        }
+        Ok(users)
+    }
+
+    /// none before the first start of the notification service
+    pub async fn cursor(&self) -> ApiResult<Option<Datetime>> {
+        Ok(self
+            .conn
+            .query("SELECT VALUE created FROM $id")
+            .bind(("id", cursor()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn set_cursor(&self, created: &Datetime) -> ApiResult<()> {
+        self.conn
+            .query("UPDATE $id SET created = $created")
+            .bind(("id", cursor()))
+            .bind(("created", created.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
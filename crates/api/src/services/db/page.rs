@@ -1,12 +1,15 @@
 use crate::errors::{ApiError, ApiResult};
+use crate::services::db::chapter::Chapter;
+use api_structure::reader::CropRect;
 use image::DynamicImage;
 use img_hash::Hasher;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use surrealdb::engine::local::Db;
-use surrealdb::sql::Datetime;
+use surrealdb::opt::PatchOp;
+use surrealdb::sql::{Datetime, Thing};
 use surrealdb::Surreal;
-use surrealdb_extras::{RecordData, SurrealTable, ThingType};
+use surrealdb_extras::{Record, RecordData, SurrealTable, SurrealTableInfo, ThingFunc, ThingType};
 
 #[derive(SurrealTable, Serialize, Deserialize, Debug)]
 #[db("manga_pages")]
@@ -18,6 +21,15 @@ pub struct Page {
     pub ext: String,
     pub hash: String,
     pub translation: bool,
+    /// uniform padding in pixels, detected when the page is stored
+    #[serde(default)]
+    pub margin_top: u32,
+    #[serde(default)]
+    pub margin_bottom: u32,
+    #[serde(default)]
+    pub margin_left: u32,
+    #[serde(default)]
+    pub margin_right: u32,
     #[opt(exclude = true)]
     pub updated: Datetime,
     #[opt(exclude = true)]
@@ -27,6 +39,7 @@ pub struct Page {
 impl Page {
     pub fn new(img: DynamicImage, ext: &str, page: u32, hasher: &Hasher) -> Self {
         let hash = hasher.hash_image(&img).to_base64();
+        let margins = Margins::detect(&img);
         Self {
             page,
             width: img.width(),
@@ -34,10 +47,110 @@ impl Page {
             ext: ext.to_string(),
             translation: false,
             hash,
+            margin_top: margins.top,
+            margin_bottom: margins.bottom,
+            margin_left: margins.left,
+            margin_right: margins.right,
             updated: Default::default(),
             created: Default::default(),
         }
     }
+
+    /// area of the page without its padding
+    pub fn crop(&self) -> CropRect {
+        let min_x = self.margin_left.min(self.width);
+        let min_y = self.margin_top.min(self.height);
+        CropRect {
+            min_x,
+            min_y,
+            max_x: self.width.saturating_sub(self.margin_right).max(min_x),
+            max_y: self.height.saturating_sub(self.margin_bottom).max(min_y),
+        }
+    }
+}
+
+/// max difference to pure white/black that still counts as padding(jpeg noise)
+const MARGIN_TOLERANCE: u8 = 24;
+/// share of pixels in a line that have to match the padding color
+const MARGIN_MIN_SHARE: f32 = 0.995;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Margins {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+impl Margins {
+    /// detects uniform white or black borders around an image
+    pub fn detect(img: &DynamicImage) -> Self {
+        let img = img.to_luma8();
+        let (width, height) = img.dimensions();
+        if width == 0 || height == 0 {
+            return Self::default();
+        }
+        let row = |y: u32| {
+            (0..width)
+                .map(|x| img.get_pixel(x, y)[0])
+                .collect::<Vec<_>>()
+        };
+        let column = |x: u32| {
+            (0..height)
+                .map(|y| img.get_pixel(x, y)[0])
+                .collect::<Vec<_>>()
+        };
+
+        let mut top = count_border(height, row);
+        let mut bottom = count_border(height, |i| row(height - 1 - i));
+        if top + bottom >= height {
+            // blank page
+            top = 0;
+            bottom = 0;
+        }
+        let mut left = count_border(width, column);
+        let mut right = count_border(width, |i| column(width - 1 - i));
+        if left + right >= width {
+            left = 0;
+            right = 0;
+        }
+        Self {
+            top,
+            bottom,
+            left,
+            right,
+        }
+    }
+}
+
+/// counts lines from the outside in which have the same padding color as the first line
+fn count_border(len: u32, line: impl Fn(u32) -> Vec<u8>) -> u32 {
+    let color = match border_color(&line(0)) {
+        Some(v) => v,
+        None => return 0,
+    };
+    let mut count = 1;
+    while count < len && border_color(&line(count)) == Some(color) {
+        count += 1;
+    }
+    count
+}
+
+/// true for white, false for black
+fn border_color(line: &[u8]) -> Option<bool> {
+    let min = (line.len() as f32 * MARGIN_MIN_SHARE).ceil() as usize;
+    let white = line
+        .iter()
+        .filter(|v| **v >= u8::MAX - MARGIN_TOLERANCE)
+        .count();
+    if white >= min {
+        return Some(true);
+    }
+    let black = line.iter().filter(|v| **v <= MARGIN_TOLERANCE).count();
+    if black >= min {
+        return Some(false);
+    }
+    None
 }
 
 pub struct PageDBService {
@@ -56,4 +169,125 @@ impl PageDBService {
             .ok_or(ApiError::db_error())?;
         Ok(v.data)
     }
+
+    pub async fn exists(&self, page_id: &str) -> ApiResult<bool> {
+        let res: Option<Thing> = self
+            .conn
+            .query("SELECT VALUE id FROM $page")
+            .bind(("page", Thing::from((Page::name(), page_id))))
+            .await?
+            .take(0)?;
+        Ok(res.is_some())
+    }
+
+    pub async fn set_translation(&self, page_id: &str, translation: bool) -> ApiResult<()> {
+        let v: ThingFunc = ThingFunc::new(Thing::from((Page::name(), page_id)));
+        let _: Option<Record> = v
+            .patch(&*self.conn, PatchOp::replace("translation", translation))
+            .await?;
+        Ok(())
+    }
+
+    /// hash of a page in one query. `version_id` is without table prefix.
+    /// None if the chapter, version or page doesn't exist
+    pub async fn hash(
+        &self,
+        chapter_id: &str,
+        version_id: &str,
+        page: u32,
+    ) -> ApiResult<Option<String>> {
+        Ok(self
+            .conn
+            .query(
+                "LET $pages = array::flatten((SELECT VALUE versions[$version].pages FROM $chapter)); \
+                 SELECT VALUE hash FROM $pages WHERE page = $page LIMIT 1",
+            )
+            .bind(("chapter", Thing::from((Chapter::name(), chapter_id))))
+            .bind(("version", format!("chapter_versions:{}", version_id)))
+            .bind(("page", page))
+            .await?
+            .take(1)?)
+    }
+
+    /// finds the page with the number `page` in a chapter version
+    pub async fn find(
+        &self,
+        pages: Vec<ThingType<Page>>,
+        page: u32,
+    ) -> ApiResult<RecordData<Page>> {
+        if pages.is_empty() {
+            return Err(ApiError::db_error());
+        }
+        let ids = pages
+            .iter()
+            .map(|v| v.thing.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut res: Vec<RecordData<Page>> = Page::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE id INSIDE [{}] AND page = {} LIMIT 1",
+                ids, page
+            )),
+        )
+        .await?;
+        if res.is_empty() {
+            return Err(ApiError::db_error());
+        }
+        Ok(res.remove(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma};
+
+    /// white page with gray content between the margins
+    fn page(width: u32, height: u32, margins: Margins) -> DynamicImage {
+        let img = GrayImage::from_fn(width, height, |x, y| {
+            let content = x >= margins.left
+                && x < width - margins.right
+                && y >= margins.top
+                && y < height - margins.bottom;
+            Luma([if content { 128 } else { 255 }])
+        });
+        DynamicImage::ImageLuma8(img)
+    }
+
+    #[test]
+    fn blank_page() {
+        let white = DynamicImage::ImageLuma8(GrayImage::from_pixel(40, 60, Luma([255])));
+        assert_eq!(Margins::detect(&white), Margins::default());
+        let black = DynamicImage::ImageLuma8(GrayImage::from_pixel(40, 60, Luma([0])));
+        assert_eq!(Margins::detect(&black), Margins::default());
+    }
+
+    #[test]
+    fn full_bleed() {
+        let img = page(40, 60, Margins::default());
+        assert_eq!(Margins::detect(&img), Margins::default());
+    }
+
+    #[test]
+    fn uneven_margins() {
+        let margins = Margins {
+            top: 5,
+            bottom: 20,
+            left: 3,
+            right: 12,
+        };
+        assert_eq!(Margins::detect(&page(100, 80, margins)), margins);
+    }
+
+    #[test]
+    fn jpeg_noise_is_padding() {
+        let img = GrayImage::from_fn(50, 50, |x, y| match y < 10 {
+            true => Luma([255 - ((x + y) % 20) as u8]),
+            false => Luma([128]),
+        });
+        let margins = Margins::detect(&DynamicImage::ImageLuma8(img));
+        assert_eq!(margins.top, 10);
+        assert_eq!(margins.bottom, 0);
+    }
 }
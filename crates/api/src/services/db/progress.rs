@@ -1,10 +1,13 @@
+use crate::errors::ApiResult;
 use crate::services::db::chapter::Chapter;
 use crate::services::db::manga::Manga;
 use crate::services::db::user::User;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use surrealdb::engine::local::Db;
-use surrealdb::sql::Datetime;
+use surrealdb::sql::{Datetime, Thing};
 use surrealdb::Surreal;
 use surrealdb_extras::{
     RecordData, SurrealSelect, SurrealTable, SurrealTableInfo, ThingFunc, ThingType,
@@ -12,13 +15,14 @@ use surrealdb_extras::{
 
 #[derive(SurrealTable, Serialize, Deserialize, Debug)]
 #[db("user_progress")]
-#[sql(["DEFINE EVENT user_progress_updated ON TABLE user_progress WHEN $event = \"UPDATE\" AND $before.updated == $after.updated THEN (UPDATE $after.id SET updated = time::now() );"])]
+#[sql(["DEFINE EVENT user_progress_updated ON TABLE user_progress WHEN $event = \"UPDATE\" AND $before.updated == $after.updated AND $before.hidden == false AND $after.hidden == false THEN (UPDATE $after.id SET updated = time::now() );"])]
 pub struct UserProgress {
     user: ThingType<User>,
     manga: ThingType<Manga>,
     chapter: ThingType<Chapter>,
-    #[opt(exclude = true)]
     progress: f64,
+    /// imported progress which isn't part of the history
+    hidden: bool,
     #[opt(exclude = true)]
     updated: Datetime,
 }
@@ -29,6 +33,29 @@ pub struct Progress {
     progress: f64,
 }
 
+#[derive(SurrealSelect, Deserialize)]
+pub struct HistoryItem {
+    pub manga: ThingType<Manga>,
+    pub chapter: ThingType<Chapter>,
+    pub progress: f64,
+    pub updated: Datetime,
+}
+
+#[derive(Deserialize)]
+pub struct ReadManga {
+    pub user: Thing,
+    pub manga: Thing,
+}
+
+#[derive(Deserialize)]
+struct FinishedCount {
+    manga: Thing,
+    count: u32,
+}
+
+/// progress at which a chapter counts as read
+pub const FINISHED: f64 = 0.99;
+
 pub struct ProgressDBService {
     conn: Arc<Surreal<Db>>,
 }
@@ -39,7 +66,7 @@ impl ProgressDBService {
     }
 
     pub async fn get_progress(&self, user: &str, manga: ThingFunc) -> Option<(String, f64)> {
-        let mut res: Vec<RecordData<Progress>> = User::search(
+        let mut res: Vec<RecordData<Progress>> = UserProgress::search(
             &*self.conn,
             Some(format!(
                 "WHERE user = users:{} AND manga = {} ORDER BY updated DESC LIMIT 1",
@@ -55,4 +82,214 @@ impl ProgressDBService {
             Some((v.data.chapter.thing.id().to_string(), v.data.progress))
         }
     }
+
+    /// returns the progress before the change
+    pub async fn set_progress(
+        &self,
+        user: &str,
+        manga: &str,
+        chapter: &str,
+        progress: f64,
+    ) -> ApiResult<f64> {
+        let user = Thing::from((User::name(), user));
+        let chapter = Thing::from((Chapter::name(), chapter));
+        let mut res: Vec<RecordData<Progress>> = UserProgress::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE user = {} AND chapter = {} LIMIT 1",
+                user, chapter
+            )),
+        )
+        .await?;
+        match res.pop() {
+            Some(v) => {
+                // reading again brings a deleted history entry back
+                self.conn
+                    .query(format!(
+                        "UPDATE {} SET progress = $progress, hidden = false, updated = time::now() \
+                         WHERE user = $user AND chapter = $chapter",
+                        UserProgress::name()
+                    ))
+                    .bind(("progress", progress))
+                    .bind(("user", user))
+                    .bind(("chapter", chapter))
+                    .await?
+                    .check()?;
+                Ok(v.data.progress)
+            }
+            None => {
+                UserProgress {
+                    user: ThingType::from(user),
+                    manga: ThingType::from(Thing::from((Manga::name(), manga))),
+                    chapter: ThingType::from(chapter),
+                    progress,
+                    hidden: false,
+                    updated: Default::default(),
+                }
+                .add_i(&*self.conn)
+                .await?;
+                Ok(0.0)
+            }
+        }
+    }
+
+    /// marks the chapter as finished at `updated`, unless it has progress already.
+    /// imported progress isn't reading history, so it is hidden
+    pub async fn import(
+        &self,
+        user: &str,
+        manga: &str,
+        chapter: &str,
+        updated: Option<DateTime<Utc>>,
+    ) -> ApiResult<()> {
+        let user = Thing::from((User::name(), user));
+        let chapter = Thing::from((Chapter::name(), chapter));
+        let res: Vec<RecordData<Progress>> = UserProgress::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE user = {} AND chapter = {} LIMIT 1",
+                user, chapter
+            )),
+        )
+        .await?;
+        if !res.is_empty() {
+            return Ok(());
+        }
+        self.conn
+            .query(format!(
+                "CREATE {} SET user = $user, manga = $manga, chapter = $chapter, \
+                 progress = 1.0, hidden = true, updated = $updated",
+                UserProgress::name()
+            ))
+            .bind(("user", user))
+            .bind(("manga", Thing::from((Manga::name(), manga))))
+            .bind(("chapter", chapter))
+            // unknown dates are older than anything which was read here
+            .bind((
+                "updated",
+                Datetime::from(updated.unwrap_or(DateTime::UNIX_EPOCH)),
+            ))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// number of chapters of a manga the user finished
+    pub async fn finished(&self, user: &str, manga: &str) -> ApiResult<u32> {
+        let res: Vec<RecordData<Progress>> = UserProgress::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE user = {} AND manga = {} AND progress >= {}",
+                Thing::from((User::name(), user)),
+                Thing::from((Manga::name(), manga)),
+                FINISHED
+            )),
+        )
+        .await?;
+        Ok(res.len() as u32)
+    }
+
+    /// manga id => number of finished chapters
+    pub async fn finished_by_manga(&self, user: &str) -> ApiResult<HashMap<String, u32>> {
+        let res: Vec<FinishedCount> = self
+            .conn
+            .query(format!(
+                "SELECT manga, count() AS count FROM {} \
+                 WHERE user = $user AND progress >= $finished GROUP BY manga",
+                UserProgress::name()
+            ))
+            .bind(("user", Thing::from((User::name(), user))))
+            .bind(("finished", FINISHED))
+            .await?
+            .take(0)?;
+        Ok(res
+            .into_iter()
+            .map(|v| (v.manga.id.to_raw(), v.count))
+            .collect())
+    }
+
+    /// newest first
+    pub async fn history(
+        &self,
+        user: &str,
+        limit: u32,
+        page: u32,
+    ) -> ApiResult<Vec<RecordData<HistoryItem>>> {
+        Ok(UserProgress::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE user = {} AND hidden = false AND chapter.id != NONE \
+                 ORDER BY updated DESC LIMIT {} START {}",
+                Thing::from((User::name(), user)),
+                limit,
+                (page.max(1) - 1) * limit
+            )),
+        )
+        .await?)
+    }
+
+    /// every manga a user started. imported progress is skipped
+    pub async fn read_mangas(&self) -> ApiResult<Vec<ReadManga>> {
+        Ok(self
+            .conn
+            .query(format!(
+                "SELECT user, manga FROM {} WHERE hidden = false GROUP BY user, manga",
+                UserProgress::name()
+            ))
+            .await?
+            .take(0)?)
+    }
+
+    /// ids of the mangas the user started
+    pub async fn mangas_of(&self, user: &str) -> ApiResult<Vec<String>> {
+        let res: Vec<Thing> = self
+            .conn
+            .query(format!(
+                "SELECT VALUE manga FROM {} WHERE user = $user GROUP BY manga",
+                UserProgress::name()
+            ))
+            .bind(("user", Thing::from((User::name(), user))))
+            .await?
+            .take(0)?;
+        Ok(res.into_iter().map(|v| v.id.to_raw()).collect())
+    }
+
+    /// deletes the progress of a chapter. returns the manga if no chapter of it is left
+    pub async fn delete_entry(&self, user: &str, id: &str) -> ApiResult<Option<String>> {
+        let user = Thing::from((User::name(), user));
+        let deleted: Option<Thing> = self
+            .conn
+            .query("DELETE $entry WHERE user = $user RETURN BEFORE.manga")
+            .bind(("entry", Thing::from((UserProgress::name(), id))))
+            .bind(("user", &user))
+            .await?
+            .take((0, "manga"))?;
+        let Some(manga) = deleted else {
+            return Ok(None);
+        };
+        let left: Option<Thing> = self
+            .conn
+            .query(format!(
+                "SELECT VALUE id FROM {} WHERE user = $user AND manga = $manga LIMIT 1",
+                UserProgress::name()
+            ))
+            .bind(("user", &user))
+            .bind(("manga", &manga))
+            .await?
+            .take(0)?;
+        Ok(left.is_none().then(|| manga.id.to_raw()))
+    }
+
+    /// deletes the progress of every chapter
+    pub async fn clear(&self, user: &str) -> ApiResult<()> {
+        self.conn
+            .query(format!(
+                "DELETE {} WHERE user = $user",
+                UserProgress::name()
+            ))
+            .bind(("user", Thing::from((User::name(), user))))
+            .await?
+            .check()?;
+        Ok(())
+    }
 }
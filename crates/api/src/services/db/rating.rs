@@ -0,0 +1,163 @@
+use crate::errors::ApiResult;
+use crate::services::db::manga::Manga;
+use crate::services::db::user::User;
+use api_structure::rating::{RatingSummary, MAX_SCORE, MIN_SCORE};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{Record, RecordData, SurrealTable, SurrealTableInfo, ThingType};
+
+/// one rating per user and manga
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("manga_ratings")]
+#[sql([
+    "DEFINE EVENT manga_rating_updated ON TABLE manga_ratings WHEN $event = \"UPDATE\" AND $before.updated == $after.updated THEN (UPDATE $after.id SET updated = time::now() );",
+    "DEFINE INDEX manga_rating_unique ON TABLE manga_ratings COLUMNS manga, user UNIQUE"
+])]
+pub struct MangaRating {
+    pub manga: ThingType<Manga>,
+    pub user: ThingType<User>,
+    pub score: u32,
+    pub review: Option<String>,
+    #[opt(exclude = true)]
+    pub updated: Datetime,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+/// average score of the current manga. used inside of manga queries
+pub fn average_sql() -> String {
+    format!(
+        "math::mean((SELECT VALUE score FROM {} WHERE manga = $parent.id))",
+        MangaRating::name()
+    )
+}
+
+#[derive(Deserialize)]
+struct ScoreCount {
+    score: u32,
+    count: u32,
+}
+
+pub struct RatingDBService {
+    conn: Arc<Surreal<Db>>,
+}
+
+impl RatingDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    async fn get(&self, manga: &str, user: &str) -> ApiResult<Option<RecordData<MangaRating>>> {
+        let mut res: Vec<RecordData<MangaRating>> = MangaRating::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE manga = {} AND user = {} LIMIT 1",
+                Thing::from((Manga::name(), manga)),
+                Thing::from((User::name(), user))
+            )),
+        )
+        .await?;
+        Ok(res.pop())
+    }
+
+    /// creates or replaces the rating of the user
+    pub async fn set(
+        &self,
+        manga: &str,
+        user: &str,
+        score: u32,
+        review: Option<String>,
+    ) -> ApiResult<()> {
+        match self.get(manga, user).await? {
+            Some(mut v) => {
+                v.data.score = score;
+                v.data.review = review;
+                let _: Option<Record> = v.id.update(&*self.conn, v.data).await?;
+            }
+            None => {
+                MangaRating {
+                    manga: ThingType::from(Thing::from((Manga::name(), manga))),
+                    user: ThingType::from(Thing::from((User::name(), user))),
+                    score,
+                    review,
+                    updated: Default::default(),
+                    created: Default::default(),
+                }
+                .add_i(&*self.conn)
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn delete(&self, manga: &str, user: &str) -> ApiResult<()> {
+        self.conn
+            .query(format!(
+                "DELETE {} WHERE manga = $manga AND user = $user",
+                MangaRating::name()
+            ))
+            .bind(("manga", Thing::from((Manga::name(), manga))))
+            .bind(("user", Thing::from((User::name(), user))))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// `user` is used for [RatingSummary::own]
+    pub async fn summary(&self, manga: &str, user: &str) -> ApiResult<RatingSummary> {
+        let res: Vec<ScoreCount> = self
+            .conn
+            .query(format!(
+                "SELECT score, count() AS count FROM {} WHERE manga = $manga GROUP BY score",
+                MangaRating::name()
+            ))
+            .bind(("manga", Thing::from((Manga::name(), manga))))
+            .await?
+            .take(0)?;
+        let mut distribution = vec![0; (MAX_SCORE - MIN_SCORE + 1) as usize];
+        let mut sum = 0;
+        for v in res {
+            if let Some(entry) = distribution.get_mut(v.score.wrapping_sub(MIN_SCORE) as usize) {
+                *entry += v.count;
+                sum += v.score * v.count;
+            }
+        }
+        let count = distribution.iter().sum();
+        Ok(RatingSummary {
+            average: match count {
+                0 => None,
+                _ => Some(sum as f64 / count as f64),
+            },
+            count,
+            distribution,
+            own: self.get(manga, user).await?.map(|v| v.data.score),
+        })
+    }
+
+    /// newest first
+    pub async fn list(
+        &self,
+        manga: &str,
+        reviews_only: bool,
+        limit: u32,
+        page: u32,
+    ) -> ApiResult<Vec<RecordData<MangaRating>>> {
+        Ok(MangaRating::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE manga = {}{} ORDER BY updated DESC LIMIT {} START {}",
+                Thing::from((Manga::name(), manga)),
+                match reviews_only {
+                    true => " AND review != NONE",
+                    false => "",
+                },
+                limit,
+                (page.max(1) - 1) * limit
+            )),
+        )
+        .await?)
+    }
+}
@@ -0,0 +1,129 @@
+use crate::errors::ApiResult;
+use crate::services::db::user::User;
+use chrono::{DateTime, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::Thing;
+use surrealdb::Surreal;
+use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingType};
+
+/// reading totals of a user for a single day. updated whenever progress is saved.
+/// the id is `[user, day]`
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("user_daily_stats")]
+pub struct DailyStats {
+    pub user: ThingType<User>,
+    /// YYYY-MM-DD in utc
+    pub day: String,
+    pub pages: u32,
+    pub chapters: u32,
+    /// pages per hour of the day. 24 entries
+    pub hours: Vec<u32>,
+    /// manga id => pages
+    pub mangas: HashMap<String, u32>,
+}
+
+/// key of [DailyStats]
+pub fn day(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d").to_string()
+}
+
+/// id of [DailyStats]. `user` and `day` are surrealql expressions
+pub fn key_query(user: &str, day: &str) -> String {
+    format!(
+        "type::thing(\"{}\", [{}, {}])",
+        DailyStats::name(),
+        user,
+        day
+    )
+}
+
+pub struct ReadingStatsDBService {
+    conn: Arc<Surreal<Db>>,
+}
+
+impl ReadingStatsDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    /// adds pages and finished chapters to today. a single statement, so saves of other
+    /// devices at the same time aren't lost
+    pub async fn record(
+        &self,
+        user: &str,
+        manga: &str,
+        pages: u32,
+        chapters: u32,
+    ) -> ApiResult<()> {
+        if pages == 0 && chapters == 0 {
+            return Ok(());
+        }
+        let now = Utc::now();
+        self.conn
+            .query(format!(
+                "UPDATE {} SET user = $user, day = $day, pages += $pages, chapters += $chapters, \
+                 hours = IF hours = NONE THEN $no_hours ELSE hours END, hours[$hour] += $pages, \
+                 mangas = IF mangas = NONE THEN {{}} ELSE mangas END, mangas[$manga] += $pages",
+                key_query("$user", "$day")
+            ))
+            .bind(("user", Thing::from((User::name(), user))))
+            .bind(("day", day(now)))
+            .bind(("pages", pages))
+            .bind(("chapters", chapters))
+            .bind(("no_hours", vec![0u32; 24]))
+            .bind(("hour", now.hour()))
+            .bind(("manga", manga))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// days are inclusive. oldest first
+    pub async fn range(
+        &self,
+        user: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> ApiResult<Vec<DailyStats>> {
+        let mut filter = format!("WHERE user = {}", Thing::from((User::name(), user)));
+        if let Some(from) = from {
+            filter.push_str(&format!(" AND day >= {}", serde_json::to_string(from)?));
+        }
+        if let Some(to) = to {
+            filter.push_str(&format!(" AND day <= {}", serde_json::to_string(to)?));
+        }
+        let res: Vec<RecordData<DailyStats>> =
+            DailyStats::search(&*self.conn, Some(format!("{} ORDER BY day ASC", filter))).await?;
+        Ok(res.into_iter().map(|v| v.data).collect())
+    }
+
+    /// removes which mangas were read. the totals are kept
+    pub async fn forget_mangas(&self, user: &str) -> ApiResult<()> {
+        self.conn
+            .query(format!(
+                "UPDATE {} SET mangas = {{}} WHERE user = $user",
+                DailyStats::name()
+            ))
+            .bind(("user", Thing::from((User::name(), user))))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// removes a single manga from every day. the totals are kept
+    pub async fn forget_manga(&self, user: &str, manga: &str) -> ApiResult<()> {
+        self.conn
+            .query(format!(
+                "UPDATE {} SET mangas[$manga] = NONE WHERE user = $user",
+                DailyStats::name()
+            ))
+            .bind(("user", Thing::from((User::name(), user))))
+            .bind(("manga", manga))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
@@ -4,7 +4,9 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use surrealdb::engine::local::Db;
 use surrealdb::Surreal;
-use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingArray};
+use surrealdb_extras::{
+    RecordData, SurrealSelect, SurrealTable, SurrealTableInfo, ThingArray, ThingType,
+};
 
 #[derive(SurrealTable, Serialize, Deserialize, Debug, Clone)]
 #[db("tags")]
@@ -25,6 +27,9 @@ impl Tag {
     }
 }
 
+#[derive(SurrealSelect, Deserialize)]
+struct Empty {}
+
 pub struct TagDBService {
     pub conn: Arc<Surreal<Db>>,
     temp: Arc<Mutex<HashMap<String, Tag>>>,
@@ -48,6 +53,33 @@ impl TagDBService {
         v
     }
 
+    /// id of the tag. creates it if it doesnt exist yet
+    pub async fn get_or_create(&self, tag: api_structure::info::Tag) -> ApiResult<ThingType<Tag>> {
+        let mut found: Vec<RecordData<Empty>> = Tag::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE tag = {} AND sex = {} LIMIT 1",
+                serde_json::to_string(&tag.tag)?,
+                tag.sex
+            )),
+        )
+        .await?;
+        let id = match found.pop() {
+            Some(v) => v.id,
+            None => {
+                Tag {
+                    tag: tag.tag,
+                    description: tag.description,
+                    sex: tag.sex,
+                }
+                .add_i(&*self.conn)
+                .await?
+                .id
+            }
+        };
+        Ok(ThingType::from(id.0))
+    }
+
     pub fn new(conn: Arc<Surreal<Db>>) -> Self {
         Self {
             conn,
@@ -0,0 +1,123 @@
+use crate::errors::{ApiError, ApiResult};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::Datetime;
+use surrealdb::Surreal;
+use surrealdb_extras::{SurrealTable, SurrealTableInfo};
+use tokio::sync::Mutex;
+
+/// file in `root_folder/temp`. removed when the file is claimed or expired
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("temp_uploads")]
+#[sql(["DEFINE INDEX temp_upload_name ON TABLE temp_uploads COLUMNS name UNIQUE"])]
+pub struct TempUpload {
+    /// file name inside of the temp folder
+    pub name: String,
+    /// user id or `ip:<address>` for uploads without a session
+    pub owner: String,
+    pub size: u64,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+pub struct TempUploadDBService {
+    conn: Arc<Surreal<Db>>,
+    /// held while the quota is checked, so parallel uploads can't exceed it together
+    quota: Mutex<()>,
+}
+
+impl TempUploadDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self {
+            conn,
+            quota: Mutex::new(()),
+        }
+    }
+
+    /// adds the upload if the owner stays within `quota` bytes. returns false otherwise
+    pub async fn add_within(
+        &self,
+        name: String,
+        owner: String,
+        size: u64,
+        quota: u64,
+    ) -> ApiResult<bool> {
+        let _guard = self.quota.lock().await;
+        if self.usage(&owner).await? + size > quota {
+            return Ok(false);
+        }
+        self.add(name, owner, size).await?;
+        Ok(true)
+    }
+
+    async fn add(&self, name: String, owner: String, size: u64) -> ApiResult<()> {
+        TempUpload {
+            name,
+            owner,
+            size,
+            created: Default::default(),
+        }
+        .add_i(&*self.conn)
+        .await?;
+        Ok(())
+    }
+
+    /// bytes of unclaimed uploads
+    pub async fn usage(&self, owner: &str) -> ApiResult<u64> {
+        let usage: Option<u64> = self
+            .conn
+            .query(format!(
+                "RETURN math::sum((SELECT VALUE size FROM {} WHERE owner = $owner))",
+                TempUpload::name()
+            ))
+            .bind(("owner", owner.to_string()))
+            .await?
+            .take(0)?;
+        Ok(usage.unwrap_or_default())
+    }
+
+    /// has to be called before the file is moved out of the temp folder.
+    /// fails if the file isn't an unclaimed upload of `owner`
+    pub async fn claim(&self, name: &str, owner: &str) -> ApiResult<()> {
+        let claimed: Vec<TempUpload> = self
+            .conn
+            .query(format!(
+                "DELETE {} WHERE name = $name AND owner = $owner RETURN BEFORE",
+                TempUpload::name()
+            ))
+            .bind(("name", name.to_string()))
+            .bind(("owner", owner.to_string()))
+            .await?
+            .take(0)?;
+        match claimed.is_empty() {
+            true => Err(ApiError::invalid_input("File does not exist")),
+            false => Ok(()),
+        }
+    }
+
+    /// removes uploads which are older than `ttl` seconds. returns their file names
+    pub async fn remove_expired(&self, ttl: u64) -> ApiResult<Vec<String>> {
+        Ok(self
+            .conn
+            .query(format!(
+                "DELETE {} WHERE created < time::now() - {}s RETURN BEFORE",
+                TempUpload::name(),
+                ttl
+            ))
+            .await?
+            .take::<Vec<TempUpload>>(0)?
+            .into_iter()
+            .map(|v| v.name)
+            .collect())
+    }
+
+    /// every tracked file name
+    pub async fn names(&self) -> ApiResult<Vec<String>> {
+        Ok(self
+            .conn
+            .query(format!("SELECT VALUE name FROM {}", TempUpload::name()))
+            .await?
+            .take(0)?)
+    }
+}
@@ -0,0 +1,330 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::page::Page;
+use crate::services::db::user::User;
+use api_structure::translation::{TranslationAction, TranslationAreaData};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::opt::PatchOp;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{
+    Record, RecordData, SurrealSelect, SurrealTable, SurrealTableInfo, ThingFunc, ThingType,
+};
+
+/// source name of the translations that were dropped into the chapter folders as json
+pub const LEGACY_SOURCE: &str = "ichigo";
+/// language of the translations that were dropped into the chapter folders as json
+pub const LEGACY_LANGUAGE: &str = "eng";
+
+#[derive(SurrealTable, Serialize, Deserialize, Debug, Clone)]
+#[db("page_translations")]
+#[sql(["DEFINE EVENT page_translation_updated ON TABLE page_translations WHEN $event = \"UPDATE\" AND $before.updated == $after.updated THEN (UPDATE $after.id SET updated = time::now() );"])]
+pub struct PageTranslation {
+    pub page: ThingType<Page>,
+    /// where the text came from. e.g. an ocr backend or manual
+    pub source: String,
+    /// language => text
+    pub translated_text: HashMap<String, String>,
+    pub min_x: u32,
+    pub min_y: u32,
+    pub max_x: u32,
+    pub max_y: u32,
+    /// 0xRRGGBB
+    pub text_color: u32,
+    /// 0xRRGGBB
+    pub outline_color: u32,
+    /// base64 image which covers the original text
+    pub background: String,
+    #[opt(exclude = true)]
+    pub updated: Datetime,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+impl PageTranslation {
+    pub fn from_legacy(page_id: &str, value: Translation) -> Self {
+        Self::from_tool(page_id, LEGACY_SOURCE, LEGACY_LANGUAGE, value)
+    }
+
+    /// area in the format of the external translation tool
+    pub fn from_tool(page_id: &str, source: &str, language: &str, value: Translation) -> Self {
+        let mut translated_text = HashMap::new();
+        translated_text.insert(language.to_string(), value.translated_text);
+        Self {
+            page: page_thing(page_id),
+            source: source.to_string(),
+            translated_text,
+            min_x: value.min_x,
+            min_y: value.min_y,
+            max_x: value.max_x,
+            max_y: value.max_y,
+            text_color: rgb_to_u32([0; 3]),
+            outline_color: rgb_to_u32([255; 3]),
+            background: value.background,
+            updated: Default::default(),
+            created: Default::default(),
+        }
+    }
+
+    pub fn from_public(page_id: &str, source: String, area: TranslationAreaData) -> Self {
+        let content = TranslationContent::from(area);
+        Self {
+            page: page_thing(page_id),
+            source,
+            translated_text: content.translated_text,
+            min_x: content.min_x,
+            min_y: content.min_y,
+            max_x: content.max_x,
+            max_y: content.max_y,
+            text_color: content.text_color,
+            outline_color: content.outline_color,
+            background: content.background,
+            updated: Default::default(),
+            created: Default::default(),
+        }
+    }
+
+    pub fn to_editor(&self) -> TranslationAreaData {
+        TranslationAreaData {
+            translated_text: self.translated_text.clone(),
+            min_x: self.min_x,
+            min_y: self.min_y,
+            max_x: self.max_x,
+            max_y: self.max_y,
+            text_color: u32_to_rgb(self.text_color),
+            outline_color: u32_to_rgb(self.outline_color),
+            background: self.background.clone(),
+        }
+    }
+
+    /// drops every text that is not in `language`. returns false if nothing is left
+    pub fn retain_language(&mut self, language: &str) -> bool {
+        self.translated_text.retain(|lang, _| lang == language);
+        !self.translated_text.is_empty()
+    }
+
+    pub fn to_public(self) -> api_structure::reader::TranslationArea {
+        api_structure::reader::TranslationArea {
+            translated_text: self.translated_text,
+            min_x: self.min_x,
+            min_y: self.min_y,
+            max_x: self.max_x,
+            max_y: self.max_y,
+            text_color: u32_to_rgb(self.text_color),
+            outline_color: u32_to_rgb(self.outline_color),
+            background: self.background,
+            source: self.source,
+        }
+    }
+}
+
+/// part of a [PageTranslation] which can be changed in the editor
+#[derive(SurrealSelect, Serialize, Deserialize)]
+pub struct TranslationContent {
+    pub translated_text: HashMap<String, String>,
+    pub min_x: u32,
+    pub min_y: u32,
+    pub max_x: u32,
+    pub max_y: u32,
+    pub text_color: u32,
+    pub outline_color: u32,
+    pub background: String,
+}
+
+impl From<TranslationAreaData> for TranslationContent {
+    fn from(value: TranslationAreaData) -> Self {
+        Self {
+            translated_text: value.translated_text,
+            min_x: value.min_x,
+            min_y: value.min_y,
+            max_x: value.max_x,
+            max_y: value.max_y,
+            text_color: rgb_to_u32(value.text_color),
+            outline_color: rgb_to_u32(value.outline_color),
+            background: value.background,
+        }
+    }
+}
+
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("translation_revisions")]
+pub struct TranslationRevision {
+    pub page: ThingType<Page>,
+    pub area: ThingType<PageTranslation>,
+    pub editor: ThingType<User>,
+    /// 0 = add, 1 = edit, 2 = delete
+    pub action: u32,
+    /// json of [TranslationAreaData]
+    pub before: Option<String>,
+    /// json of [TranslationAreaData]
+    pub after: Option<String>,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+impl TranslationRevision {
+    pub fn action(&self) -> TranslationAction {
+        match self.action {
+            0 => TranslationAction::Add,
+            1 => TranslationAction::Edit,
+            _ => TranslationAction::Delete,
+        }
+    }
+}
+
+pub fn rgb_to_u32(rgb: [u8; 3]) -> u32 {
+    u32::from_be_bytes([0, rgb[0], rgb[1], rgb[2]])
+}
+
+pub fn u32_to_rgb(v: u32) -> [u8; 3] {
+    let [_, r, g, b] = v.to_be_bytes();
+    [r, g, b]
+}
+
+fn page_thing(page_id: &str) -> ThingType<Page> {
+    ThingType::from(Thing::from((Page::name(), page_id)))
+}
+
+pub struct TranslationDBService {
+    pub conn: Arc<Surreal<Db>>,
+}
+
+impl TranslationDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    /// every area of a page. filtered to a single language if `language` is set
+    pub async fn get(
+        &self,
+        page_id: &str,
+        language: Option<&str>,
+    ) -> ApiResult<Vec<RecordData<PageTranslation>>> {
+        let mut areas: Vec<RecordData<PageTranslation>> = self
+            .conn
+            .query(format!(
+                "SELECT * FROM {} WHERE page = $page ORDER BY created ASC",
+                PageTranslation::name()
+            ))
+            .bind(("page", Thing::from((Page::name(), page_id))))
+            .await?
+            .take(0)?;
+        if let Some(language) = language {
+            areas.retain_mut(|v| v.data.retain_language(language));
+        }
+        Ok(areas)
+    }
+
+    pub async fn add(&self, area: PageTranslation) -> ApiResult<Record> {
+        Ok(area.add_i(&*self.conn).await?)
+    }
+
+    pub async fn get_area(&self, area_id: &str) -> ApiResult<RecordData<PageTranslation>> {
+        let thing = ThingFunc::from((PageTranslation::name(), area_id));
+        thing
+            .get(&*self.conn)
+            .await?
+            .ok_or(ApiError::invalid_input("Translation area does not exist"))
+    }
+
+    pub async fn update(&self, area_id: &str, content: TranslationContent) -> ApiResult<()> {
+        let thing = ThingFunc::from((PageTranslation::name(), area_id));
+        let _: Option<Record> = thing.update(&*self.conn, content).await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, area: RecordData<PageTranslation>) -> ApiResult<()> {
+        area.delete_s(&*self.conn).await?;
+        Ok(())
+    }
+
+    pub async fn add_revision(
+        &self,
+        area: &PageTranslation,
+        area_id: &str,
+        editor: &str,
+        action: TranslationAction,
+        before: Option<TranslationAreaData>,
+        after: Option<TranslationAreaData>,
+    ) -> ApiResult<()> {
+        let revision = TranslationRevision {
+            page: ThingType::from(area.page.thing.0.clone()),
+            area: ThingType::from(Thing::from((PageTranslation::name(), area_id))),
+            editor: ThingType::from(Thing::from((User::name(), editor))),
+            action: action as u32,
+            before: before.map(|v| serde_json::to_string(&v)).transpose()?,
+            after: after.map(|v| serde_json::to_string(&v)).transpose()?,
+            created: Default::default(),
+        };
+        revision.add_i(&*self.conn).await?;
+        Ok(())
+    }
+
+    /// newest first
+    pub async fn history(&self, page_id: &str) -> ApiResult<Vec<RecordData<TranslationRevision>>> {
+        Ok(self
+            .conn
+            .query(format!(
+                "SELECT * FROM {} WHERE page = $page ORDER BY created DESC",
+                TranslationRevision::name()
+            ))
+            .bind(("page", Thing::from((Page::name(), page_id))))
+            .await?
+            .take(0)?)
+    }
+
+    /// removes the `language` text of every area of a page which came from `source`.
+    /// areas without text in another language are deleted
+    pub async fn delete_source(
+        &self,
+        page_id: &str,
+        source: &str,
+        language: &str,
+    ) -> ApiResult<()> {
+        for mut area in self.get(page_id, None).await? {
+            if area.data.source != source || area.data.translated_text.remove(language).is_none() {
+                continue;
+            }
+            if area.data.translated_text.is_empty() {
+                self.delete(area).await?;
+            } else {
+                let _: Option<Record> = ThingFunc::new(area.id)
+                    .patch(
+                        &*self.conn,
+                        PatchOp::replace("translated_text", area.data.translated_text),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// single area in the format of the external translation tool
+#[derive(Serialize, Deserialize)]
+pub struct Translation {
+    #[serde(rename = "translatedText")]
+    pub translated_text: String,
+    #[serde(rename = "minX")]
+    pub min_x: u32,
+    #[serde(rename = "minY")]
+    pub min_y: u32,
+    #[serde(rename = "maxX")]
+    pub max_x: u32,
+    #[serde(rename = "maxY")]
+    pub max_y: u32,
+    pub background: String,
+}
+
+/// response of the external translation tool
+#[derive(Serialize, Deserialize)]
+pub struct TranslationResponse {
+    pub images: Vec<Vec<Translation>>,
+}
+
+pub fn is_valid_translation(s: &str) -> bool {
+    let v: Result<TranslationResponse, _> = serde_json::from_str(s);
+    v.is_ok()
+}
@@ -0,0 +1,173 @@
+use crate::errors::ApiResult;
+use crate::services::db::chapter::Chapter;
+use crate::services::db::chapter_version::ChapterVersion;
+use crate::services::db::user::User;
+use api_structure::translation::TranslationJobStatus;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::{Datetime, Thing};
+use surrealdb::Surreal;
+use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingType};
+
+#[derive(SurrealTable, Serialize, Deserialize, Debug)]
+#[db("translation_jobs")]
+#[sql(["DEFINE EVENT translation_job_updated ON TABLE translation_jobs WHEN $event = \"UPDATE\" AND $before.updated == $after.updated THEN (UPDATE $after.id SET updated = time::now() );"])]
+pub struct TranslationJob {
+    pub manga_id: String,
+    pub chapter: ThingType<Chapter>,
+    /// key of the version in [Chapter::versions]. e.g. chapter_versions:abc
+    pub version_id: String,
+    pub connection: ThingType<ChapterVersion>,
+    pub requested_by: ThingType<User>,
+    pub language: String,
+    /// [TranslationJobStatus]
+    pub status: u32,
+    pub pages_done: u32,
+    pub pages_total: u32,
+    pub error: Option<String>,
+    #[opt(exclude = true)]
+    pub updated: Datetime,
+    #[opt(exclude = true)]
+    pub created: Datetime,
+}
+
+impl TranslationJob {
+    pub fn to_public(self, job_id: String) -> api_structure::translation::TranslationJob {
+        api_structure::translation::TranslationJob {
+            job_id,
+            version_id: self.version_id,
+            language: self.language,
+            status: self.status.into(),
+            pages_done: self.pages_done,
+            pages_total: self.pages_total,
+            error: self.error,
+            created: self.created.to_string(),
+            updated: self.updated.to_string(),
+        }
+    }
+}
+
+pub struct TranslationJobDBService {
+    conn: Arc<Surreal<Db>>,
+}
+
+impl TranslationJobDBService {
+    pub fn new(conn: Arc<Surreal<Db>>) -> Self {
+        Self { conn }
+    }
+
+    /// queues a job unless the version already waits for or is being translated into
+    /// `language`. both happen in one transaction. none if a job is pending
+    pub async fn add(
+        &self,
+        manga_id: &str,
+        chapter_id: &str,
+        version_id: &str,
+        connection: &str,
+        user_id: &str,
+        language: String,
+    ) -> ApiResult<Option<String>> {
+        let created: Vec<Thing> = self
+            .conn
+            .query(format!(
+                "BEGIN TRANSACTION; \
+                 LET $pending = (SELECT VALUE id FROM {table} WHERE connection = $connection \
+                 AND language = $language AND status INSIDE $pending_status LIMIT 1); \
+                 IF array::len($pending) = 0 THEN (CREATE {table} SET manga_id = $manga_id, \
+                 chapter = $chapter, version_id = $version_id, connection = $connection, \
+                 requested_by = $user, language = $language, status = $queued, pages_done = 0, \
+                 pages_total = 0, error = NONE RETURN VALUE id) ELSE [] END; \
+                 COMMIT TRANSACTION;",
+                table = TranslationJob::name()
+            ))
+            .bind(("manga_id", manga_id))
+            .bind(("chapter", Thing::from((Chapter::name(), chapter_id))))
+            .bind(("version_id", version_id))
+            .bind((
+                "connection",
+                Thing::from((ChapterVersion::name(), connection)),
+            ))
+            .bind(("user", Thing::from((User::name(), user_id))))
+            .bind(("language", language))
+            .bind(("queued", TranslationJobStatus::Queued as u32))
+            .bind((
+                "pending_status",
+                [
+                    TranslationJobStatus::Queued as u32,
+                    TranslationJobStatus::Running as u32,
+                ],
+            ))
+            .await?
+            .take(1)?;
+        Ok(created.into_iter().next().map(|v| v.id.to_raw()))
+    }
+
+    /// newest first
+    pub async fn for_chapter(
+        &self,
+        chapter_id: &str,
+    ) -> ApiResult<Vec<RecordData<TranslationJob>>> {
+        Ok(self
+            .conn
+            .query(format!(
+                "SELECT * FROM {} WHERE chapter = $chapter ORDER BY created DESC",
+                TranslationJob::name()
+            ))
+            .bind(("chapter", Thing::from((Chapter::name(), chapter_id))))
+            .await?
+            .take(0)?)
+    }
+
+    /// oldest queued job
+    pub async fn next(&self) -> ApiResult<Option<RecordData<TranslationJob>>> {
+        let mut v: Vec<RecordData<TranslationJob>> = TranslationJob::search(
+            &*self.conn,
+            Some(format!(
+                "WHERE status = {} ORDER BY created ASC LIMIT 1",
+                TranslationJobStatus::Queued as u32
+            )),
+        )
+        .await?;
+        Ok(v.pop())
+    }
+
+    /// puts jobs that were interrupted by a shutdown back into the queue
+    pub async fn requeue_running(&self) -> ApiResult<()> {
+        let q = format!(
+            "UPDATE {} SET status = {}, pages_done = 0 WHERE status = {}",
+            TranslationJob::name(),
+            TranslationJobStatus::Queued as u32,
+            TranslationJobStatus::Running as u32
+        );
+        self.conn.query(q).await?.check()?;
+        Ok(())
+    }
+
+    pub async fn set_status(
+        &self,
+        job_id: &str,
+        status: TranslationJobStatus,
+        error: Option<String>,
+    ) -> ApiResult<()> {
+        self.conn
+            .query("UPDATE $job SET status = $status, error = $error")
+            .bind(("job", Thing::from((TranslationJob::name(), job_id))))
+            .bind(("status", status as u32))
+            .bind(("error", error))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    pub async fn set_progress(&self, job_id: &str, done: u32, total: u32) -> ApiResult<()> {
+        self.conn
+            .query("UPDATE $job SET pages_done = $done, pages_total = $total")
+            .bind(("job", Thing::from((TranslationJob::name(), job_id))))
+            .bind(("done", done))
+            .bind(("total", total))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
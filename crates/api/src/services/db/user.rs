@@ -48,6 +48,23 @@ pub struct UserRolePassword {
     pub password: String,
 }
 
+#[derive(SurrealSelect, Deserialize)]
+pub struct UserProfilePart {
+    pub names: Vec<String>,
+    pub role: u32,
+    pub bio: Option<String>,
+    pub links: Vec<String>,
+    pub thumb_ext: Option<String>,
+    pub icon_ext: Option<String>,
+    pub created: Datetime,
+}
+
+#[derive(SurrealSelect, Deserialize, Serialize)]
+pub struct UserBio {
+    pub bio: Option<String>,
+    pub links: Vec<String>,
+}
+
 pub struct UserDBService {
     pub conn: Arc<Surreal<Db>>,
     temp: Arc<Mutex<HashMap<String, String>>>,
@@ -91,6 +108,31 @@ impl UserDBService {
         Ok(())
     }
 
+    pub async fn get_profile(&self, id: &str) -> ApiResult<UserProfilePart> {
+        let v: ThingType<User> = ThingType::new(ThingFunc::new(Thing::from((User::name(), id))));
+        let v: RecordData<UserProfilePart> = v.get_part(&*self.conn).await?.ok_or(ApiErr {
+            message: Some("No user found".to_string()),
+            cause: None,
+            err_type: ApiErrorType::InvalidInput,
+        })?;
+        Ok(v.data)
+    }
+
+    pub async fn set_bio(&self, id: &str, bio: UserBio) -> ApiResult<()> {
+        let v: ThingFunc = ThingFunc::new(Thing::from((User::name(), id)));
+        let _: Option<Record> = v.update(&*self.conn, bio).await?;
+        Ok(())
+    }
+
+    /// `field` is icon_ext or thumb_ext
+    pub async fn set_image_ext(&self, id: &str, field: &str, ext: String) -> ApiResult<()> {
+        let v: ThingFunc = ThingFunc::new(Thing::from((User::name(), id)));
+        let _: Option<Record> = v
+            .patch(&*self.conn, PatchOp::replace(field, Some(ext)))
+            .await?;
+        Ok(())
+    }
+
     pub async fn set_role(&self, id: &str, role: Role) -> ApiResult<()> {
         let v: ThingFunc = ThingFunc::new(Thing::from((User::name(), id)));
         let role = UserRole { role: role as u32 };
@@ -1,13 +1,16 @@
+use crate::errors::{ApiError, ApiResult};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use surrealdb::engine::local::Db;
+use surrealdb::sql::Thing;
 use surrealdb::Surreal;
-use surrealdb_extras::SurrealTable;
+use surrealdb_extras::{RecordData, SurrealTable, SurrealTableInfo, ThingFunc};
 
 #[derive(SurrealTable, Serialize, Deserialize, Debug)]
 #[db("chapter_versions")]
 pub struct Version {
     pub name: String,
+    /// options which are passed to the translation backend
     pub translate_opts: Option<String>,
 }
 
@@ -28,4 +31,16 @@ impl VersionDBService {
     pub fn new(conn: Arc<Surreal<Db>>) -> Self {
         Self { conn }
     }
+
+    /// `id` with or without the table prefix
+    pub async fn get(&self, id: &str) -> ApiResult<Version> {
+        let id = id
+            .strip_prefix(&format!("{}:", Version::name()))
+            .unwrap_or(id);
+        let v: RecordData<Version> = ThingFunc::from(Thing::from((Version::name(), id)))
+            .get(&*self.conn)
+            .await?
+            .ok_or(ApiError::invalid_input("Version does not exist"))?;
+        Ok(v.data)
+    }
 }
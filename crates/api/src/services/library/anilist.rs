@@ -0,0 +1,161 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::library::{site_id, LibraryEntry, ReadingStatus, ANILIST_PREFIX, MAL_PREFIX};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// response of the `MediaListCollection` query
+#[derive(Serialize, Deserialize)]
+struct Response {
+    data: Data,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Data {
+    #[serde(rename = "MediaListCollection")]
+    collection: Collection,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Collection {
+    lists: Vec<List>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct List {
+    name: String,
+    /// custom lists contain entries which are also part of a status list
+    #[serde(default, rename = "isCustomList")]
+    custom: bool,
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    status: String,
+    #[serde(default)]
+    progress: Option<u32>,
+    /// unix timestamp
+    #[serde(default, rename = "updatedAt", skip_serializing_if = "Option::is_none")]
+    updated_at: Option<i64>,
+    media: Media,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Media {
+    id: Option<u64>,
+    #[serde(rename = "idMal")]
+    id_mal: Option<u64>,
+    title: Title,
+    #[serde(default)]
+    synonyms: Vec<String>,
+    #[serde(rename = "siteUrl")]
+    site_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Title {
+    romaji: Option<String>,
+    english: Option<String>,
+    native: Option<String>,
+}
+
+fn status(status: &str) -> ReadingStatus {
+    match status {
+        "COMPLETED" => ReadingStatus::Completed,
+        "PAUSED" => ReadingStatus::OnHold,
+        "DROPPED" => ReadingStatus::Dropped,
+        "PLANNING" => ReadingStatus::PlanToRead,
+        _ => ReadingStatus::Reading,
+    }
+}
+
+/// (status, list name)
+fn status_name(status: ReadingStatus) -> (&'static str, &'static str) {
+    match status {
+        ReadingStatus::Reading => ("CURRENT", "Reading"),
+        ReadingStatus::Completed => ("COMPLETED", "Completed"),
+        ReadingStatus::OnHold => ("PAUSED", "Paused"),
+        ReadingStatus::Dropped => ("DROPPED", "Dropped"),
+        ReadingStatus::PlanToRead => ("PLANNING", "Planning"),
+    }
+}
+
+pub fn parse(data: &str) -> ApiResult<Vec<LibraryEntry>> {
+    let response: Response = serde_json::from_str(data)
+        .map_err(|e| ApiError::invalid_input(format!("Invalid AniList export: {}", e)))?;
+    Ok(response
+        .data
+        .collection
+        .lists
+        .into_iter()
+        .filter(|v| !v.custom)
+        .flat_map(|v| v.entries)
+        .map(|v| {
+            let media = v.media;
+            let mut urls = vec![];
+            if let Some(id) = media.id {
+                urls.push(format!("https://{}{}", ANILIST_PREFIX, id));
+            }
+            if let Some(id) = media.id_mal {
+                urls.push(format!("https://{}{}", MAL_PREFIX, id));
+            }
+            urls.extend(media.site_url);
+            LibraryEntry {
+                titles: [media.title.english, media.title.romaji, media.title.native]
+                    .into_iter()
+                    .flatten()
+                    .chain(media.synonyms)
+                    .collect(),
+                urls,
+                status: status(&v.status),
+                chapters: v.progress.unwrap_or_default(),
+                updated: v
+                    .updated_at
+                    .and_then(|v| DateTime::<Utc>::from_timestamp(v, 0)),
+            }
+        })
+        .collect())
+}
+
+/// every title is exported as english title
+pub fn write(entries: &[LibraryEntry]) -> ApiResult<String> {
+    let lists = ReadingStatus::ALL
+        .into_iter()
+        .map(|status| {
+            let (status_key, name) = status_name(status);
+            List {
+                name: name.to_string(),
+                custom: false,
+                entries: entries
+                    .iter()
+                    .filter(|v| v.status == status)
+                    .map(|v| {
+                        let id = site_id(&v.urls, ANILIST_PREFIX);
+                        Entry {
+                            status: status_key.to_string(),
+                            progress: Some(v.chapters),
+                            updated_at: None,
+                            media: Media {
+                                id,
+                                id_mal: site_id(&v.urls, MAL_PREFIX),
+                                title: Title {
+                                    romaji: None,
+                                    english: v.titles.first().cloned(),
+                                    native: None,
+                                },
+                                synonyms: v.titles.iter().skip(1).cloned().collect(),
+                                site_url: id.map(|id| format!("https://{}{}", ANILIST_PREFIX, id)),
+                            },
+                        }
+                    })
+                    .collect(),
+            }
+        })
+        .filter(|v| !v.entries.is_empty())
+        .collect();
+    Ok(serde_json::to_string_pretty(&Response {
+        data: Data {
+            collection: Collection { lists },
+        },
+    })?)
+}
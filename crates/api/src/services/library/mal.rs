@@ -0,0 +1,113 @@
+use crate::errors::{ApiError, ApiResult};
+use crate::services::library::{site_id, LibraryEntry, ReadingStatus, MAL_PREFIX};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// `user_export_type` of manga lists
+const MANGA_EXPORT: u32 = 2;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "myanimelist")]
+struct Export {
+    myinfo: Option<MyInfo>,
+    #[serde(default)]
+    manga: Vec<Entry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MyInfo {
+    user_export_type: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    manga_mangadb_id: u64,
+    manga_title: String,
+    my_read_chapters: u32,
+    my_status: String,
+    /// YYYY-MM-DD. 0000-00-00 if unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    my_start_date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    my_finish_date: Option<String>,
+}
+
+fn status(status: &str) -> ReadingStatus {
+    match status {
+        "Completed" => ReadingStatus::Completed,
+        "On-Hold" => ReadingStatus::OnHold,
+        "Dropped" => ReadingStatus::Dropped,
+        "Plan to Read" => ReadingStatus::PlanToRead,
+        _ => ReadingStatus::Reading,
+    }
+}
+
+fn date(date: &str) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(DateTime::from_naive_utc_and_offset(
+        date.and_hms_opt(0, 0, 0)?,
+        Utc,
+    ))
+}
+
+fn status_name(status: ReadingStatus) -> &'static str {
+    match status {
+        ReadingStatus::Reading => "Reading",
+        ReadingStatus::Completed => "Completed",
+        ReadingStatus::OnHold => "On-Hold",
+        ReadingStatus::Dropped => "Dropped",
+        ReadingStatus::PlanToRead => "Plan to Read",
+    }
+}
+
+pub fn parse(data: &str) -> ApiResult<Vec<LibraryEntry>> {
+    let export: Export = quick_xml::de::from_str(data)
+        .map_err(|e| ApiError::invalid_input(format!("Invalid MyAnimeList export: {}", e)))?;
+    if export
+        .myinfo
+        .is_some_and(|v| v.user_export_type != MANGA_EXPORT)
+    {
+        return Err(ApiError::invalid_input(
+            "MyAnimeList export does not contain a manga list",
+        ));
+    }
+    Ok(export
+        .manga
+        .into_iter()
+        .map(|v| LibraryEntry {
+            titles: vec![v.manga_title],
+            urls: vec![format!("https://{}{}", MAL_PREFIX, v.manga_mangadb_id)],
+            status: status(&v.my_status),
+            chapters: v.my_read_chapters,
+            updated: [v.my_finish_date, v.my_start_date]
+                .into_iter()
+                .flatten()
+                .find_map(|v| date(&v)),
+        })
+        .collect())
+}
+
+/// mangas without a myanimelist source get the id 0
+pub fn write(entries: &[LibraryEntry]) -> ApiResult<String> {
+    let export = Export {
+        myinfo: Some(MyInfo {
+            user_export_type: MANGA_EXPORT,
+        }),
+        manga: entries
+            .iter()
+            .map(|v| Entry {
+                manga_mangadb_id: site_id(&v.urls, MAL_PREFIX).unwrap_or_default(),
+                manga_title: v.titles.first().cloned().unwrap_or_default(),
+                my_read_chapters: v.chapters,
+                my_status: status_name(v.status).to_string(),
+                my_start_date: None,
+                my_finish_date: None,
+            })
+            .collect(),
+    };
+    let xml = quick_xml::se::to_string(&export).map_err(ApiError::write_error)?;
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" ?>\n{}",
+        xml
+    ))
+}
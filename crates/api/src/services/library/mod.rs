@@ -0,0 +1,247 @@
+mod anilist;
+mod mal;
+
+use crate::errors::{ApiError, ApiResult};
+use api_structure::library::LibraryFormat;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// titles in this language are exported first
+const EXPORT_LANGUAGE: &str = "eng";
+const MAL_PREFIX: &str = "myanimelist.net/manga/";
+const ANILIST_PREFIX: &str = "anilist.co/manga/";
+
+/// every status has its own manga list
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReadingStatus {
+    Reading,
+    Completed,
+    OnHold,
+    Dropped,
+    PlanToRead,
+}
+
+impl ReadingStatus {
+    pub const ALL: [ReadingStatus; 5] = [
+        ReadingStatus::Reading,
+        ReadingStatus::Completed,
+        ReadingStatus::OnHold,
+        ReadingStatus::Dropped,
+        ReadingStatus::PlanToRead,
+    ];
+
+    /// name of the manga list
+    pub fn list_name(&self) -> &'static str {
+        match self {
+            ReadingStatus::Reading => "Reading",
+            ReadingStatus::Completed => "Completed",
+            ReadingStatus::OnHold => "On Hold",
+            ReadingStatus::Dropped => "Dropped",
+            ReadingStatus::PlanToRead => "Plan to Read",
+        }
+    }
+
+    pub fn from_list_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.list_name() == name)
+    }
+}
+
+/// manga of a tracking site export
+pub struct LibraryEntry {
+    /// the first title is used for the export
+    pub titles: Vec<String>,
+    /// urls of the manga on tracking sites or scraper sources
+    pub urls: Vec<String>,
+    pub status: ReadingStatus,
+    /// number of read chapters
+    pub chapters: u32,
+    /// last change on the tracking site
+    pub updated: Option<DateTime<Utc>>,
+}
+
+/// detects the format from the content
+pub fn parse(data: &str) -> ApiResult<Vec<LibraryEntry>> {
+    match data.trim_start().chars().next() {
+        Some('<') => mal::parse(data),
+        Some('{') => anilist::parse(data),
+        _ => Err(ApiError::invalid_input("Unknown library format")),
+    }
+}
+
+pub fn write(format: LibraryFormat, entries: &[LibraryEntry]) -> ApiResult<String> {
+    match format {
+        LibraryFormat::MyAnimeList => mal::write(entries),
+        LibraryFormat::AniList => anilist::write(entries),
+    }
+}
+
+/// titles of a manga in the order of [LibraryEntry::titles]. english first, then by language
+pub fn export_titles(mut titles: HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut res = titles.remove(EXPORT_LANGUAGE).unwrap_or_default();
+    let mut rest: Vec<_> = titles.into_iter().collect();
+    rest.sort_by(|a, b| a.0.cmp(&b.0));
+    res.extend(rest.into_iter().flat_map(|v| v.1));
+    res
+}
+
+/// removes the scheme, `www.` and trailing slashes
+pub fn normalize_url(url: &str) -> String {
+    let url = url.split_once("://").map(|v| v.1).unwrap_or(url);
+    let url = url.strip_prefix("www.").unwrap_or(url);
+    url.trim_end_matches('/').to_lowercase()
+}
+
+/// lowercase without whitespace and punctuation
+pub fn normalize_title(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// id of the manga on a tracking site. `prefix` is the normalized url in front of the id
+fn site_id(urls: &[String], prefix: &str) -> Option<u64> {
+    urls.iter().find_map(|url| {
+        normalize_url(url)
+            .strip_prefix(prefix)?
+            .split('/')
+            .next()?
+            .parse()
+            .ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAL: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<myanimelist>
+    <myinfo><user_export_type>2</user_export_type></myinfo>
+    <manga>
+        <manga_mangadb_id>2</manga_mangadb_id>
+        <manga_title><![CDATA[Berserk]]></manga_title>
+        <my_read_chapters>364</my_read_chapters>
+        <my_status>On-Hold</my_status>
+        <my_start_date>2020-01-02</my_start_date>
+        <my_finish_date>0000-00-00</my_finish_date>
+    </manga>
+</myanimelist>"#;
+
+    const ANILIST: &str = r#"{"data": {"MediaListCollection": {"lists": [
+        {"name": "Reading", "entries": [{
+            "status": "CURRENT",
+            "progress": 12,
+            "updatedAt": 1700000000,
+            "media": {
+                "id": 30002,
+                "idMal": 2,
+                "title": {"romaji": "Berserk", "english": null, "native": "ベルセルク"},
+                "synonyms": ["Berserk: The Prototype"],
+                "siteUrl": "https://anilist.co/manga/30002"
+            }
+        }]},
+        {"name": "Favorites", "isCustomList": true, "entries": [{
+            "status": "CURRENT",
+            "media": {"id": 1, "idMal": null, "title": {"romaji": "Other"}}
+        }]}
+    ]}}}"#;
+
+    #[test]
+    fn normalized_urls() {
+        assert_eq!(
+            normalize_url("https://www.MyAnimeList.net/manga/2/Berserk/"),
+            "myanimelist.net/manga/2/berserk"
+        );
+        assert_eq!(
+            normalize_url("anilist.co/manga/30002"),
+            "anilist.co/manga/30002"
+        );
+        assert_eq!(normalize_url("http://anilist.co//"), "anilist.co");
+    }
+
+    #[test]
+    fn site_ids() {
+        let urls = vec![
+            "https://mangadex.org/title/abc".to_string(),
+            "https://www.myanimelist.net/manga/2/Berserk".to_string(),
+        ];
+        assert_eq!(site_id(&urls, MAL_PREFIX), Some(2));
+        assert_eq!(site_id(&urls, ANILIST_PREFIX), None);
+        let urls = vec!["https://myanimelist.net/manga/berserk".to_string()];
+        assert_eq!(site_id(&urls, MAL_PREFIX), None);
+    }
+
+    #[test]
+    fn parse_mal() {
+        let entries = parse(MAL).unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.titles, ["Berserk"]);
+        assert_eq!(site_id(&entry.urls, MAL_PREFIX), Some(2));
+        assert_eq!(entry.status, ReadingStatus::OnHold);
+        assert_eq!(entry.chapters, 364);
+        // the unknown finish date falls back to the start date
+        assert_eq!(
+            entry.updated.unwrap().to_rfc3339(),
+            "2020-01-02T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn parse_anime_list() {
+        let data = MAL.replace("<user_export_type>2", "<user_export_type>1");
+        assert!(parse(&data).is_err());
+    }
+
+    #[test]
+    fn parse_anilist() {
+        let entries = parse(ANILIST).unwrap();
+        // custom lists only repeat entries of the status lists
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(
+            entry.titles,
+            ["Berserk", "ベルセルク", "Berserk: The Prototype"]
+        );
+        assert_eq!(site_id(&entry.urls, ANILIST_PREFIX), Some(30002));
+        assert_eq!(site_id(&entry.urls, MAL_PREFIX), Some(2));
+        assert_eq!(entry.status, ReadingStatus::Reading);
+        assert_eq!(entry.chapters, 12);
+        assert_eq!(entry.updated.unwrap().timestamp(), 1700000000);
+    }
+
+    #[test]
+    fn parse_unknown() {
+        assert!(parse("").is_err());
+        assert!(parse("title,chapters").is_err());
+    }
+
+    #[test]
+    fn write_and_parse() {
+        for format in [LibraryFormat::MyAnimeList, LibraryFormat::AniList] {
+            let entries = parse(&write(format, &parse(ANILIST).unwrap()).unwrap()).unwrap();
+            assert_eq!(entries.len(), 1);
+            assert_eq!(entries[0].titles[0], "Berserk");
+            assert_eq!(site_id(&entries[0].urls, MAL_PREFIX), Some(2));
+            assert_eq!(entries[0].status, ReadingStatus::Reading);
+            assert_eq!(entries[0].chapters, 12);
+        }
+    }
+
+    #[test]
+    fn english_title_first() {
+        let titles = HashMap::from([
+            ("jpn".to_string(), vec!["ベルセルク".to_string()]),
+            ("eng".to_string(), vec!["Berserk".to_string()]),
+            ("deu".to_string(), vec!["Berserk DE".to_string()]),
+        ]);
+        assert_eq!(
+            export_titles(titles),
+            vec!["Berserk", "Berserk DE", "ベルセルク"]
+        );
+        let titles = HashMap::from([("jpn".to_string(), vec!["ベルセルク".to_string()])]);
+        assert_eq!(export_titles(titles), vec!["ベルセルク"]);
+    }
+}
@@ -2,4 +2,13 @@ pub mod auth_service;
 pub mod crypto_service;
 pub mod db;
 pub mod internal;
+pub mod library;
+pub mod notification_service;
+pub mod recommendation_service;
+#[cfg(unix)]
+pub mod reload_service;
+pub mod storage;
+pub mod sync_service;
+pub mod temp_service;
+pub mod translation_service;
 pub mod uri_service;
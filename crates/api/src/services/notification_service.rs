@@ -0,0 +1,131 @@
+use crate::errors::ApiResult;
+use crate::services::db::chapter::ChapterDBService;
+use crate::services::db::manga::{MangaDBService, MangaTitles};
+use crate::services::db::notification::NotificationDBService;
+use api_structure::notification::{Notification, NotificationEvent};
+use chrono::Utc;
+use log::{error, warn};
+use std::sync::Arc;
+use std::time::Duration;
+use surrealdb::engine::local::Db;
+use surrealdb::sql::Datetime;
+use surrealdb::Surreal;
+use surrealdb_extras::RecordData;
+use tokio::sync::broadcast;
+
+/// how often new chapters are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// time a new chapter can take to be added to its manga
+const UNLINKED_GRACE: Duration = Duration::from_secs(10 * 60);
+
+/// forwards new notifications to the open event streams
+pub struct NotificationHub {
+    sender: broadcast::Sender<(String, NotificationEvent)>,
+}
+
+impl Default for NotificationHub {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(256).0,
+        }
+    }
+}
+
+impl NotificationHub {
+    /// receives (user id, event)
+    pub fn subscribe(&self) -> broadcast::Receiver<(String, NotificationEvent)> {
+        self.sender.subscribe()
+    }
+
+    fn send(&self, user: String, event: NotificationEvent) {
+        // fails if nobody is listening
+        let _ = self.sender.send((user, event));
+    }
+}
+
+/// notifies users about new chapters. the cursor is stored, so chapters which were
+/// added while the server was offline are notified after the next start
+pub async fn notification_service(db: Arc<Surreal<Db>>, hub: Arc<NotificationHub>) {
+    let worker = Worker {
+        chapters: ChapterDBService::new(db.clone()),
+        mangas: MangaDBService::new(db.clone()),
+        notifications: NotificationDBService::new(db),
+        hub,
+    };
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = worker.poll().await {
+            error!("Failed to create notifications: {}", e);
+        }
+    }
+}
+
+struct Worker {
+    chapters: ChapterDBService,
+    mangas: MangaDBService,
+    notifications: NotificationDBService,
+    hub: Arc<NotificationHub>,
+}
+
+impl Worker {
+    /// advances the cursor past every processed chapter
+    async fn poll(&self) -> ApiResult<()> {
+        let since = match self.notifications.cursor().await? {
+            Some(v) => v,
+            None => {
+                // first start. existing chapters aren't new
+                let now = Datetime::default();
+                self.notifications.set_cursor(&now).await?;
+                now
+            }
+        };
+        for chapter in self.chapters.created_since(&since).await? {
+            let chapter_id = chapter.id.id().to_string();
+            let age = Utc::now() - chapter.data.created.0;
+            match self.mangas.by_chapter(&chapter_id).await? {
+                Some(manga) => {
+                    self.notify(&manga, &chapter_id, chapter.data.chapter)
+                        .await?
+                }
+                // the chapter is created before it is added to the manga
+                None if age.num_seconds() < UNLINKED_GRACE.as_secs() as i64 => break,
+                None => warn!("Chapter {} has no manga. skipped", chapter_id),
+            }
+            self.notifications.set_cursor(&chapter.data.created).await?;
+        }
+        Ok(())
+    }
+
+    async fn notify(
+        &self,
+        manga: &RecordData<MangaTitles>,
+        chapter_id: &str,
+        chapter: f64,
+    ) -> ApiResult<()> {
+        let manga_id = manga.id.id().to_string();
+        for user in self.notifications.subscribers(&manga_id).await? {
+            let Some(v) = self.notifications.add(&user, &manga_id, chapter_id).await? else {
+                continue;
+            };
+            let notification = Notification {
+                notification_id: v.id.id().to_string(),
+                manga_id: manga_id.clone(),
+                titles: manga.data.titles.clone(),
+                chapter_id: chapter_id.to_string(),
+                chapter,
+                read: v.data.read,
+                created: v.data.created.to_string(),
+            };
+            let unread = self.notifications.unread(&user).await?;
+            self.hub.send(
+                user,
+                NotificationEvent {
+                    unread,
+                    notification: Some(notification),
+                },
+            );
+        }
+        Ok(())
+    }
+}
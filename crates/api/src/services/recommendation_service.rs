@@ -0,0 +1,301 @@
+use crate::errors::ApiResult;
+use crate::services::db::manga::MangaDBService;
+use crate::services::db::manga_list::MangaListDBService;
+use crate::services::db::progress::ProgressDBService;
+use log::error;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use surrealdb::engine::local::Db;
+use surrealdb::Surreal;
+
+/// how often the recommendations are recomputed
+const INTERVAL: Duration = Duration::from_secs(60 * 30);
+/// recommendations per user
+const LIMIT: usize = 20;
+/// weight of a manga the user started reading
+const READ_WEIGHT: f64 = 1.0;
+/// added to [READ_WEIGHT] when the manga is a favorite
+const FAVORITE_WEIGHT: f64 = 2.0;
+const KIND_WEIGHT: f64 = 0.5;
+const CO_READING_WEIGHT: f64 = 1.0;
+
+/// precomputed manga ids per user. best first
+#[derive(Default)]
+pub struct RecommendationStore {
+    data: RwLock<HashMap<String, Vec<String>>>,
+}
+
+impl RecommendationStore {
+    pub fn get(&self, user: &str) -> Vec<String> {
+        self.data
+            .read()
+            .map(|v| v.get(user).cloned().unwrap_or_default())
+            .unwrap_or_default()
+    }
+
+    fn replace(&self, data: HashMap<String, Vec<String>>) {
+        if let Ok(mut v) = self.data.write() {
+            *v = data;
+        }
+    }
+}
+
+/// recomputes the recommendations of all users in an interval
+pub async fn recommendation_service(db: Arc<Surreal<Db>>, store: Arc<RecommendationStore>) {
+    let worker = Worker {
+        mangas: MangaDBService::new(db.clone()),
+        lists: MangaListDBService::new(db.clone()),
+        progress: ProgressDBService::new(db),
+    };
+    let mut interval = tokio::time::interval(INTERVAL);
+    loop {
+        interval.tick().await;
+        let snapshot = match worker.load().await {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to load recommendation data: {}", e);
+                continue;
+            }
+        };
+        // scoring every manga for every user would block the runtime
+        match tokio::task::spawn_blocking(move || snapshot.rank()).await {
+            Ok(v) => store.replace(v),
+            Err(e) => error!("Failed to compute recommendations: {}", e),
+        }
+    }
+}
+
+struct Profile {
+    kind: String,
+    tags: Vec<String>,
+}
+
+struct Worker {
+    mangas: MangaDBService,
+    lists: MangaListDBService,
+    progress: ProgressDBService,
+}
+
+/// everything the recommendations are computed from
+struct Snapshot {
+    /// user => manga => weight
+    interactions: HashMap<String, HashMap<String, f64>>,
+    mangas: HashMap<String, Profile>,
+}
+
+impl Snapshot {
+    fn rank(&self) -> HashMap<String, Vec<String>> {
+        let mut readers: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (user, seen) in &self.interactions {
+            for manga in seen.keys() {
+                readers
+                    .entry(manga.as_str())
+                    .or_default()
+                    .push(user.as_str());
+            }
+        }
+        self.interactions
+            .keys()
+            .map(|user| {
+                (
+                    user.clone(),
+                    recommend(user, &self.interactions, &readers, &self.mangas),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Worker {
+    async fn load(&self) -> ApiResult<Snapshot> {
+        let mangas: HashMap<String, Profile> = self
+            .mangas
+            .profiles()
+            .await?
+            .into_iter()
+            .map(|v| {
+                let profile = Profile {
+                    kind: v.data.kind.thing.id().to_string(),
+                    tags: v
+                        .data
+                        .tags
+                        .into_iter()
+                        .map(|v| v.thing.id().to_string())
+                        .collect(),
+                };
+                (v.id.id().to_string(), profile)
+            })
+            .collect();
+
+        let mut interactions: HashMap<String, HashMap<String, f64>> = HashMap::new();
+        for v in self.progress.read_mangas().await? {
+            interactions
+                .entry(v.user.id.to_string())
+                .or_default()
+                .insert(v.manga.id.to_string(), READ_WEIGHT);
+        }
+        for list in self.lists.favorites().await? {
+            let seen = interactions
+                .entry(list.data.user.thing.id().to_string())
+                .or_default();
+            for manga in list.data.mangas {
+                *seen.entry(manga.thing.id().to_string()).or_default() += FAVORITE_WEIGHT;
+            }
+        }
+        Ok(Snapshot {
+            interactions,
+            mangas,
+        })
+    }
+}
+
+/// scores every unseen manga by weighted tag overlap with the taste profile of the user
+/// and by how much similar users read it
+fn recommend(
+    user: &str,
+    interactions: &HashMap<String, HashMap<String, f64>>,
+    readers: &HashMap<&str, Vec<&str>>,
+    mangas: &HashMap<String, Profile>,
+) -> Vec<String> {
+    let seen = &interactions[user];
+
+    let mut tags: HashMap<&str, f64> = HashMap::new();
+    let mut kinds: HashMap<&str, f64> = HashMap::new();
+    let mut total = 0.0;
+    for (manga, weight) in seen {
+        if let Some(profile) = mangas.get(manga) {
+            for tag in &profile.tags {
+                *tags.entry(tag.as_str()).or_default() += weight;
+            }
+            *kinds.entry(profile.kind.as_str()).or_default() += weight;
+            total += weight;
+        }
+    }
+
+    // cosine similarity of the read mangas
+    let mut overlap: HashMap<&str, f64> = HashMap::new();
+    for manga in seen.keys() {
+        for other in readers.get(manga.as_str()).into_iter().flatten() {
+            if *other != user {
+                *overlap.entry(*other).or_default() += 1.0;
+            }
+        }
+    }
+    let mut co_reading: HashMap<&str, f64> = HashMap::new();
+    for (other, count) in overlap {
+        let other_seen = &interactions[other];
+        let similarity = count / ((seen.len() * other_seen.len()) as f64).sqrt();
+        for (manga, weight) in other_seen {
+            if !seen.contains_key(manga) {
+                *co_reading.entry(manga.as_str()).or_default() += similarity * weight;
+            }
+        }
+    }
+    let max_co_reading = co_reading.values().copied().fold(0.0, f64::max);
+
+    let mut scores = vec![];
+    for (manga, profile) in mangas {
+        if seen.contains_key(manga) {
+            continue;
+        }
+        let mut score = 0.0;
+        if total > 0.0 {
+            let unique: HashSet<_> = profile.tags.iter().collect();
+            if !unique.is_empty() {
+                let overlap: f64 = unique.iter().filter_map(|tag| tags.get(tag.as_str())).sum();
+                score += overlap / total / (unique.len() as f64).sqrt();
+            }
+            score += KIND_WEIGHT * kinds.get(profile.kind.as_str()).unwrap_or(&0.0) / total;
+        }
+        if max_co_reading > 0.0 {
+            score +=
+                CO_READING_WEIGHT * co_reading.get(manga.as_str()).unwrap_or(&0.0) / max_co_reading;
+        }
+        if score > 0.0 {
+            scores.push((manga, score));
+        }
+    }
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scores
+        .into_iter()
+        .take(LIMIT)
+        .map(|(manga, _)| manga.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(kind: &str, tags: &[&str]) -> Profile {
+        Profile {
+            kind: kind.to_string(),
+            tags: tags.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    fn snapshot(users: &[(&str, &[&str])], mangas: Vec<(&str, Profile)>) -> Snapshot {
+        Snapshot {
+            interactions: users
+                .iter()
+                .map(|(user, seen)| {
+                    let seen = seen.iter().map(|v| (v.to_string(), READ_WEIGHT)).collect();
+                    (user.to_string(), seen)
+                })
+                .collect(),
+            mangas: mangas
+                .into_iter()
+                .map(|(id, profile)| (id.to_string(), profile))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn ranks_by_tags_and_kind() {
+        let snapshot = snapshot(
+            &[("user", &["read"])],
+            vec![
+                ("read", profile("manga", &["action", "fantasy"])),
+                ("same_tags", profile("manga", &["action", "fantasy"])),
+                ("one_tag", profile("manga", &["action", "romance"])),
+                ("other_kind", profile("novel", &["action", "romance"])),
+                ("unrelated", profile("novel", &["romance"])),
+            ],
+        );
+        assert_eq!(
+            snapshot.rank()["user"],
+            vec!["same_tags", "one_tag", "other_kind"]
+        );
+    }
+
+    #[test]
+    fn co_reading_counts() {
+        let snapshot = snapshot(
+            &[("user", &["a"]), ("other", &["a", "b"])],
+            vec![
+                ("a", profile("manga", &[])),
+                ("b", profile("novel", &[])),
+                ("c", profile("other", &[])),
+            ],
+        );
+        let ranked = snapshot.rank();
+        assert_eq!(ranked["user"], vec!["b"]);
+        assert!(ranked["other"].is_empty());
+    }
+
+    #[test]
+    fn excludes_read_mangas() {
+        let snapshot = snapshot(
+            &[("user", &["a", "b"]), ("other", &["a", "b", "c"])],
+            vec![
+                ("a", profile("manga", &["action"])),
+                ("b", profile("manga", &["action"])),
+                ("c", profile("manga", &["action"])),
+            ],
+        );
+        let ranked = snapshot.rank();
+        assert_eq!(ranked["user"], vec!["c"]);
+        assert!(ranked["other"].is_empty());
+    }
+}
@@ -0,0 +1,41 @@
+use crate::env::config::{get_env, Config};
+use crate::env::settings::Settings;
+use log::{error, info, warn};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::signal::unix::{signal, SignalKind};
+
+/// reloads the config on SIGHUP. `config` is the config the server was started with
+pub async fn reload_service(settings: Arc<Settings>, path: Option<PathBuf>, config: Config) {
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(v) => v,
+        Err(e) => {
+            error!("Failed to listen for SIGHUP: {}", e);
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        let new = match get_env(path.as_deref()) {
+            Ok(v) => v,
+            Err(e) => {
+                error!("Failed to reload config: {}", e);
+                continue;
+            }
+        };
+        match settings.reload(&new) {
+            true => info!("Reloaded config"),
+            false => info!("Config has no reloadable changes"),
+        }
+        if needs_restart(&config, &new) {
+            warn!("Only rust_log, spinner and cors_origins are reloaded. Other changes need a restart");
+        }
+    }
+}
+
+fn needs_restart(old: &Config, new: &Config) -> bool {
+    let mut new = new.clone();
+    new.rust_log.clone_from(&old.rust_log);
+    new.spinner = old.spinner.clone();
+    new.cors_origins.clone_from(&old.cors_origins);
+    serde_yaml::to_string(old).ok() != serde_yaml::to_string(&new).ok()
+}
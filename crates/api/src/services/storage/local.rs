@@ -0,0 +1,111 @@
+use crate::services::storage::{validate_key, Blob, BlobStore, ByteRange, Stat};
+use actix_web::web::Bytes;
+use futures_util::future::BoxFuture;
+use futures_util::stream;
+use std::io::{self, SeekFrom};
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// bytes per chunk of a stream
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+/// blobs are files inside of root_folder
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, key: &str) -> io::Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+impl BlobStore for LocalStore {
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+        range: Option<ByteRange>,
+    ) -> BoxFuture<'a, io::Result<Blob>> {
+        Box::pin(async move {
+            let mut file = File::open(self.path(key)?).await?;
+            let size = file.metadata().await?.len();
+            let (start, end) = match range {
+                Some(range) => range.resolve(size).ok_or(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Range not satisfiable",
+                ))?,
+                None => (0, size.saturating_sub(1)),
+            };
+            file.seek(SeekFrom::Start(start)).await?;
+            let remaining = match size {
+                0 => 0,
+                _ => end - start + 1,
+            };
+            let stream =
+                stream::try_unfold((file, remaining), |(mut file, remaining)| async move {
+                    if remaining == 0 {
+                        return Ok(None);
+                    }
+                    let mut buf = vec![0; remaining.min(CHUNK_SIZE) as usize];
+                    let n = file.read(&mut buf).await?;
+                    if n == 0 {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                    }
+                    buf.truncate(n);
+                    Ok(Some((Bytes::from(buf), (file, remaining - n as u64))))
+                });
+            Ok(Blob {
+                size,
+                range: range.map(|_| (start, end)),
+                stream: Box::pin(stream),
+            })
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(async move {
+            let path = self.path(key)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            fs::write(path, data).await
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(async move {
+            match fs::remove_file(self.path(key)?).await {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                res => res,
+            }
+        })
+    }
+
+    fn stat<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<Stat>>> {
+        Box::pin(async move {
+            match fs::metadata(self.path(key)?).await {
+                Ok(v) if v.is_file() => {
+                    let modified = v
+                        .modified()?
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_nanos();
+                    Ok(Some(Stat {
+                        size: v.len(),
+                        version: format!("{:x}-{:x}", v.len(), modified),
+                    }))
+                }
+                Ok(_) => Ok(None),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+    }
+}
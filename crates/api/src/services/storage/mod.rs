@@ -0,0 +1,402 @@
+mod local;
+mod s3;
+
+use crate::env::config::{Config, Storage};
+use crate::errors::ApiResult;
+use actix_web::body::SizedStream;
+use actix_web::http::header::{
+    HeaderValue, ACCEPT_RANGES, CACHE_CONTROL, CONTENT_RANGE, CONTENT_TYPE, ETAG, IF_NONE_MATCH,
+    RANGE,
+};
+use actix_web::http::StatusCode;
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse};
+use futures_util::future::BoxFuture;
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use std::io;
+use std::sync::Arc;
+
+pub use local::LocalStore;
+pub use s3::S3Store;
+
+/// part of a blob. both ends are inclusive like in the `Range` header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    /// `bytes=start-` or `bytes=start-end`
+    From(u64, Option<u64>),
+    /// `bytes=-len`
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// only single ranges are supported. others are ignored and the whole blob is sent
+    pub fn parse(header: &str) -> Option<Self> {
+        let (start, end) = header.trim().strip_prefix("bytes=")?.split_once('-')?;
+        if start.is_empty() {
+            return end.parse().ok().map(ByteRange::Suffix);
+        }
+        let start = start.parse().ok()?;
+        match end {
+            "" => Some(ByteRange::From(start, None)),
+            end => Some(ByteRange::From(start, Some(end.parse().ok()?))),
+        }
+    }
+
+    /// (start, end) inside of a blob with `size` bytes. none if it can't be satisfied
+    pub fn resolve(&self, size: u64) -> Option<(u64, u64)> {
+        let last = size.checked_sub(1)?;
+        match *self {
+            ByteRange::From(start, end) if start <= last => {
+                let end = end.unwrap_or(last).min(last);
+                (start <= end).then_some((start, end))
+            }
+            ByteRange::Suffix(len) if len > 0 => Some((size.saturating_sub(len), last)),
+            _ => None,
+        }
+    }
+
+    pub fn header(&self) -> String {
+        match self {
+            ByteRange::From(start, Some(end)) => format!("bytes={}-{}", start, end),
+            ByteRange::From(start, None) => format!("bytes={}-", start),
+            ByteRange::Suffix(len) => format!("bytes=-{}", len),
+        }
+    }
+}
+
+/// content of a blob which is read in chunks
+pub struct Blob {
+    /// size of the whole blob
+    pub size: u64,
+    /// (start, end) if only a range is sent
+    pub range: Option<(u64, u64)>,
+    pub stream: BoxStream<'static, io::Result<Bytes>>,
+}
+
+/// metadata of a blob
+pub struct Stat {
+    pub size: u64,
+    /// changes when the blob is overwritten
+    pub version: String,
+}
+
+/// storage of covers, pages and user images.
+/// keys are paths relative to root_folder separated by `/`, e.g. `covers/abc.jpeg`.
+/// missing blobs fail with [io::ErrorKind::NotFound]
+pub trait BlobStore: Send + Sync {
+    /// fails with [io::ErrorKind::InvalidInput] if the range can't be satisfied
+    fn get<'a>(&'a self, key: &'a str, range: Option<ByteRange>)
+        -> BoxFuture<'a, io::Result<Blob>>;
+
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> BoxFuture<'a, io::Result<()>>;
+
+    /// succeeds if the blob doesn't exist
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<()>>;
+
+    /// none if the blob doesn't exist
+    fn stat<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<Stat>>>;
+
+    /// reads the whole blob
+    fn read<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Vec<u8>>> {
+        Box::pin(async move {
+            let mut blob = self.get(key, None).await?;
+            let mut data = Vec::with_capacity(blob.size as usize);
+            while let Some(chunk) = blob.stream.next().await {
+                data.extend_from_slice(&chunk?);
+            }
+            Ok(data)
+        })
+    }
+}
+
+/// keys have to be relative paths without `.`, `..` or empty segments.
+/// every [BlobStore] checks its keys, because parts of them come from clients
+pub fn validate_key(key: &str) -> io::Result<()> {
+    let valid = !key.contains('\\')
+        && key
+            .split('/')
+            .all(|v| !v.is_empty() && v != "." && v != "..");
+    match valid {
+        true => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Illegal key {}", key),
+        )),
+    }
+}
+
+pub fn new_store(config: &Config) -> Arc<dyn BlobStore> {
+    match &config.storage {
+        Storage::Local => Arc::new(LocalStore::new(config.root_folder.clone())),
+        Storage::S3(s3) => Arc::new(S3Store::new(s3.clone())),
+    }
+}
+
+/// key of a chapter page. `version_id` without table prefix
+pub fn page_key(manga_id: &str, chapter_id: &str, version_id: &str, file: &str) -> String {
+    format!("mangas/{}/{}/{}/{}", manga_id, chapter_id, version_id, file)
+}
+
+/// `Cache-Control` of blobs which never change under their url
+pub const IMMUTABLE: &str = "private, max-age=31536000, immutable";
+/// `Cache-Control` of blobs which can change. clients revalidate with the `ETag`
+pub const REVALIDATE: &str = "private, no-cache";
+
+/// true if `If-None-Match` contains `etag`. weak comparison like the rfc requires
+fn none_match(req: &HttpRequest, etag: &str) -> bool {
+    req.headers()
+        .get_all(IF_NONE_MATCH)
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|v| v.trim())
+        .any(|v| v == "*" || v.trim_start_matches("W/") == etag)
+}
+
+/// like [respond] with caching headers. `etag` is quoted and makes `If-None-Match` return 304
+pub async fn respond_cached(
+    req: &HttpRequest,
+    store: &dyn BlobStore,
+    key: &str,
+    etag: Option<&str>,
+    cache_control: &'static str,
+) -> ApiResult<HttpResponse> {
+    let etag = etag.map(|v| format!("\"{}\"", v));
+    let mut res = match &etag {
+        Some(etag) if none_match(req, etag) => HttpResponse::NotModified().finish(),
+        _ => respond(req, store, key).await?,
+    };
+    if res.status().is_success() || res.status() == StatusCode::NOT_MODIFIED {
+        let headers = res.headers_mut();
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static(cache_control));
+        if let Some(etag) = etag.and_then(|v| HeaderValue::from_str(&v).ok()) {
+            headers.insert(ETAG, etag);
+        }
+    }
+    Ok(res)
+}
+
+/// sends the blob like a file. supports the `Range` header
+pub async fn respond(
+    req: &HttpRequest,
+    store: &dyn BlobStore,
+    key: &str,
+) -> ApiResult<HttpResponse> {
+    let range = req
+        .headers()
+        .get(RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(ByteRange::parse);
+    let blob = match store.get(key, range).await {
+        Ok(v) => v,
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput && range.is_some() => {
+            let size = store.stat(key).await?.map(|v| v.size).unwrap_or_default();
+            return Ok(HttpResponse::build(StatusCode::RANGE_NOT_SATISFIABLE)
+                .insert_header((CONTENT_RANGE, format!("bytes */{}", size)))
+                .finish());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let mime =
+        actix_files::file_extension_to_mime(key.rsplit_once('.').map(|v| v.1).unwrap_or_default());
+    let (mut res, len) = match blob.range {
+        Some((start, end)) => {
+            let mut res = HttpResponse::build(StatusCode::PARTIAL_CONTENT);
+            res.insert_header((
+                CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, blob.size),
+            ));
+            (res, end - start + 1)
+        }
+        None => (HttpResponse::Ok(), blob.size),
+    };
+    res.insert_header((ACCEPT_RANGES, HeaderValue::from_static("bytes")))
+        .insert_header((CONTENT_TYPE, mime.to_string()));
+    Ok(res.body(SizedStream::new(len, blob.stream)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::to_bytes;
+    use actix_web::http::header::HeaderName;
+    use actix_web::test::TestRequest;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_range() {
+        assert_eq!(
+            ByteRange::parse("bytes=0-99"),
+            Some(ByteRange::From(0, Some(99)))
+        );
+        assert_eq!(
+            ByteRange::parse("bytes=100-"),
+            Some(ByteRange::From(100, None))
+        );
+        assert_eq!(ByteRange::parse("bytes=-10"), Some(ByteRange::Suffix(10)));
+        assert_eq!(ByteRange::parse("bytes=0-1,5-6"), None);
+        assert_eq!(ByteRange::parse("items=0-1"), None);
+        assert_eq!(ByteRange::parse("bytes=a-"), None);
+    }
+
+    #[test]
+    fn resolve_range() {
+        assert_eq!(ByteRange::From(0, Some(9)).resolve(100), Some((0, 9)));
+        assert_eq!(ByteRange::From(90, None).resolve(100), Some((90, 99)));
+        // end after the last byte is clamped
+        assert_eq!(ByteRange::From(90, Some(200)).resolve(100), Some((90, 99)));
+        assert_eq!(ByteRange::From(100, None).resolve(100), None);
+        assert_eq!(ByteRange::From(5, Some(4)).resolve(100), None);
+        assert_eq!(ByteRange::Suffix(10).resolve(100), Some((90, 99)));
+        assert_eq!(ByteRange::Suffix(200).resolve(100), Some((0, 99)));
+        assert_eq!(ByteRange::Suffix(0).resolve(100), None);
+        assert_eq!(ByteRange::From(0, None).resolve(0), None);
+        assert_eq!(ByteRange::Suffix(1).resolve(0), None);
+    }
+
+    #[test]
+    fn keys() {
+        assert!(validate_key("covers/abc.jpeg").is_ok());
+        assert!(validate_key("../config.yml").is_err());
+        assert!(validate_key("covers/./abc.jpeg").is_err());
+        assert!(validate_key("/etc/passwd").is_err());
+        assert!(validate_key("covers//abc.jpeg").is_err());
+        assert!(validate_key("covers\\abc.jpeg").is_err());
+        assert!(validate_key("").is_err());
+    }
+
+    /// store in a new temporary folder with `data` at `blob.txt`
+    async fn store(data: &[u8]) -> (LocalStore, PathBuf) {
+        let root = std::env::temp_dir().join(format!("storage-{}", nanoid::nanoid!()));
+        let store = LocalStore::new(root.clone());
+        store.put("blob.txt", data.to_vec()).await.unwrap();
+        (store, root)
+    }
+
+    /// status, `Content-Range` and body of [respond]
+    async fn get(data: &[u8], range: Option<&str>) -> (StatusCode, Option<String>, Vec<u8>) {
+        let (store, root) = store(data).await;
+        let mut req = TestRequest::default();
+        if let Some(range) = range {
+            req = req.insert_header((RANGE, range));
+        }
+        let res = respond(&req.to_http_request(), &store, "blob.txt")
+            .await
+            .unwrap();
+        let status = res.status();
+        let content_range = res
+            .headers()
+            .get(CONTENT_RANGE)
+            .map(|v| v.to_str().unwrap().to_string());
+        let body = to_bytes(res.into_body()).await.unwrap().to_vec();
+        std::fs::remove_dir_all(root).unwrap();
+        (status, content_range, body)
+    }
+
+    #[actix_web::test]
+    async fn respond_whole() {
+        let (status, range, body) = get(b"0123456789", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(range, None);
+        assert_eq!(body, b"0123456789");
+    }
+
+    #[actix_web::test]
+    async fn respond_range() {
+        let (status, range, body) = get(b"0123456789", Some("bytes=2-4")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(range.as_deref(), Some("bytes 2-4/10"));
+        assert_eq!(body, b"234");
+    }
+
+    #[actix_web::test]
+    async fn respond_suffix() {
+        let (status, range, body) = get(b"0123456789", Some("bytes=-3")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(range.as_deref(), Some("bytes 7-9/10"));
+        assert_eq!(body, b"789");
+    }
+
+    #[actix_web::test]
+    async fn respond_end_after_size() {
+        let (status, range, body) = get(b"0123456789", Some("bytes=8-100")).await;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT);
+        assert_eq!(range.as_deref(), Some("bytes 8-9/10"));
+        assert_eq!(body, b"89");
+    }
+
+    #[actix_web::test]
+    async fn respond_not_satisfiable() {
+        let (status, range, body) = get(b"0123456789", Some("bytes=10-")).await;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(range.as_deref(), Some("bytes */10"));
+        assert!(body.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn respond_empty() {
+        let (status, _, body) = get(b"", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+        let (status, range, _) = get(b"", Some("bytes=-1")).await;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(range.as_deref(), Some("bytes */0"));
+    }
+
+    fn if_none_match(value: &str) -> HttpRequest {
+        TestRequest::default()
+            .insert_header((IF_NONE_MATCH, value))
+            .to_http_request()
+    }
+
+    #[test]
+    fn none_match_etags() {
+        let etag = "\"abc\"";
+        assert!(none_match(&if_none_match("\"abc\""), etag));
+        assert!(none_match(&if_none_match("W/\"abc\""), etag));
+        assert!(none_match(&if_none_match("\"old\", W/\"abc\""), etag));
+        assert!(none_match(&if_none_match("*"), etag));
+        assert!(!none_match(&if_none_match("\"abcd\""), etag));
+        assert!(!none_match(&TestRequest::default().to_http_request(), etag));
+    }
+
+    /// status, `ETag`, `Cache-Control` and body of [respond_cached]
+    async fn get_cached(
+        if_none_match_header: &str,
+    ) -> (StatusCode, Option<String>, Option<String>, Vec<u8>) {
+        let (store, root) = store(b"0123456789").await;
+        let req = if_none_match(if_none_match_header);
+        let res = respond_cached(&req, &store, "blob.txt", Some("abc"), IMMUTABLE)
+            .await
+            .unwrap();
+        let header = |name: HeaderName| {
+            res.headers()
+                .get(name)
+                .map(|v| v.to_str().unwrap().to_string())
+        };
+        let (status, etag, cache_control) = (res.status(), header(ETAG), header(CACHE_CONTROL));
+        let body = to_bytes(res.into_body()).await.unwrap().to_vec();
+        std::fs::remove_dir_all(root).unwrap();
+        (status, etag, cache_control, body)
+    }
+
+    #[actix_web::test]
+    async fn cached_not_modified() {
+        for header in ["\"abc\"", "W/\"abc\"", "*"] {
+            let (status, etag, cache_control, body) = get_cached(header).await;
+            assert_eq!(status, StatusCode::NOT_MODIFIED);
+            assert_eq!(etag.as_deref(), Some("\"abc\""));
+            assert_eq!(cache_control.as_deref(), Some(IMMUTABLE));
+            assert!(body.is_empty());
+        }
+    }
+
+    #[actix_web::test]
+    async fn cached_changed() {
+        let (status, etag, cache_control, body) = get_cached("\"old\"").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(etag.as_deref(), Some("\"abc\""));
+        assert_eq!(cache_control.as_deref(), Some(IMMUTABLE));
+        assert_eq!(body, b"0123456789");
+    }
+}
@@ -0,0 +1,241 @@
+use crate::env::config::S3Storage;
+use crate::services::storage::{validate_key, Blob, BlobStore, ByteRange, Stat};
+use chrono::Utc;
+use futures_util::future::BoxFuture;
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, CONTENT_LENGTH, CONTENT_RANGE, ETAG};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use std::io;
+
+/// sha256 of an empty body
+const EMPTY_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// S3 compatible object storage like MinIO. uses path style urls: `endpoint/bucket/key`
+pub struct S3Store {
+    config: S3Storage,
+    client: Client,
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts every key size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// percent encoding of aws. `/` is kept in paths
+fn uri_encode(s: &str, path: bool) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            b'/' if path => "/".to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn other(e: impl ToString) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+impl S3Store {
+    pub fn new(config: S3Storage) -> Self {
+        Self {
+            config,
+            client: Client::new(),
+        }
+    }
+
+    /// signs the request with aws signature version 4.
+    /// `headers` have to be lowercase and are signed as well
+    fn request(
+        &self,
+        method: Method,
+        key: &str,
+        headers: Vec<(&str, String)>,
+        body: Vec<u8>,
+    ) -> io::Result<RequestBuilder> {
+        validate_key(key)?;
+        let url = Url::parse(&self.config.endpoint)
+            .and_then(|v| {
+                v.join(&format!(
+                    "/{}/{}",
+                    uri_encode(&self.config.bucket, false),
+                    uri_encode(key, true)
+                ))
+            })
+            .map_err(other)?;
+        let path = url.path().to_string();
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(other("S3 endpoint has no host")),
+        };
+        let now = Utc::now();
+        let date = now.format("%Y%m%d").to_string();
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let payload = match body.is_empty() {
+            true => EMPTY_SHA256.to_string(),
+            false => hex_sha256(&body),
+        };
+
+        let mut signed = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload.clone()),
+            ("x-amz-date", timestamp.clone()),
+        ];
+        signed.extend(headers);
+        signed.sort_by(|a, b| a.0.cmp(b.0));
+        let canonical_headers: String = signed
+            .iter()
+            .map(|(k, v)| format!("{}:{}\n", k, v.trim()))
+            .collect();
+        let signed_headers = signed.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "{}\n{}\n\n{}\n{}\n{}",
+            method, path, canonical_headers, signed_headers, payload
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex_sha256(canonical_request.as_bytes())
+        );
+        let key = [self.config.region.as_str(), "s3", "aws4_request"]
+            .into_iter()
+            .fold(
+                hmac(format!("AWS4{}", self.config.secret_key).as_bytes(), &date),
+                |key, v| hmac(&key, v),
+            );
+        let signature = hmac(&key, &string_to_sign)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+
+        let mut req = self.client.request(method, url).header(
+            "authorization",
+            format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.config.access_key, scope, signed_headers, signature
+            ),
+        );
+        for (k, v) in signed.into_iter().filter(|(k, _)| *k != "host") {
+            req = req.header(k, v);
+        }
+        Ok(req.body(body))
+    }
+
+    async fn send(&self, req: RequestBuilder) -> io::Result<Response> {
+        let res = req.send().await.map_err(other)?;
+        match res.status() {
+            StatusCode::NOT_FOUND => Err(io::ErrorKind::NotFound.into()),
+            StatusCode::RANGE_NOT_SATISFIABLE => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Range not satisfiable",
+            )),
+            v if v.is_success() => Ok(res),
+            v => Err(other(format!(
+                "S3 responded with {}: {}",
+                v,
+                res.text().await.unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+/// (start, end, size) of `bytes start-end/size`
+fn content_range(v: &str) -> Option<(u64, u64, u64)> {
+    let (range, size) = v.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    Some((start.parse().ok()?, end.parse().ok()?, size.parse().ok()?))
+}
+
+fn header_u64(res: &Response, name: HeaderName) -> Option<u64> {
+    res.headers().get(name)?.to_str().ok()?.parse().ok()
+}
+
+impl BlobStore for S3Store {
+    fn get<'a>(
+        &'a self,
+        key: &'a str,
+        range: Option<ByteRange>,
+    ) -> BoxFuture<'a, io::Result<Blob>> {
+        Box::pin(async move {
+            let headers = range
+                .map(|v| vec![("range", v.header())])
+                .unwrap_or_default();
+            let res = self
+                .send(self.request(Method::GET, key, headers, vec![])?)
+                .await?;
+            let (size, range) = match res.status() {
+                StatusCode::PARTIAL_CONTENT => {
+                    let (start, end, size) = res
+                        .headers()
+                        .get(CONTENT_RANGE)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(content_range)
+                        .ok_or(other("S3 sent no valid Content-Range"))?;
+                    (size, Some((start, end)))
+                }
+                _ => (
+                    header_u64(&res, CONTENT_LENGTH).ok_or(other("S3 sent no Content-Length"))?,
+                    None,
+                ),
+            };
+            Ok(Blob {
+                size,
+                range,
+                stream: Box::pin(res.bytes_stream().map(|v| v.map_err(other))),
+            })
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, data: Vec<u8>) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(async move {
+            self.send(self.request(Method::PUT, key, vec![], data)?)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(async move {
+            match self
+                .send(self.request(Method::DELETE, key, vec![], vec![])?)
+                .await
+            {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                res => res.map(|_| ()),
+            }
+        })
+    }
+
+    fn stat<'a>(&'a self, key: &'a str) -> BoxFuture<'a, io::Result<Option<Stat>>> {
+        Box::pin(async move {
+            match self
+                .send(self.request(Method::HEAD, key, vec![], vec![])?)
+                .await
+            {
+                Ok(res) => Ok(header_u64(&res, CONTENT_LENGTH).map(|size| Stat {
+                    size,
+                    version: res
+                        .headers()
+                        .get(ETAG)
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.trim_start_matches("W/").trim_matches('"').to_string())
+                        .unwrap_or_else(|| size.to_string()),
+                })),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use api_structure::reader::ProgressUpdate;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// time between getting a ticket and opening the websocket
+const TICKET_TTL: Duration = Duration::from_secs(30);
+
+/// a progress change of a user
+#[derive(Clone)]
+pub struct ProgressChange {
+    pub user: String,
+    /// session which made the change
+    pub session: Option<String>,
+    pub update: ProgressUpdate,
+}
+
+/// forwards progress changes to the open websocket sessions
+pub struct ProgressHub {
+    sender: broadcast::Sender<ProgressChange>,
+    /// ticket => (user, issued)
+    tickets: Mutex<HashMap<String, (String, Instant)>>,
+}
+
+impl Default for ProgressHub {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(256).0,
+            tickets: Default::default(),
+        }
+    }
+}
+
+impl ProgressHub {
+    pub fn subscribe(&self) -> broadcast::Receiver<ProgressChange> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, change: ProgressChange) {
+        // fails if nobody is listening
+        let _ = self.sender.send(change);
+    }
+
+    /// the websocket can't send the bearer token, so it is exchanged for a ticket.
+    /// tickets are short lived, so it doesn't matter when they end up in a log
+    pub fn issue_ticket(&self, user: String) -> String {
+        let ticket = nanoid::nanoid!(32);
+        let mut tickets = self.tickets.lock().unwrap();
+        tickets.retain(|_, (_, issued)| issued.elapsed() < TICKET_TTL);
+        tickets.insert(ticket.clone(), (user, Instant::now()));
+        ticket
+    }
+
+    /// user of the ticket. a ticket can only be redeemed once
+    pub fn redeem_ticket(&self, ticket: &str) -> Option<String> {
+        let (user, issued) = self.tickets.lock().unwrap().remove(ticket)?;
+        (issued.elapsed() < TICKET_TTL).then_some(user)
+    }
+}
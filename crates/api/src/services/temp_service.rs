@@ -0,0 +1,65 @@
+use crate::env::config::Config;
+use crate::errors::ApiResult;
+use crate::services::db::temp_upload::TempUploadDBService;
+use log::{error, info};
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use surrealdb::engine::local::Db;
+use surrealdb::Surreal;
+
+/// how often expired uploads are removed
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// deletes uploads which weren't claimed within the ttl
+pub async fn temp_service(db: Arc<Surreal<Db>>, config: Config) {
+    let worker = Worker {
+        uploads: TempUploadDBService::new(db),
+        folder: config.root_folder.join("temp"),
+        ttl: config.temp_uploads.ttl,
+    };
+    let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+    loop {
+        interval.tick().await;
+        match worker.cleanup().await {
+            Ok(0) => {}
+            Ok(removed) => info!("Removed {} expired uploads", removed),
+            Err(e) => error!("Failed to remove expired uploads: {}", e),
+        }
+    }
+}
+
+struct Worker {
+    uploads: TempUploadDBService,
+    folder: PathBuf,
+    /// seconds
+    ttl: u64,
+}
+
+impl Worker {
+    /// returns the number of removed files
+    async fn cleanup(&self) -> ApiResult<usize> {
+        let mut removed = 0;
+        for name in self.uploads.remove_expired(self.ttl).await? {
+            if fs::remove_file(self.folder.join(name)).is_ok() {
+                removed += 1;
+            }
+        }
+        // files which were uploaded before uploads were tracked
+        let tracked: HashSet<String> = self.uploads.names().await?.into_iter().collect();
+        let expiry = SystemTime::now() - Duration::from_secs(self.ttl);
+        for entry in fs::read_dir(&self.folder)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() || tracked.contains(&name) || metadata.modified()? > expiry {
+                continue;
+            }
+            fs::remove_file(entry.path())?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,194 @@
+use crate::env::config::{Config, TranslationBackend};
+use crate::errors::{ApiError, ApiResult};
+use crate::services::db::chapter_version::ChapterVersionDBService;
+use crate::services::db::page::PageDBService;
+use crate::services::db::translation::{
+    PageTranslation, Translation, TranslationDBService, TranslationResponse,
+};
+use crate::services::db::translation_job::{TranslationJob, TranslationJobDBService};
+use crate::services::db::version::VersionDBService;
+use crate::services::storage::{page_key, BlobStore};
+use api_structure::translation::TranslationJobStatus;
+use log::{error, info};
+use reqwest::multipart::{Form, Part};
+use std::sync::Arc;
+use std::time::Duration;
+use surrealdb::engine::local::Db;
+use surrealdb::Surreal;
+use surrealdb_extras::RecordData;
+use tokio::sync::Notify;
+
+/// a backend which takes longer for a single page fails the job
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// wakes the translation worker after a job was queued
+#[derive(Default)]
+pub struct TranslationQueue {
+    notify: Notify,
+}
+
+impl TranslationQueue {
+    pub fn wake(&self) {
+        self.notify.notify_one();
+    }
+}
+
+/// works through the queued translation jobs one after another
+pub async fn translation_service(
+    db: Arc<Surreal<Db>>,
+    config: Config,
+    store: Arc<dyn BlobStore>,
+    queue: Arc<TranslationQueue>,
+) {
+    let worker = Worker {
+        jobs: TranslationJobDBService::new(db.clone()),
+        versions: VersionDBService::new(db.clone()),
+        cvs: ChapterVersionDBService::new(db.clone()),
+        pages: PageDBService::new(db.clone()),
+        translations: TranslationDBService::new(db),
+        client: reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to build the http client"),
+        config,
+        store,
+    };
+    if let Err(e) = worker.jobs.requeue_running().await {
+        error!("Failed to requeue translation jobs: {}", e);
+    }
+    loop {
+        match worker.jobs.next().await {
+            Ok(Some(job)) => worker.run(job).await,
+            Ok(None) => queue.notify.notified().await,
+            Err(e) => {
+                error!("Failed to load translation jobs: {}", e);
+                queue.notify.notified().await
+            }
+        }
+    }
+}
+
+struct Worker {
+    jobs: TranslationJobDBService,
+    versions: VersionDBService,
+    cvs: ChapterVersionDBService,
+    pages: PageDBService,
+    translations: TranslationDBService,
+    client: reqwest::Client,
+    config: Config,
+    store: Arc<dyn BlobStore>,
+}
+
+impl Worker {
+    async fn run(&self, job: RecordData<TranslationJob>) {
+        let job_id = job.id.id().to_string();
+        info!(
+            "Translating {} into {}",
+            job.data.version_id, job.data.language
+        );
+        let res = match self
+            .jobs
+            .set_status(&job_id, TranslationJobStatus::Running, None)
+            .await
+        {
+            Ok(_) => self.translate(&job_id, &job.data).await,
+            Err(e) => Err(e),
+        };
+        let (status, err) = match res {
+            Ok(_) => (TranslationJobStatus::Done, None),
+            Err(e) => {
+                error!("Translation job {} failed: {}", job_id, e);
+                (TranslationJobStatus::Failed, Some(e.to_string()))
+            }
+        };
+        if let Err(e) = self.jobs.set_status(&job_id, status, err).await {
+            error!("Failed to finish translation job {}: {}", job_id, e);
+        }
+    }
+
+    async fn translate(&self, job_id: &str, job: &TranslationJob) -> ApiResult<()> {
+        let backend = self
+            .config
+            .translation_backend
+            .as_ref()
+            .ok_or(ApiError::invalid_input("No translation backend configured"))?;
+        let opts = self.versions.get(&job.version_id).await?.translate_opts;
+        let chapter_id = job.chapter.thing.id().to_string();
+        let version_id = job
+            .version_id
+            .split_once(':')
+            .map(|(_, id)| id)
+            .unwrap_or(&job.version_id);
+        let pages = self.cvs.get(&job.connection.thing.id().to_string()).await?;
+        let total = pages.len() as u32;
+        self.jobs.set_progress(job_id, 0, total).await?;
+        for (done, page) in pages.into_iter().enumerate() {
+            let page_id = page.thing.id().to_string();
+            let page = self.pages.get(page).await?;
+            let file_name = format!("{}.{}", page.page, page.ext);
+            let image = self
+                .store
+                .read(&page_key(
+                    &job.manga_id,
+                    &chapter_id,
+                    version_id,
+                    &file_name,
+                ))
+                .await?;
+            let areas = self
+                .request(backend, image, file_name, &job.language, opts.clone())
+                .await?;
+            self.translations
+                .delete_source(&page_id, &backend.name, &job.language)
+                .await?;
+            let found = !areas.is_empty();
+            for area in areas {
+                self.translations
+                    .add(PageTranslation::from_tool(
+                        &page_id,
+                        &backend.name,
+                        &job.language,
+                        area,
+                    ))
+                    .await?;
+            }
+            if found && !page.translation {
+                self.pages.set_translation(&page_id, true).await?;
+            }
+            self.jobs
+                .set_progress(job_id, done as u32 + 1, total)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// sends a single page to the backend
+    async fn request(
+        &self,
+        backend: &TranslationBackend,
+        image: Vec<u8>,
+        file_name: String,
+        language: &str,
+        opts: Option<String>,
+    ) -> ApiResult<Vec<Translation>> {
+        let mut form = Form::new()
+            .part("image", Part::bytes(image).file_name(file_name))
+            .text("language", language.to_string());
+        if let Some(opts) = opts {
+            form = form.text("options", opts);
+        }
+        let mut res: TranslationResponse = self
+            .client
+            .post(&backend.url)
+            .multipart(form)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(match res.images.is_empty() {
+            true => vec![],
+            false => res.images.remove(0),
+        })
+    }
+}
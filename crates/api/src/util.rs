@@ -7,3 +7,10 @@ pub fn create_folders(root: &Path, paths: Vec<&str>) -> std::io::Result<()> {
     }
     Ok(())
 }
+
+/// most entries a paged route returns at once
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+pub fn page_size(limit: u32) -> u32 {
+    limit.clamp(1, MAX_PAGE_SIZE)
+}
@@ -0,0 +1,6 @@
+#!/bin/sh
+# downloads the redoc bundle that is compiled into the binary and served at /api/redoc.standalone.js
+set -e
+VERSION=v2.1.5
+cd "$(dirname "$0")/../src/routes/openapi"
+curl -fsSL -o redoc.standalone.js "https://cdn.redoc.ly/redoc/$VERSION/bundles/redoc.standalone.js"
@@ -0,0 +1,113 @@
+use crate::auth::role::Role;
+use crate::error::{ApiErr, ApiErrorType};
+use crate::info::Visibility;
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum AuditAction {
+    Activate,
+    RoleChange,
+    VisibilityChange,
+    MangaEdit,
+    MangaRollback,
+    RelationAdd,
+    RelationDelete,
+    TranslationAreaAdd,
+    TranslationAreaEdit,
+    TranslationAreaDelete,
+    TranslationQueue,
+    CommentModerate,
+}
+
+impl TryFrom<u32> for AuditAction {
+    type Error = ApiErr;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Activate),
+            1 => Ok(Self::RoleChange),
+            2 => Ok(Self::VisibilityChange),
+            3 => Ok(Self::MangaEdit),
+            4 => Ok(Self::MangaRollback),
+            5 => Ok(Self::RelationAdd),
+            6 => Ok(Self::RelationDelete),
+            7 => Ok(Self::TranslationAreaAdd),
+            8 => Ok(Self::TranslationAreaEdit),
+            9 => Ok(Self::TranslationAreaDelete),
+            10 => Ok(Self::TranslationQueue),
+            11 => Ok(Self::CommentModerate),
+            _ => Err(ApiErr {
+                message: Some("unknown audit action".to_string()),
+                cause: None,
+                err_type: ApiErrorType::InternalError,
+            }),
+        }
+    }
+}
+
+/// every filter is optional. newest entries first
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AuditLogRequest {
+    /// username
+    pub actor: Option<String>,
+    pub action: Option<AuditAction>,
+    /// unix timestamp in millis
+    pub from: Option<i64>,
+    /// unix timestamp in millis
+    pub to: Option<i64>,
+    /// at most 100
+    pub limit: u32,
+    /// starts with 1
+    pub page: u32,
+}
+
+impl RequestImpl for AuditLogRequest {
+    const ROUTE: &'static str = "admin/audit_log";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AuditEntry {
+    /// username
+    pub actor: String,
+    pub action: AuditAction,
+    /// record id. e.g. mangas:abc
+    pub target: String,
+    /// json
+    pub before: Option<String>,
+    /// json
+    pub after: Option<String>,
+    /// address of the connection
+    pub ip: Option<String>,
+    /// `X-Forwarded-For` or `Forwarded` header. set by proxies, but also by clients
+    pub forwarded_for: Option<String>,
+    pub created: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SetRoleRequest {
+    pub user_id: String,
+    pub role: Role,
+}
+
+impl RequestImpl for SetRoleRequest {
+    const ROUTE: &'static str = "user/role";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SetVisibilityRequest {
+    pub manga_id: String,
+    pub visibility: Visibility,
+}
+
+impl RequestImpl for SetVisibilityRequest {
+    const ROUTE: &'static str = "manga/visibility";
+    const AUTH: bool = true;
+}
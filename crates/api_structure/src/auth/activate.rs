@@ -2,6 +2,7 @@ use crate::RequestImpl;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ActivateRequest {
     pub key: String,
 }
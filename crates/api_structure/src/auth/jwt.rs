@@ -5,6 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Claim {
     pub id: String,
     pub role: Role,
@@ -40,12 +41,14 @@ impl Claim {
 }
 
 #[derive(Debug, Deserialize, Serialize, Copy, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum JwtType {
     AccessToken,
     RefreshToken,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 /// Response
 pub struct JWTs {
     pub access_token: String,
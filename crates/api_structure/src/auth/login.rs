@@ -2,18 +2,21 @@ use crate::RequestImpl;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct LoginWithUsernameAndPassword {
     pub username: String,
     pub password: String,
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct LoginWithEmailAndPassword {
     pub email: String,
     pub password: String,
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum LoginRequest {
     Username(LoginWithUsernameAndPassword),
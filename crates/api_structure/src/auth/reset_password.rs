@@ -2,6 +2,7 @@ use crate::RequestImpl;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ResetPasswordRequest {
     pub ident: String,
     pub email: bool,
@@ -15,6 +16,7 @@ impl RequestImpl for ResetPasswordRequest {
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct RequestResetPasswordRequest {
     pub ident: String,
     pub email: bool,
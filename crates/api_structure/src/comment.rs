@@ -0,0 +1,101 @@
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum CommentTarget {
+    Manga(String),
+    Chapter(String),
+}
+
+/// top level comments are newest first, replies oldest first
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CommentsRequest {
+    pub target: CommentTarget,
+    /// lists the replies of a comment
+    pub parent: Option<String>,
+    /// at most 100
+    pub limit: u32,
+    /// starts with 1
+    pub page: u32,
+}
+
+impl RequestImpl for CommentsRequest {
+    const ROUTE: &'static str = "comments";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Comment {
+    pub comment_id: String,
+    pub target: CommentTarget,
+    pub parent: Option<String>,
+    pub author_id: String,
+    pub author_name: Option<String>,
+    /// none if the comment was deleted or hidden
+    pub text: Option<String>,
+    pub spoiler: bool,
+    pub deleted: bool,
+    /// hidden by a moderator
+    pub hidden: bool,
+    pub edited: bool,
+    pub replies: u32,
+    pub created: String,
+}
+
+/// returns the id of the comment
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AddCommentRequest {
+    pub target: CommentTarget,
+    pub parent: Option<String>,
+    pub text: String,
+    pub spoiler: bool,
+}
+
+impl RequestImpl for AddCommentRequest {
+    const ROUTE: &'static str = "comment/add";
+    const AUTH: bool = true;
+}
+
+/// only the author can edit a comment
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EditCommentRequest {
+    pub comment_id: String,
+    pub text: String,
+    pub spoiler: bool,
+}
+
+impl RequestImpl for EditCommentRequest {
+    const ROUTE: &'static str = "comment/edit";
+    const AUTH: bool = true;
+}
+
+/// comments with replies are only marked as deleted
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeleteCommentRequest {
+    pub comment_id: String,
+}
+
+impl RequestImpl for DeleteCommentRequest {
+    const ROUTE: &'static str = "comment/delete";
+    const AUTH: bool = true;
+}
+
+/// soft delete and hide. unchanged if none
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ModerateCommentRequest {
+    pub comment_id: String,
+    pub deleted: Option<bool>,
+    pub hidden: Option<bool>,
+}
+
+impl RequestImpl for ModerateCommentRequest {
+    const ROUTE: &'static str = "comment/moderate";
+    const AUTH: bool = true;
+}
@@ -3,6 +3,7 @@ use std::fmt;
 use std::fmt::{Display, Formatter};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ApiErr {
     pub message: Option<String>,
     pub cause: Option<String>,
@@ -26,6 +27,7 @@ impl ApiErr {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ClientError {
     pub message: String,
     pub cause: Option<String>,
@@ -33,6 +35,7 @@ pub struct ClientError {
 }
 
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ApiErrorType {
     InternalError,
     NotFoundError,
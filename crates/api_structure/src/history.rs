@@ -0,0 +1,64 @@
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// newest first
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReadingHistoryRequest {
+    pub limit: u32,
+    /// starts with 1
+    pub page: u32,
+}
+
+impl RequestImpl for ReadingHistoryRequest {
+    const ROUTE: &'static str = "me/history";
+    const AUTH: bool = true;
+}
+
+/// entries which were read without a longer break
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HistorySession {
+    pub start: String,
+    pub end: String,
+    /// newest first
+    pub entries: Vec<HistoryEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct HistoryEntry {
+    pub entry_id: String,
+    pub manga_id: String,
+    pub titles: HashMap<String, Vec<String>>,
+    pub chapter_id: String,
+    pub chapter: f64,
+    /// 0.0 - 1.0
+    pub progress: f64,
+    pub updated: String,
+}
+
+/// deletes the progress of the chapter. the manga is removed from the reading stats
+/// if no other chapter of it is left
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeleteHistoryEntryRequest {
+    pub entry_id: String,
+}
+
+impl RequestImpl for DeleteHistoryEntryRequest {
+    const ROUTE: &'static str = "me/history/delete";
+    const AUTH: bool = true;
+}
+
+/// deletes the progress of every chapter and removes the mangas from the reading stats.
+/// the daily totals are kept
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClearHistoryRequest;
+
+impl RequestImpl for ClearHistoryRequest {
+    const ROUTE: &'static str = "me/history/clear";
+    const AUTH: bool = true;
+}
@@ -3,6 +3,7 @@ use crate::RequestImpl;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct HomeResponse {
     pub trending: Vec<SearchResponse>,
     pub newest: Vec<SearchResponse>,
@@ -10,6 +11,8 @@ pub struct HomeResponse {
     pub favorites: Vec<SearchResponse>,
     pub reading: Vec<SearchResponse>,
     pub random: Vec<SearchResponse>,
+    /// based on the tags and kinds of the mangas the user read and favorited
+    pub recommended: Vec<SearchResponse>,
 }
 
 impl RequestImpl for HomeResponse {
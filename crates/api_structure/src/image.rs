@@ -2,6 +2,7 @@ use crate::RequestImpl;
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MangaCoverRequest {
     pub manga_id: String,
     pub file_ext: String,
@@ -12,7 +13,15 @@ impl RequestImpl for MangaCoverRequest {
     const AUTH: bool = true;
 }
 
+impl MangaCoverRequest {
+    /// path of the cacheable GET route
+    pub fn path(&self) -> String {
+        format!("cover/{}.{}", self.manga_id, self.file_ext)
+    }
+}
+
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MangaReaderImageRequest {
     pub manga_id: String,
     pub chapter_id: String,
@@ -25,3 +34,56 @@ impl RequestImpl for MangaReaderImageRequest {
     const ROUTE: &'static str = "chapter_page";
     const AUTH: bool = true;
 }
+
+impl MangaReaderImageRequest {
+    /// path of the cacheable GET route. the version id is sent without table prefix
+    pub fn path(&self) -> String {
+        format!(
+            "chapter_page/{}/{}/{}/{}.{}",
+            self.manga_id,
+            self.chapter_id,
+            self.version_id
+                .strip_prefix("chapter_versions:")
+                .unwrap_or(&self.version_id),
+            self.page,
+            self.file_ext
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ImageResource {
+    Cover(MangaCoverRequest),
+    Page(MangaReaderImageRequest),
+}
+
+impl ImageResource {
+    pub fn path(&self) -> String {
+        match self {
+            ImageResource::Cover(v) => v.path(),
+            ImageResource::Page(v) => v.path(),
+        }
+    }
+}
+
+/// urls for clients which can't send a Bearer header, e.g. `<img>` tags
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SignImageUrlsRequest {
+    pub images: Vec<ImageResource>,
+}
+
+impl RequestImpl for SignImageUrlsRequest {
+    const ROUTE: &'static str = "sign_image_urls";
+    const AUTH: bool = true;
+}
+
+#[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SignImageUrlsResponse {
+    /// relative to the api url, in the same order as the request
+    pub urls: Vec<String>,
+    /// unix timestamp in seconds
+    pub expires: u64,
+}
@@ -1,15 +1,18 @@
 use crate::error::ApiErr;
+use crate::rating::RatingSummary;
 use crate::search::Status;
 use crate::{ApiErrorType, RequestImpl};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MangaInfoRequest {
     pub manga_id: String,
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MangaInfoResponse {
     pub manga_id: String,
     pub titles: HashMap<String, Vec<String>>,
@@ -26,7 +29,8 @@ pub struct MangaInfoResponse {
     pub cover_ext: String,
     pub chapters: Vec<Chapter>,
     pub sources: Vec<ExternalSite>,
-    pub relations: Vec<(String, String)>,
+    pub relations: Vec<MangaRelation>,
+    pub rating: RatingSummary,
     pub scraper: bool,
     pub favorite: bool,
     /// manga_id
@@ -38,13 +42,111 @@ impl RequestImpl for MangaInfoRequest {
     const AUTH: bool = true;
 }
 
+/// related manga shown in the relations strip
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MangaRelation {
+    pub manga_id: String,
+    pub kind: RelationKind,
+    pub titles: HashMap<String, Vec<String>>,
+    pub cover: u32,
+    /// empty if the manga has no cover
+    pub cover_ext: String,
+}
+
+/// how the related manga relates to the current one
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum RelationKind {
+    Sequel,
+    Prequel,
+    SpinOff,
+    /// inverse of [RelationKind::SpinOff]
+    Parent,
+    SideStory,
+    /// inverse of [RelationKind::SideStory]
+    MainStory,
+    AlternateVersion,
+    SameFranchise,
+    /// relations from before kinds existed
+    Related,
+}
+
+impl RelationKind {
+    /// kind of the link from the other side
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::Sequel => Self::Prequel,
+            Self::Prequel => Self::Sequel,
+            Self::SpinOff => Self::Parent,
+            Self::Parent => Self::SpinOff,
+            Self::SideStory => Self::MainStory,
+            Self::MainStory => Self::SideStory,
+            Self::AlternateVersion => Self::AlternateVersion,
+            Self::SameFranchise => Self::SameFranchise,
+            Self::Related => Self::Related,
+        }
+    }
+}
+
+impl TryFrom<u64> for RelationKind {
+    type Error = ApiErr;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Sequel),
+            1 => Ok(Self::Prequel),
+            2 => Ok(Self::SpinOff),
+            3 => Ok(Self::Parent),
+            4 => Ok(Self::SideStory),
+            5 => Ok(Self::MainStory),
+            6 => Ok(Self::AlternateVersion),
+            7 => Ok(Self::SameFranchise),
+            8 => Ok(Self::Related),
+            _ => Err(ApiErr {
+                message: Some("unknown relation kind".to_string()),
+                cause: None,
+                err_type: ApiErrorType::InternalError,
+            }),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AddRelationRequest {
+    pub manga_id: String,
+    pub related_id: String,
+    /// `related_id` is the [RelationKind] of `manga_id`
+    pub kind: RelationKind,
+}
+
+impl RequestImpl for AddRelationRequest {
+    const ROUTE: &'static str = "relation/add";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeleteRelationRequest {
+    pub manga_id: String,
+    pub related_id: String,
+}
+
+impl RequestImpl for DeleteRelationRequest {
+    const ROUTE: &'static str = "relation/delete";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ExternalSite {
     pub url: String,
     pub icon_uri: String,
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Chapter {
     pub titles: Vec<String>,
     pub chapter: f64,
@@ -54,13 +156,15 @@ pub struct Chapter {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Tag {
     pub tag: String,
     pub description: Option<String>,
     pub sex: u64,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Visibility {
     /// Everyone
     Visible,
@@ -86,3 +190,68 @@ impl TryFrom<u64> for Visibility {
         }
     }
 }
+
+/// fields that are `None` stay unchanged
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EditMangaRequest {
+    pub manga_id: String,
+    pub titles: Option<HashMap<String, Vec<String>>>,
+    /// empty string removes the description
+    pub description: Option<String>,
+    pub tags: Option<Vec<Tag>>,
+    pub status: Option<Status>,
+    /// usernames
+    pub authors: Option<Vec<String>>,
+    /// usernames
+    pub artists: Option<Vec<String>>,
+    pub sources: Option<Vec<String>>,
+}
+
+impl RequestImpl for EditMangaRequest {
+    const ROUTE: &'static str = "manga/edit";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MangaHistoryRequest {
+    pub manga_id: String,
+}
+
+impl RequestImpl for MangaHistoryRequest {
+    const ROUTE: &'static str = "manga/history";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MangaRevision {
+    pub revision_id: String,
+    /// username
+    pub editor: String,
+    pub created: String,
+    pub changes: Vec<MangaFieldChange>,
+}
+
+/// values are json in the format they are stored in
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MangaFieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// restores the state directly after `revision_id`
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RollbackMangaRequest {
+    pub manga_id: String,
+    pub revision_id: String,
+}
+
+impl RequestImpl for RollbackMangaRequest {
+    const ROUTE: &'static str = "manga/rollback";
+    const AUTH: bool = true;
+}
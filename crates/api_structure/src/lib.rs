@@ -1,13 +1,22 @@
+pub mod audit;
 pub mod auth;
+pub mod comment;
 pub mod error;
 pub mod fonts;
+pub mod history;
 pub mod home;
 pub mod image;
 pub mod info;
+pub mod library;
+pub mod notification;
+pub mod rating;
 pub mod reader;
 pub mod scrape;
 pub mod scraper;
 pub mod search;
+pub mod stats;
+pub mod translation;
+pub mod user;
 
 use crate::error::{ApiErr, ApiErrorType};
 use std::collections::HashMap;
@@ -0,0 +1,41 @@
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum LibraryFormat {
+    /// xml export of myanimelist.net
+    MyAnimeList,
+    /// json of the MediaListCollection query of anilist.co
+    AniList,
+}
+
+/// the file is uploaded as multipart field `file[]` to `library/import`.
+/// the format is detected from the content
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ImportLibraryResponse {
+    pub imported: u32,
+    pub unmatched: Vec<UnmatchedEntry>,
+}
+
+/// entry of the import which couldn't be found on the server
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UnmatchedEntry {
+    pub title: String,
+    /// url of the entry on the tracking site
+    pub url: Option<String>,
+}
+
+/// returns the file as attachment
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ExportLibraryRequest {
+    pub format: LibraryFormat,
+}
+
+impl RequestImpl for ExportLibraryRequest {
+    const ROUTE: &'static str = "library/export";
+    const AUTH: bool = true;
+}
@@ -0,0 +1,110 @@
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// newest first
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NotificationsRequest {
+    pub unread_only: bool,
+    /// at most 100
+    pub limit: u32,
+    /// starts with 1
+    pub page: u32,
+}
+
+impl RequestImpl for NotificationsRequest {
+    const ROUTE: &'static str = "notifications";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Notification {
+    pub notification_id: String,
+    pub manga_id: String,
+    pub titles: HashMap<String, Vec<String>>,
+    pub chapter_id: String,
+    pub chapter: f64,
+    pub read: bool,
+    pub created: String,
+}
+
+/// marks all notifications as read if `ids` is none
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MarkNotificationsReadRequest {
+    pub ids: Option<Vec<String>>,
+}
+
+impl RequestImpl for MarkNotificationsReadRequest {
+    const ROUTE: &'static str = "notifications/read";
+    const AUTH: bool = true;
+}
+
+/// returns [NotificationSettings]
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NotificationSettingsRequest;
+
+impl RequestImpl for NotificationSettingsRequest {
+    const ROUTE: &'static str = "notifications/settings";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NotificationSettings {
+    /// disables all notifications
+    pub muted: bool,
+    /// notify about mangas in the favorites list
+    pub favorites: bool,
+    /// notify about mangas with reading progress
+    pub reading: bool,
+    pub muted_mangas: Vec<String>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            muted: false,
+            favorites: true,
+            reading: true,
+            muted_mangas: vec![],
+        }
+    }
+}
+
+impl RequestImpl for NotificationSettings {
+    const ROUTE: &'static str = "notifications/settings/edit";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MuteMangaRequest {
+    pub manga_id: String,
+    pub muted: bool,
+}
+
+impl RequestImpl for MuteMangaRequest {
+    const ROUTE: &'static str = "notifications/mute";
+    const AUTH: bool = true;
+}
+
+/// server sent events. every event is a json encoded [NotificationEvent]
+pub struct NotificationStreamRequest;
+
+impl RequestImpl for NotificationStreamRequest {
+    const ROUTE: &'static str = "notifications/stream";
+    const AUTH: bool = true;
+    const METHOD: &'static str = "GET";
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NotificationEvent {
+    pub unread: u32,
+    /// none for the initial event
+    pub notification: Option<Notification>,
+}
@@ -0,0 +1,71 @@
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+
+pub const MIN_SCORE: u32 = 1;
+pub const MAX_SCORE: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RatingSummary {
+    /// none without ratings
+    pub average: Option<f64>,
+    pub count: u32,
+    /// number of ratings per score. index 0 is score 1
+    pub distribution: Vec<u32>,
+    /// score of the requesting user
+    pub own: Option<u32>,
+}
+
+/// creates or replaces the rating of the user
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RateMangaRequest {
+    pub manga_id: String,
+    /// 1 - 10
+    pub score: u32,
+    pub review: Option<String>,
+}
+
+impl RequestImpl for RateMangaRequest {
+    const ROUTE: &'static str = "rating/set";
+    const AUTH: bool = true;
+}
+
+/// deletes the rating of the user
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeleteRatingRequest {
+    pub manga_id: String,
+}
+
+impl RequestImpl for DeleteRatingRequest {
+    const ROUTE: &'static str = "rating/delete";
+    const AUTH: bool = true;
+}
+
+/// newest first
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RatingsRequest {
+    pub manga_id: String,
+    /// skips ratings without review
+    pub reviews_only: bool,
+    pub limit: u32,
+    /// starts with 1
+    pub page: u32,
+}
+
+impl RequestImpl for RatingsRequest {
+    const ROUTE: &'static str = "ratings";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Rating {
+    pub user_id: String,
+    pub user_name: Option<String>,
+    pub score: u32,
+    pub review: Option<String>,
+    pub updated: String,
+}
@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MangaReaderResponse {
     pub manga_id: String,
     pub titles: HashMap<String, Vec<String>>,
@@ -74,6 +75,7 @@ impl MangaReaderResponse {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ReaderChapter {
     pub chapter_id: String,
     pub titles: Vec<String>,
@@ -85,6 +87,7 @@ pub struct ReaderChapter {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct MangaReaderRequest {
     pub manga_id: String,
     pub chapter_id: Option<String>,
@@ -95,6 +98,64 @@ impl RequestImpl for MangaReaderRequest {
     const AUTH: bool = true;
 }
 
+/// saves how far a chapter was read
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SetProgressRequest {
+    pub manga_id: String,
+    pub chapter_id: String,
+    pub version_id: String,
+    /// 0.0 - 1.0
+    pub progress: f64,
+    /// [SyncMessage::Hello] of the sender. the change isnt pushed back to it
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+impl RequestImpl for SetProgressRequest {
+    const ROUTE: &'static str = "progress";
+    const AUTH: bool = true;
+}
+
+/// single use ticket for [ProgressSyncRequest]. valid for a few seconds
+pub struct SyncTicketRequest;
+
+impl RequestImpl for SyncTicketRequest {
+    const ROUTE: &'static str = "sync/ticket";
+    const AUTH: bool = true;
+}
+
+/// websocket which pushes [SyncMessage]s. browsers cant set headers,
+/// so a ticket of [SyncTicketRequest] is passed as `ticket` query parameter
+pub struct ProgressSyncRequest;
+
+impl RequestImpl for ProgressSyncRequest {
+    const ROUTE: &'static str = "sync/progress";
+    const AUTH: bool = false;
+    const METHOD: &'static str = "GET";
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SyncMessage {
+    /// first message after connecting
+    Hello { session: String },
+    /// progress changed on another device
+    Progress(ProgressUpdate),
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ProgressUpdate {
+    pub manga_id: String,
+    pub chapter_id: String,
+    pub version_id: String,
+    /// 0.0 - 1.0
+    pub progress: f64,
+    pub updated: String,
+}
+
 fn max_f64(items: &Vec<f64>) -> Option<f64> {
     let mut max = None;
     for item in items {
@@ -110,6 +171,7 @@ fn max_f64(items: &Vec<f64>) -> Option<f64> {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ReaderPageRequest {
     pub chapter_version_id: String,
 }
@@ -120,9 +182,12 @@ impl RequestImpl for ReaderPageRequest {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ReaderPageResponse {
     pub version_id: String,
+    /// share of the page height that is padding at the top of every page
     pub hide_top: f64,
+    /// share of the page height that is padding at the bottom of every page
     pub hide_bottom: f64,
     pub pages: HashMap<u32, ReaderPage>,
 }
@@ -146,6 +211,7 @@ impl ReaderPageResponse {
 }
 
 #[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ReaderPage {
     pub page_id: String,
     pub width: u32,
@@ -153,6 +219,8 @@ pub struct ReaderPage {
     pub ext: String,
     pub translation: bool,
     pub progress: Progress,
+    /// content of the page without its padding
+    pub crop: CropRect,
 }
 
 impl ReaderPage {
@@ -169,6 +237,12 @@ impl ReaderPage {
                 height_start: 0.0,
                 height_end: 0.0,
             },
+            crop: CropRect {
+                min_x: 0,
+                min_y: 0,
+                max_x: w,
+                max_y: h,
+            },
         }
     }
     pub fn width(&self, available_height: f32) -> f32 {
@@ -179,7 +253,28 @@ impl ReaderPage {
     }
 }
 
+/// pixel coordinates in the original image
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CropRect {
+    pub min_x: u32,
+    pub min_y: u32,
+    pub max_x: u32,
+    pub max_y: u32,
+}
+
+impl CropRect {
+    pub fn width(&self) -> u32 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> u32 {
+        self.max_y - self.min_y
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Progress {
     pub width_start: f64,
     pub width_end: f64,
@@ -188,7 +283,25 @@ pub struct Progress {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PageTranslationRequest {
+    pub manga_id: String,
+    pub chapter_id: String,
+    pub version_id: String,
+    pub page: u32,
+    /// only returns texts in this language. e.g. "eng"
+    pub language: Option<String>,
+}
+
+impl RequestImpl for PageTranslationRequest {
+    const ROUTE: &'static str = "page_translation";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TranslationArea {
+    /// language => text
     pub translated_text: HashMap<String, String>,
     pub min_x: u32,
     pub min_y: u32,
@@ -197,4 +310,6 @@ pub struct TranslationArea {
     pub text_color: [u8; 3],
     pub outline_color: [u8; 3],
     pub background: String,
+    /// where the text came from. e.g. an ocr backend or manual
+    pub source: String,
 }
@@ -1,6 +1,7 @@
 use serde::Serialize;
 
 #[derive(Clone, Serialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ScrapeAccount {
     pub username: String,
     pub password: String,
@@ -6,6 +6,7 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ExternalSearchRequest {
     pub data: ExternalSearchData,
     pub uri: String,
@@ -28,6 +29,7 @@ impl ExternalSearchRequest {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ExternalSearchData {
     Simple(SimpleSearch),
     String((String, u32)),
@@ -69,6 +71,7 @@ impl RequestImpl for ExternalSearchRequest {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ScrapeSearchResult {
     pub title: String,
     pub url: String,
@@ -110,6 +113,7 @@ impl DisplaySearch for ScrapeSearchResult {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ValidSearch {
     pub sorts: Vec<String>,
     pub tags: Vec<String>,
@@ -152,6 +156,7 @@ impl ValidSearch {
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SimpleSearch {
     pub search: String,
     pub sort: Option<String>,
@@ -184,6 +189,7 @@ impl SimpleSearch {
 }
 
 #[derive(Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ValidSearches {
     String,
     ValidSearch(ValidSearch),
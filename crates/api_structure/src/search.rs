@@ -19,6 +19,7 @@ pub trait DisplaySearch: DeserializeOwned + Send {
 }
 
 #[derive(Deserialize, Serialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SearchResponse {
     pub manga_id: String,
     pub titles: HashMap<String, Vec<String>>,
@@ -59,6 +60,7 @@ impl DisplaySearch for SearchResponse {
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Status {
     Dropped,
     Hiatus,
@@ -99,6 +101,7 @@ impl From<Status> for u64 {
 }
 
 #[derive(Deserialize, Serialize, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SearchRequest {
     pub order: Order,
     pub desc: bool,
@@ -113,6 +116,7 @@ impl RequestImpl for SearchRequest {
 }
 
 #[derive(Deserialize, Serialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Order {
     Created,
     Alphabetical,
@@ -120,10 +124,13 @@ pub enum Order {
     LastRead,
     Popularity,
     Random,
+    /// average score
+    Rating,
 }
 
 /// can contain item or array
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(untagged)]
 pub enum ItemOrArray {
     Item(Item),
@@ -145,6 +152,7 @@ impl Display for ItemOrArray {
 
 /// array joined with and or or
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Array {
     pub or: bool,
     pub items: Vec<ItemOrArray>,
@@ -173,6 +181,7 @@ impl Display for Array {
 
 /// item include or exclude
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Item {
     pub not: bool,
     pub data: ItemData,
@@ -208,6 +217,7 @@ impl Item {
 
 /// field and value
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ItemData {
     pub name: String,
     pub value: ItemValue,
@@ -253,6 +263,7 @@ impl ItemKind {
 
 /// enum with different values
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum ItemValue {
     None,
     Bool(bool),
@@ -0,0 +1,53 @@
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReadingStatsRequest {
+    /// day in the format YYYY-MM-DD (utc). open if `None`
+    pub from: Option<String>,
+    /// day in the format YYYY-MM-DD (utc). open if `None`
+    pub to: Option<String>,
+}
+
+impl RequestImpl for ReadingStatsRequest {
+    const ROUTE: &'static str = "me/stats";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ReadingStats {
+    pub days: Vec<DayStats>,
+    pub total_pages: u32,
+    pub total_chapters: u32,
+    /// pages per hour of the day (utc). always 24 entries
+    pub hours: Vec<u32>,
+    /// tag => pages. sorted by pages
+    pub tags: Vec<(String, u32)>,
+    /// kind => pages. sorted by pages
+    pub kinds: Vec<(String, u32)>,
+    pub completion: Vec<MangaCompletion>,
+    /// days in a row up to today
+    pub current_streak: u32,
+    pub longest_streak: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DayStats {
+    /// YYYY-MM-DD
+    pub day: String,
+    pub pages: u32,
+    pub chapters: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct MangaCompletion {
+    pub manga_id: String,
+    pub titles: HashMap<String, Vec<String>>,
+    pub chapters_read: u32,
+    pub chapters_total: u32,
+}
@@ -0,0 +1,165 @@
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// editable content of a translation box
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TranslationAreaData {
+    /// language => text
+    pub translated_text: HashMap<String, String>,
+    pub min_x: u32,
+    pub min_y: u32,
+    pub max_x: u32,
+    pub max_y: u32,
+    pub text_color: [u8; 3],
+    pub outline_color: [u8; 3],
+    /// base64 image which covers the original text
+    pub background: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EditorTranslationArea {
+    pub area_id: String,
+    pub source: String,
+    pub area: TranslationAreaData,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TranslationAreasRequest {
+    pub page_id: String,
+}
+
+impl RequestImpl for TranslationAreasRequest {
+    const ROUTE: &'static str = "translation/areas";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AddTranslationAreaRequest {
+    pub page_id: String,
+    pub area: TranslationAreaData,
+}
+
+impl RequestImpl for AddTranslationAreaRequest {
+    const ROUTE: &'static str = "translation/area/add";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EditTranslationAreaRequest {
+    pub area_id: String,
+    pub area: TranslationAreaData,
+}
+
+impl RequestImpl for EditTranslationAreaRequest {
+    const ROUTE: &'static str = "translation/area/edit";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DeleteTranslationAreaRequest {
+    pub area_id: String,
+}
+
+impl RequestImpl for DeleteTranslationAreaRequest {
+    const ROUTE: &'static str = "translation/area/delete";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TranslationHistoryRequest {
+    pub page_id: String,
+}
+
+impl RequestImpl for TranslationHistoryRequest {
+    const ROUTE: &'static str = "translation/history";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum TranslationAction {
+    Add,
+    Edit,
+    Delete,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TranslationRevision {
+    pub area_id: String,
+    pub action: TranslationAction,
+    /// username
+    pub editor: String,
+    pub before: Option<TranslationAreaData>,
+    pub after: Option<TranslationAreaData>,
+    pub created: String,
+}
+
+/// sends every page of a chapter version to the translation backend
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TranslateChapterRequest {
+    pub manga_id: String,
+    pub chapter_id: String,
+    pub version_id: String,
+    /// language the backend translates into. e.g. "eng"
+    pub language: String,
+}
+
+impl RequestImpl for TranslateChapterRequest {
+    const ROUTE: &'static str = "translation/queue";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TranslationJobsRequest {
+    pub chapter_id: String,
+}
+
+impl RequestImpl for TranslationJobsRequest {
+    const ROUTE: &'static str = "translation/jobs";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum TranslationJobStatus {
+    Queued = 0,
+    Running = 1,
+    Done = 2,
+    Failed = 3,
+}
+
+impl From<u32> for TranslationJobStatus {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::Queued,
+            1 => Self::Running,
+            2 => Self::Done,
+            _ => Self::Failed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TranslationJob {
+    pub job_id: String,
+    pub version_id: String,
+    pub language: String,
+    pub status: TranslationJobStatus,
+    pub pages_done: u32,
+    pub pages_total: u32,
+    pub error: Option<String>,
+    pub created: String,
+    pub updated: String,
+}
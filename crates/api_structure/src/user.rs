@@ -0,0 +1,86 @@
+use crate::auth::role::Role;
+use crate::RequestImpl;
+use serde::{Deserialize, Serialize};
+
+/// either `user_id` or `name` has to be set
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UserProfileRequest {
+    pub user_id: Option<String>,
+    pub name: Option<String>,
+}
+
+impl RequestImpl for UserProfileRequest {
+    const ROUTE: &'static str = "user/profile";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UserProfile {
+    pub user_id: String,
+    pub names: Vec<String>,
+    pub role: Role,
+    pub bio: Option<String>,
+    pub links: Vec<String>,
+    pub icon_ext: Option<String>,
+    pub banner_ext: Option<String>,
+    pub created: String,
+}
+
+/// fields that are `None` stay unchanged
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EditProfileRequest {
+    /// empty string removes the bio
+    pub bio: Option<String>,
+    pub links: Option<Vec<String>>,
+}
+
+impl RequestImpl for EditProfileRequest {
+    const ROUTE: &'static str = "user/profile/edit";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum UserImageKind {
+    Icon,
+    Banner,
+}
+
+impl UserImageKind {
+    /// folder inside of users
+    pub fn folder(&self) -> &'static str {
+        match self {
+            UserImageKind::Icon => "icon",
+            UserImageKind::Banner => "banner",
+        }
+    }
+}
+
+/// replaces the own icon or banner with an uploaded file
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SetUserImageRequest {
+    pub kind: UserImageKind,
+    /// name returned by upload_images
+    pub temp_name: String,
+}
+
+impl RequestImpl for SetUserImageRequest {
+    const ROUTE: &'static str = "user/image/set";
+    const AUTH: bool = true;
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct UserImageRequest {
+    pub user_id: String,
+    pub kind: UserImageKind,
+}
+
+impl RequestImpl for UserImageRequest {
+    const ROUTE: &'static str = "user/image";
+    const AUTH: bool = true;
+}
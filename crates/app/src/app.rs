@@ -1,13 +1,16 @@
+use crate::data::notifications::listen;
 use crate::fonts::setup_custom_fonts;
 use crate::get_app_data;
 use crate::window_storage::Windows;
 use eframe::Frame;
 use egui::{Context, Margin, Vec2};
+use ethread::ThreadHandler;
 
 #[derive(Default)]
 pub struct TemplateApp {
     windows: Windows,
     init: bool,
+    notifications: Option<ThreadHandler<()>>,
 }
 
 fn init(ctx: &Context) {
@@ -25,6 +28,18 @@ impl eframe::App for TemplateApp {
         if let Some(pages) = get_app_data().change_window() {
             self.windows.dispose_many(pages);
         }
+        // live notification count
+        let logged_in = get_app_data().logged_in();
+        if self
+            .notifications
+            .as_ref()
+            .is_some_and(|v| v.task.ready().is_some() && !logged_in)
+        {
+            self.notifications = None;
+        }
+        if logged_in && self.notifications.is_none() {
+            self.notifications = Some(ThreadHandler::new_async_ctx(listen(ctx.clone()), Some(ctx)));
+        }
         // get page
         let page = get_app_data().page();
         // render page
@@ -122,14 +122,15 @@ impl CoverStorage {
         async move {
             let app = get_app_data();
             let token = format!("Bearer {}", app.get_access_token().await.unwrap());
+            let path = MangaCoverRequest {
+                manga_id: manga_id.clone(),
+                file_ext: ext,
+            }
+            .path();
             let bytes = app
                 .client
-                .post(app.url.join("cover").unwrap())
+                .get(app.url.join(&path).unwrap())
                 .header(AUTHORIZATION, token)
-                .json(&MangaCoverRequest {
-                    manga_id: manga_id.clone(),
-                    file_ext: ext,
-                })
                 .send()
                 .await
                 .ok()?
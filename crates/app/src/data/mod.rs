@@ -1,3 +1,5 @@
 pub mod image;
+pub mod notifications;
+pub mod progress_sync;
 pub mod shared_data;
 pub mod user;
@@ -0,0 +1,66 @@
+use crate::get_app_data;
+use api_structure::notification::{NotificationEvent, NotificationStreamRequest};
+use api_structure::RequestImpl;
+use egui::Context;
+use futures_util::StreamExt;
+use log::warn;
+use reqwest::header::AUTHORIZATION;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+#[cfg(not(target_arch = "wasm32"))]
+const RECONNECT: Duration = Duration::from_secs(10);
+
+/// keeps the unread count up to date. returns after logout.
+/// the web build doesnt reconnect and returns when the stream ends
+pub async fn listen(ctx: Context) {
+    while get_app_data().logged_in() {
+        if let Err(e) = stream(&ctx).await {
+            warn!("Notification stream failed: {}", e);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::time::sleep(RECONNECT).await;
+        #[cfg(target_arch = "wasm32")]
+        break;
+    }
+}
+
+async fn stream(ctx: &Context) -> Result<(), String> {
+    let app = get_app_data();
+    let token = app
+        .get_access_token()
+        .await
+        .ok_or("Not logged in".to_string())?;
+    let url = app
+        .url
+        .join(NotificationStreamRequest::ROUTE)
+        .map_err(|e| e.to_string())?;
+    let resp = app
+        .client
+        .get(url)
+        .header(AUTHORIZATION, format!("Bearer {}", token))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+        return Err(resp.status().to_string());
+    }
+    let mut buffer: Vec<u8> = vec![];
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        buffer.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+        while let Some(end) = buffer.windows(2).position(|v| v == b"\n\n") {
+            let event: Vec<u8> = buffer.drain(..end + 2).collect();
+            for line in String::from_utf8_lossy(&event).lines() {
+                // lines starting with ":" are keep-alive comments
+                if let Some(data) = line.strip_prefix("data: ") {
+                    if let Ok(v) = serde_json::from_str::<NotificationEvent>(data) {
+                        app.set_unread_notifications(v.unread);
+                        ctx.request_repaint();
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
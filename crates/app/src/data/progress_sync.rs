@@ -0,0 +1,117 @@
+use crate::fetcher::Fetcher;
+use crate::get_app_data;
+use api_structure::reader::{
+    ProgressSyncRequest, ProgressUpdate, SetProgressRequest, SyncMessage, SyncTicketRequest,
+};
+use api_structure::RequestImpl;
+use egui::Context;
+use ethread::ThreadHandler;
+use ewebsock::{Options, WsEvent, WsMessage, WsReceiver, WsSender};
+use reqwest::header::AUTHORIZATION;
+
+/// seconds between two reports of the same chapter
+const REPORT_INTERVAL: f64 = 2.0;
+
+/// receives progress changes made on other devices and reports the own progress
+pub struct ProgressSync {
+    connection: ThreadHandler<Option<(WsSender, WsReceiver)>>,
+    /// [SyncMessage::Hello] of the server. own reports aren't pushed back to this session
+    session: Option<String>,
+    report: Option<Fetcher<()>>,
+    /// chapter, progress and time of the last report
+    reported: Option<(String, f64, f64)>,
+}
+
+impl ProgressSync {
+    pub fn connect(ctx: &Context) -> Self {
+        let c = ctx.clone();
+        let connection = ThreadHandler::new_async_ctx(
+            async move {
+                let app = get_app_data();
+                let token = app.get_access_token().await?;
+                // the websocket can't send the token, so it is exchanged for a ticket
+                let ticket: String = app
+                    .client
+                    .post(app.url.join(SyncTicketRequest::ROUTE).ok()?)
+                    .header(AUTHORIZATION, format!("Bearer {}", token))
+                    .send()
+                    .await
+                    .ok()?
+                    .json()
+                    .await
+                    .ok()?;
+                let mut url = app.url.join(ProgressSyncRequest::ROUTE).ok()?;
+                let scheme = match url.scheme() {
+                    "https" => "wss",
+                    _ => "ws",
+                };
+                url.set_scheme(scheme).ok()?;
+                url.query_pairs_mut().append_pair("ticket", &ticket);
+                ewebsock::connect_with_wakeup(url.as_str(), Options::default(), move || {
+                    c.request_repaint()
+                })
+                .ok()
+            },
+            Some(ctx),
+        );
+        Self {
+            connection,
+            session: None,
+            report: None,
+            reported: None,
+        }
+    }
+
+    /// newest change since the last call
+    pub fn poll(&mut self) -> Option<ProgressUpdate> {
+        let (_, receiver) = self.connection.task.ready_mut()?.as_mut()?;
+        let mut last = None;
+        while let Some(event) = receiver.try_recv() {
+            if let WsEvent::Message(WsMessage::Text(text)) = event {
+                match serde_json::from_str(&text) {
+                    Ok(SyncMessage::Hello { session }) => self.session = Some(session),
+                    Ok(SyncMessage::Progress(update)) => last = Some(update),
+                    Err(_) => {}
+                }
+            }
+        }
+        last
+    }
+
+    /// saves the progress on the server. skipped while the last report is pending
+    /// or when the chapter didn't change much since the last one
+    pub fn report(
+        &mut self,
+        ctx: &Context,
+        manga_id: &str,
+        chapter_id: &str,
+        version_id: &str,
+        progress: f64,
+    ) {
+        if self.report.as_mut().is_some_and(|v| v.result().is_none()) {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        if let Some((chapter, last, time)) = &self.reported {
+            if chapter == chapter_id
+                && ((last - progress).abs() < 0.01 || now - time < REPORT_INTERVAL)
+            {
+                return;
+            }
+        }
+        let Ok(request) = SetProgressRequest::request(&get_app_data().url) else {
+            return;
+        };
+        let mut fetcher = Fetcher::new(request);
+        fetcher.set_body(&SetProgressRequest {
+            manga_id: manga_id.to_string(),
+            chapter_id: chapter_id.to_string(),
+            version_id: version_id.to_string(),
+            progress,
+            session: self.session.clone(),
+        });
+        fetcher.send();
+        self.report = Some(fetcher);
+        self.reported = Some((chapter_id.to_string(), progress, now));
+    }
+}
@@ -29,6 +29,7 @@ pub struct SharedData {
     pub lang_hierarchy: Vec<String>,
     pub search: Arc<Mutex<SearchRequest>>,
     pub fonts: Arc<Mutex<Vec<String>>>,
+    unread_notifications: Arc<Mutex<u32>>,
 }
 
 impl SharedData {
@@ -57,8 +58,22 @@ impl SharedData {
         user.as_ref().map(|v| v.user_data.clone())
     }
 
+    /// doesnt redirect to the sign in page
+    pub fn logged_in(&self) -> bool {
+        self.user.lock().unwrap().is_some()
+    }
+
+    pub fn unread_notifications(&self) -> u32 {
+        *self.unread_notifications.lock().unwrap()
+    }
+
+    pub fn set_unread_notifications(&self, unread: u32) {
+        *self.unread_notifications.lock().unwrap() = unread;
+    }
+
     pub fn logout(&self) {
         *self.user.lock().unwrap() = None;
+        self.set_unread_notifications(0);
         User::delete_token().unwrap();
         self.user();
     }
@@ -151,6 +166,7 @@ impl SharedData {
                 }),
             })),
             fonts: Arc::new(Mutex::new(vec![])),
+            unread_notifications: Default::default(),
         }
     }
 
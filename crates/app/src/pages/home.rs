@@ -100,6 +100,7 @@ impl HomePage {
     fn show(&mut self, ui: &mut Ui, data: &HomeResponse) {
         show_top_bar(ui, HomePages::Home);
         ScrollArea::vertical().show(ui, |ui| {
+            show_row(&data.recommended, "Recommended", ui);
             show_row(&data.newest, "Newest", ui);
             show_row(&data.trending, "Trending", ui);
             show_row(&data.reading, "Reading", ui);
@@ -128,6 +129,15 @@ fn show_top_bar(ui: &mut Ui, active: HomePages) {
             }
         }
 
+        let unread = app.unread_notifications();
+        let notifications = match unread {
+            0 => "Notifications".to_string(),
+            _ => format!("Notifications ({})", unread),
+        };
+        if ui.button(notifications).clicked() {
+            app.open(Page::Notifications)
+        }
+
         if add_button && ui.button("Add Manga").clicked() {
             app.open(Page::AddManga)
         }
@@ -159,7 +169,8 @@ fn show_row(item: &[SearchResponse], label: &str, ui: &mut Ui) {
 fn render_row(label: &str, ui: &mut Ui) {
     ui.label(label);
     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-        if ui.add(Button::new("More").sense(Sense::click())).clicked() {
+        // recommendations can't be expressed as a search
+        if label != "Recommended" && ui.add(Button::new("More").sense(Sense::click())).clicked() {
             let (search, order, desc) = match label {
                 "Newest" => (vec![], Order::Created, true),
                 "Trending" => {
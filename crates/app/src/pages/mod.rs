@@ -2,6 +2,7 @@ pub mod auth;
 mod home;
 mod info;
 mod loading_init_refres;
+mod notifications;
 mod playground;
 mod reader;
 mod search;
@@ -9,6 +10,7 @@ mod search;
 pub use home::HomePage;
 pub use info::InfoPage;
 pub use loading_init_refres::LoadingInitRefreshPage;
+pub use notifications::NotificationsPage;
 pub use playground::PlaygroundPage;
 pub use reader::MangaReaderPage;
 pub use search::SearchPage;
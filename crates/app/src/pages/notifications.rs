@@ -0,0 +1,219 @@
+use crate::fetcher::{Complete, Fetcher};
+use crate::get_app_data;
+use crate::window_storage::Page;
+use api_structure::notification::{
+    MarkNotificationsReadRequest, MuteMangaRequest, Notification, NotificationSettings,
+    NotificationSettingsRequest, NotificationsRequest,
+};
+use api_structure::{Request, RequestImpl};
+use eframe::{App, Frame};
+use egui::{Context, Grid, Label, ScrollArea, Sense, Spinner};
+use serde::Serialize;
+
+const LIMIT: u32 = 50;
+
+pub struct NotificationsPage {
+    list: Fetcher<Vec<Notification>>,
+    settings: Fetcher<NotificationSettings>,
+    settings_data: Option<NotificationSettings>,
+    unread_only: bool,
+    page: u32,
+    /// returns the new unread count
+    marking: Vec<Fetcher<u32>>,
+    saving: Vec<Fetcher<()>>,
+    init: bool,
+}
+
+impl NotificationsPage {
+    pub fn new() -> Self {
+        let url = &get_app_data().url;
+        Self {
+            list: Fetcher::new(NotificationsRequest::request(url).unwrap()),
+            settings: Fetcher::new(NotificationSettingsRequest::request(url).unwrap()),
+            settings_data: None,
+            unread_only: false,
+            page: 1,
+            marking: vec![],
+            saving: vec![],
+            init: false,
+        }
+    }
+
+    fn reload(&mut self) {
+        self.list.set_body(NotificationsRequest {
+            unread_only: self.unread_only,
+            limit: LIMIT,
+            page: self.page,
+        });
+        self.list.send();
+    }
+
+    fn mark_read(&mut self, ctx: &Context, ids: Option<Vec<String>>) {
+        let mut fetcher = Fetcher::new_ctx(
+            MarkNotificationsReadRequest::request(&get_app_data().url).unwrap(),
+            ctx.clone(),
+        );
+        fetcher.set_body(MarkNotificationsReadRequest { ids });
+        fetcher.send();
+        self.marking.push(fetcher);
+    }
+
+    fn save(&mut self, ctx: &Context, body: impl Serialize, request: Request) {
+        let mut fetcher = Fetcher::new_ctx(request, ctx.clone());
+        fetcher.set_body(body);
+        fetcher.send();
+        self.saving.push(fetcher);
+    }
+}
+
+enum Action {
+    Open(Notification),
+    Mute(String, bool),
+}
+
+impl App for NotificationsPage {
+    fn update(&mut self, ctx: &Context, _: &mut Frame) {
+        let app = get_app_data();
+        if !self.init {
+            self.init = true;
+            self.list.set_ctx(ctx.clone());
+            self.settings.set_ctx(ctx.clone());
+            self.settings.send();
+            self.reload();
+        }
+        let mut reload = false;
+        self.marking.retain_mut(|v| match v.result() {
+            None => true,
+            Some(res) => {
+                if let Complete::Json(unread) = res {
+                    app.set_unread_notifications(*unread);
+                    reload = true;
+                }
+                false
+            }
+        });
+        self.saving.retain_mut(|v| v.result().is_none());
+        if self.settings_data.is_none() {
+            if let Some(Complete::Json(v)) = self.settings.result() {
+                self.settings_data = Some(v.clone());
+            }
+        }
+
+        let mut action = None;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Back").clicked() {
+                    app.open(Page::Home);
+                }
+                if ui.checkbox(&mut self.unread_only, "Unread only").changed() {
+                    self.page = 1;
+                    reload = true;
+                }
+                if ui.button("Mark all as read").clicked() {
+                    self.mark_read(ctx, None);
+                }
+            });
+            if let Some(settings) = &mut self.settings_data {
+                let mut changed = false;
+                ui.collapsing("Settings", |ui| {
+                    changed |= ui.checkbox(&mut settings.muted, "Mute all").changed();
+                    changed |= ui.checkbox(&mut settings.favorites, "Favorites").changed();
+                    changed |= ui.checkbox(&mut settings.reading, "Reading").changed();
+                });
+                if changed {
+                    let settings = settings.clone();
+                    self.save(
+                        ctx,
+                        settings,
+                        NotificationSettings::request(&app.url).unwrap(),
+                    );
+                }
+            }
+            ui.separator();
+            let muted = self
+                .settings_data
+                .as_ref()
+                .map(|v| v.muted_mangas.clone())
+                .unwrap_or_default();
+            match self.list.result() {
+                None => {
+                    ui.add(Spinner::new());
+                }
+                Some(Complete::Json(items)) => {
+                    if items.is_empty() {
+                        ui.label("No notifications");
+                    }
+                    ScrollArea::vertical().show(ui, |ui| {
+                        Grid::new("notifications")
+                            .num_columns(4)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for item in items {
+                                    let mut title = app.get_title(&item.titles);
+                                    if !item.read {
+                                        title = format!("• {}", title);
+                                    }
+                                    let label = Label::new(title).sense(Sense::click());
+                                    if ui.add(label).clicked() {
+                                        action = Some(Action::Open(item.clone()));
+                                    }
+                                    ui.label(format!("Chapter {}", item.chapter));
+                                    ui.label(&item.created);
+                                    let is_muted = muted.contains(&item.manga_id);
+                                    let text = match is_muted {
+                                        true => "Unmute",
+                                        false => "Mute",
+                                    };
+                                    if ui.button(text).clicked() {
+                                        action =
+                                            Some(Action::Mute(item.manga_id.clone(), !is_muted));
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                    });
+                    ui.horizontal(|ui| {
+                        if self.page > 1 && ui.button("Previous").clicked() {
+                            self.page -= 1;
+                            reload = true;
+                        }
+                        if items.len() as u32 == LIMIT && ui.button("Next").clicked() {
+                            self.page += 1;
+                            reload = true;
+                        }
+                    });
+                }
+                Some(e) => e.display_error(ui),
+            }
+        });
+
+        match action {
+            Some(Action::Open(item)) => {
+                if !item.read {
+                    self.mark_read(ctx, Some(vec![item.notification_id]));
+                }
+                app.open(Page::Reader {
+                    manga_id: item.manga_id,
+                    chapter_id: Some(item.chapter_id),
+                });
+            }
+            Some(Action::Mute(manga_id, muted)) => {
+                if let Some(settings) = &mut self.settings_data {
+                    settings.muted_mangas.retain(|v| v != &manga_id);
+                    if muted {
+                        settings.muted_mangas.push(manga_id.clone());
+                    }
+                }
+                self.save(
+                    ctx,
+                    MuteMangaRequest { manga_id, muted },
+                    MuteMangaRequest::request(&app.url).unwrap(),
+                );
+            }
+            None => {}
+        }
+        if reload {
+            self.reload();
+        }
+    }
+}
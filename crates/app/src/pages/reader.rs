@@ -1,3 +1,4 @@
+use crate::data::progress_sync::ProgressSync;
 use crate::fetcher::{Complete, Fetcher};
 use crate::get_app_data;
 use crate::widgets::reader::load::load_images;
@@ -5,16 +6,24 @@ use crate::widgets::reader::progress::Progress;
 use crate::widgets::reader::render::display_images;
 use crate::widgets::reader::scroll::set_progress;
 use crate::widgets::reader::settings::{ReadingMode, Settings, ViewArea};
-use crate::widgets::reader::storage::{get_page_resp, get_version, State, Storage};
-use api_structure::reader::MangaReaderRequest;
+use crate::widgets::reader::storage::{
+    get_page_resp, get_version, get_version_key, State, Storage,
+};
+use crate::window_storage::Page;
+use api_structure::reader::{MangaReaderRequest, MangaReaderResponse, ProgressUpdate};
 use api_structure::RequestImpl;
 use eframe::{App, Frame};
 use egui::{vec2, Context};
+use std::sync::Arc;
 
 pub struct MangaReaderPage {
     storage: Storage,
     settings: Settings,
     progress: Option<Progress>,
+    manga_id: String,
+    sync: Option<ProgressSync>,
+    /// progress from another device which wasnt applied yet
+    newer: Option<ProgressUpdate>,
     init: bool,
 }
 
@@ -22,7 +31,7 @@ impl MangaReaderPage {
     pub(crate) fn new(manga_id: String, chapter_id: Option<String>) -> Self {
         let mut manga = Fetcher::new(MangaReaderRequest::request(&get_app_data().url).unwrap());
         manga.set_body(MangaReaderRequest {
-            manga_id,
+            manga_id: manga_id.clone(),
             chapter_id,
         });
         manga.send();
@@ -44,9 +53,44 @@ impl MangaReaderPage {
                 },
             },
             progress: None,
+            manga_id,
+            sync: None,
+            newer: None,
             init: false,
         }
     }
+
+    fn show_newer(&mut self, ctx: &Context, manga: Option<&MangaReaderResponse>) {
+        let Some(newer) = &self.newer else {
+            return;
+        };
+        let chapter = manga
+            .and_then(|v| v.chapters.iter().find(|v| v.chapter_id == newer.chapter_id))
+            .map(|v| v.chapter.to_string())
+            .unwrap_or_default();
+        let mut jump = false;
+        let mut dismiss = false;
+        egui::TopBottomPanel::top("progress_sync").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Continue at chapter {} ({:.0}%) from another device?",
+                    chapter,
+                    newer.progress * 100.0
+                ));
+                jump = ui.button("Jump").clicked();
+                dismiss = ui.button("Dismiss").clicked();
+            });
+        });
+        if jump {
+            let page = Page::Reader {
+                manga_id: self.manga_id.clone(),
+                chapter_id: Some(newer.chapter_id.clone()),
+            };
+            get_app_data().change(page.clone(), vec![page]);
+        } else if dismiss {
+            self.newer = None;
+        }
+    }
 }
 
 impl App for MangaReaderPage {
@@ -54,14 +98,25 @@ impl App for MangaReaderPage {
         if !self.init {
             self.init = true;
             self.storage.manga.set_ctx(ctx.clone());
+            self.sync = Some(ProgressSync::connect(ctx));
+        }
+        if let Some(update) = self.sync.as_mut().and_then(|v| v.poll()) {
+            if update.manga_id == self.manga_id {
+                self.newer = Some(update);
+            }
         }
+        let manga: Option<Arc<MangaReaderResponse>> = match self.storage.manga.result() {
+            Some(Complete::Json(v)) => Some(v.clone()),
+            _ => None,
+        };
+        self.show_newer(ctx, manga.as_deref());
         egui::CentralPanel::default().show(ctx, |ui| {
             if let Some(v) = self.storage.manga.result().cloned() {
                 match v {
                     Complete::Json(v) => {
                         let size = self.settings.view_area.get_size(ctx);
                         if let Some(p) = &mut self.progress {
-                            set_progress(
+                            let progress = set_progress(
                                 ui,
                                 &self.settings.reading_mode,
                                 p,
@@ -70,6 +125,14 @@ impl App for MangaReaderPage {
                                 &mut self.storage.page_data,
                                 size,
                             );
+                            let version = v
+                                .get_chapter(&p.chapter)
+                                .and_then(|v| get_version_key(v, &self.settings.version_hierachy));
+                            if let (Some(sync), Some(progress), Some(version)) =
+                                (&mut self.sync, progress, version)
+                            {
+                                sync.report(ctx, &self.manga_id, &p.chapter, &version, progress);
+                            }
                             load_images(
                                 v.clone(),
                                 &self.settings.version_hierachy,
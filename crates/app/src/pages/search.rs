@@ -287,11 +287,14 @@ impl App for SearchPage {
         } else {
             self.internal.move_data_internal(ctx);
             internal = true;
-            parser = Some(vec![Field::new(
-                "title".to_string(),
-                vec![String::new(), "t".to_string()],
-                ItemKind::String,
-            )]);
+            parser = Some(vec![
+                Field::new(
+                    "title".to_string(),
+                    vec![String::new(), "t".to_string()],
+                    ItemKind::String,
+                ),
+                Field::new("rating".to_string(), vec![], ItemKind::CmpFloat),
+            ]);
         }
         egui::CentralPanel::default().show(ctx, |ui| {
             let search = match internal {
@@ -12,7 +12,8 @@ use crate::widgets::reader::storage::{
 };
 use api_structure::image::MangaReaderImageRequest;
 use api_structure::reader::{
-    Action, MangaReaderResponse, ReaderChapter, ReaderPageResponse, TranslationArea,
+    Action, MangaReaderResponse, PageTranslationRequest, ReaderChapter, ReaderPageResponse,
+    TranslationArea,
 };
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
@@ -151,6 +152,13 @@ fn load_image(
                         page: page as u32,
                         file_ext: p.ext.clone(),
                     };
+                    let trans_data = PageTranslationRequest {
+                        manga_id: data.manga_id.clone(),
+                        chapter_id: data.chapter_id.clone(),
+                        version_id: data.version_id.clone(),
+                        page: data.page,
+                        language: None,
+                    };
                     let page_id = p.page_id.clone();
                     let fetch_trans = p.translation;
 
@@ -174,7 +182,7 @@ fn load_image(
                                                     .unwrap(),
                                             )
                                             .header(AUTHORIZATION, &token)
-                                            .json(&data)
+                                            .json(&trans_data)
                                             .send()
                                             .await
                                             .ok()?
@@ -219,9 +227,8 @@ fn load_image(
 
                                 let res = get_app_data()
                                     .client
-                                    .post(get_app_data().url.join("chapter_page").unwrap())
+                                    .get(get_app_data().url.join(&data.path()).unwrap())
                                     .header(AUTHORIZATION, token)
-                                    .json(&data)
                                     .send()
                                     .await
                                     .ok()?
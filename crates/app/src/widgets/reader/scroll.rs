@@ -9,9 +9,7 @@ fn get_scroll_delta(ui: &mut Ui) -> Vec2 {
     ui.input(|i| i.smooth_scroll_delta)
 }
 
-pub fn report_progress(progress: f64) {
-    println!("{progress}")
-}
+/// scrolls. returns the progress of the chapter (0.0 - 1.0) if it changed
 pub fn set_progress(
     ui: &mut Ui,
     rm: &ReadingMode,
@@ -20,12 +18,12 @@ pub fn set_progress(
     hierachy: &[String],
     page_data: &mut PageData,
     area: Vec2,
-) {
+) -> Option<f64> {
     match rm {
         ReadingMode::Strip => {
             let scroll_delta = get_scroll_delta(ui);
             if scroll_delta == Vec2::ZERO {
-                return;
+                return None;
             }
 
             let mut ch = get_page_resp(
@@ -101,11 +99,12 @@ pub fn set_progress(
                 let start = page.progress.height_start;
                 let gap = page.progress.height_end - start;
                 let img_progress = progress.pixels as f64 / page.height(area.x) as f64;
-                report_progress(start + gap * img_progress);
+                return Some(start + gap * img_progress);
             }
+            None
         }
-        ReadingMode::Row(_) => {}
-        ReadingMode::Single => {}
-        ReadingMode::Double(_) => {}
+        ReadingMode::Row(_) => None,
+        ReadingMode::Single => None,
+        ReadingMode::Double(_) => None,
     }
 }
@@ -5,7 +5,8 @@ use crate::pages::auth::sign_up::SignUpPage;
 use crate::pages::auth::sign_up_info::SignUpInfoPage;
 use crate::pages::auth::verify_account::VerifyAccountPage;
 use crate::pages::{
-    HomePage, InfoPage, LoadingInitRefreshPage, MangaReaderPage, PlaygroundPage, SearchPage,
+    HomePage, InfoPage, LoadingInitRefreshPage, MangaReaderPage, NotificationsPage, PlaygroundPage,
+    SearchPage,
 };
 use eframe::App;
 use std::collections::HashSet;
@@ -26,6 +27,7 @@ pub enum Page {
     AddManga,
     You,
     Settings,
+    Notifications,
     Reader {
         manga_id: String,
         chapter_id: Option<String>,
@@ -49,6 +51,7 @@ impl Page {
             Self::AddManga,
             Self::You,
             Self::Settings,
+            Self::Notifications,
             Self::Reader {
                 manga_id: "".to_string(),
                 chapter_id: None,
@@ -73,6 +76,7 @@ pub struct Windows {
     add_manga: Option<()>,
     you: Option<()>,
     settings: Option<()>,
+    notifications: Option<NotificationsPage>,
     reader: Option<MangaReaderPage>,
 }
 
@@ -93,6 +97,7 @@ impl Windows {
             Page::AddManga => self.add_manga = None,
             Page::You => self.you = None,
             Page::Settings => self.settings = None,
+            Page::Notifications => self.notifications = None,
             Page::Reader { .. } => self.reader = None,
         };
     }
@@ -148,6 +153,10 @@ impl Windows {
             Page::AddManga => todo!(),
             Page::You => todo!(),
             Page::Settings => todo!(),
+            Page::Notifications => {
+                self.notifications
+                    .get_or_insert_with(NotificationsPage::new) as &mut dyn App
+            }
             Page::Reader {
                 manga_id,
                 chapter_id,